@@ -78,6 +78,10 @@ extern "C" {
     pub fn ASN1_TIME_free(tm: *mut ASN1_TIME);
     pub fn ASN1_TIME_print(b: *mut BIO, tm: *const ASN1_TIME) -> c_int;
     pub fn ASN1_TIME_set(from: *mut ASN1_TIME, to: time_t) -> *mut ASN1_TIME;
+    pub fn ASN1_TIME_to_generalizedtime(
+        t: *const ASN1_TIME,
+        out: *mut *mut ASN1_GENERALIZEDTIME,
+    ) -> *mut ASN1_GENERALIZEDTIME;
 
     pub fn ASN1_INTEGER_free(x: *mut ASN1_INTEGER);
     pub fn ASN1_INTEGER_get(dest: *const ASN1_INTEGER) -> c_long;
@@ -85,6 +89,11 @@ extern "C" {
     pub fn BN_to_ASN1_INTEGER(bn: *const BIGNUM, ai: *mut ASN1_INTEGER) -> *mut ASN1_INTEGER;
     pub fn ASN1_INTEGER_to_BN(ai: *const ASN1_INTEGER, bn: *mut BIGNUM) -> *mut BIGNUM;
 
+    pub fn ASN1_ENUMERATED_new() -> *mut ASN1_ENUMERATED;
+    pub fn ASN1_ENUMERATED_free(x: *mut ASN1_ENUMERATED);
+    pub fn ASN1_ENUMERATED_get(a: *const ASN1_ENUMERATED) -> c_long;
+    pub fn ASN1_ENUMERATED_set(a: *mut ASN1_ENUMERATED, v: c_long) -> c_int;
+
     pub fn ASN1_TIME_set_string(s: *mut ASN1_TIME, str: *const c_char) -> c_int;
     #[cfg(ossl111)]
     pub fn ASN1_TIME_set_string_X509(s: *mut ASN1_TIME, str: *const c_char) -> c_int;
@@ -92,6 +101,8 @@ extern "C" {
 
 const_ptr_api! {
     extern "C" {
+        pub fn ASN1_STRING_type(x: #[const_ptr_if(ossl110)] ASN1_STRING) -> c_int;
+        pub fn ASN1_TYPE_get(a: #[const_ptr_if(ossl110)] ASN1_TYPE) -> c_int;
         pub fn i2d_ASN1_INTEGER(x: #[const_ptr_if(ossl300)] ASN1_INTEGER, buf: *mut *mut u8) -> c_int;
 
         pub fn ASN1_STRING_to_UTF8(out: *mut *mut c_uchar, s: #[const_ptr_if(any(ossl110, libressl280))] ASN1_STRING) -> c_int;
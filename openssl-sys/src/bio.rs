@@ -67,6 +67,7 @@ const_ptr_api! {
     }
 }
 extern "C" {
+    pub fn BIO_new_file(filename: *const c_char, mode: *const c_char) -> *mut BIO;
     #[cfg(not(osslconf = "OPENSSL_NO_STDIO"))]
     pub fn BIO_new_fp(stream: *mut FILE, close_flag: c_int) -> *mut BIO;
     #[cfg(any(ossl110, libressl273))]
@@ -84,6 +85,7 @@ extern "C" {
 const_ptr_api! {
     extern "C" {
         pub fn BIO_s_mem() -> #[const_ptr_if(any(ossl110, libressl280))] BIO_METHOD;
+        pub fn BIO_s_null() -> #[const_ptr_if(any(ossl110, libressl280))] BIO_METHOD;
         pub fn BIO_new_mem_buf(buf: #[const_ptr_if(any(ossl102, libressl280))] c_void, len: c_int) -> *mut BIO;
     }
 }
@@ -3,6 +3,12 @@ use *;
 
 pub enum CMS_ContentInfo {}
 
+pub enum CMS_SignerInfo {}
+
+pub enum CMS_RecipientInfo {}
+
+stack!(stack_st_CMS_SignerInfo);
+
 extern "C" {
     #[cfg(ossl101)]
     pub fn CMS_ContentInfo_free(cms: *mut ::CMS_ContentInfo);
@@ -117,3 +123,169 @@ extern "C" {
 
     pub fn CMS_get1_crls(cms: *mut ::CMS_ContentInfo) -> *mut ::stack_st_X509_CRL;
 }
+
+extern "C" {
+    #[cfg(ossl101)]
+    pub fn CMS_dataInit(cms: *mut ::CMS_ContentInfo, icont: *mut ::BIO) -> *mut ::BIO;
+    #[cfg(ossl101)]
+    pub fn CMS_dataFinal(cms: *mut ::CMS_ContentInfo, bio: *mut ::BIO) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_add1_cert(cms: *mut ::CMS_ContentInfo, cert: *mut ::X509) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_add1_signer(
+        cms: *mut ::CMS_ContentInfo,
+        signer: *mut ::X509,
+        pk: *mut ::EVP_PKEY,
+        md: *const ::EVP_MD,
+        flags: c_uint,
+    ) -> *mut ::CMS_SignerInfo;
+    #[cfg(ossl101)]
+    pub fn CMS_get0_SignerInfos(cms: *mut ::CMS_ContentInfo) -> *mut stack_st_CMS_SignerInfo;
+    #[cfg(ossl101)]
+    pub fn CMS_set1_signers_certs(
+        cms: *mut ::CMS_ContentInfo,
+        certs: *mut ::stack_st_X509,
+        flags: c_uint,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_SignerInfo_cert_cmp(si: *mut ::CMS_SignerInfo, cert: *mut ::X509) -> c_int;
+    #[cfg(ossl101)]
+    pub fn CMS_SignerInfo_get0_algs(
+        si: *mut ::CMS_SignerInfo,
+        pk: *mut *mut ::EVP_PKEY,
+        signer: *mut *mut ::X509,
+        pdig: *mut *mut ::X509_ALGOR,
+        psig: *mut *mut ::X509_ALGOR,
+    );
+    #[cfg(ossl110)]
+    pub fn CMS_SignerInfo_get0_signature(si: *mut ::CMS_SignerInfo) -> *mut ::ASN1_OCTET_STRING;
+    #[cfg(ossl101)]
+    pub fn CMS_SignerInfo_sign(si: *mut ::CMS_SignerInfo) -> c_int;
+    #[cfg(ossl101)]
+    pub fn CMS_SignerInfo_verify(si: *mut ::CMS_SignerInfo) -> c_int;
+    #[cfg(ossl101)]
+    pub fn CMS_SignerInfo_verify_content(si: *mut ::CMS_SignerInfo, chain: *mut ::BIO) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_signed_get_attr_count(si: *const ::CMS_SignerInfo) -> c_int;
+    #[cfg(ossl101)]
+    pub fn CMS_signed_get_attr_by_NID(
+        si: *const ::CMS_SignerInfo,
+        nid: c_int,
+        lastpos: c_int,
+    ) -> c_int;
+    #[cfg(ossl101)]
+    pub fn CMS_signed_get_attr(si: *const ::CMS_SignerInfo, loc: c_int) -> *mut ::X509_ATTRIBUTE;
+    #[cfg(ossl101)]
+    pub fn CMS_signed_add1_attr_by_OBJ(
+        si: *mut ::CMS_SignerInfo,
+        obj: *const ::ASN1_OBJECT,
+        type_: c_int,
+        bytes: *const c_void,
+        len: c_int,
+    ) -> c_int;
+    #[cfg(ossl101)]
+    pub fn CMS_signed_add1_attr_by_NID(
+        si: *mut ::CMS_SignerInfo,
+        nid: c_int,
+        type_: c_int,
+        bytes: *const c_void,
+        len: c_int,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_unsigned_get_attr_by_NID(
+        si: *const ::CMS_SignerInfo,
+        nid: c_int,
+        lastpos: c_int,
+    ) -> c_int;
+    #[cfg(ossl101)]
+    pub fn CMS_unsigned_get_attr(si: *const ::CMS_SignerInfo, loc: c_int) -> *mut ::X509_ATTRIBUTE;
+    #[cfg(ossl101)]
+    pub fn CMS_unsigned_add1_attr_by_OBJ(
+        si: *mut ::CMS_SignerInfo,
+        obj: *const ::ASN1_OBJECT,
+        type_: c_int,
+        bytes: *const c_void,
+        len: c_int,
+    ) -> c_int;
+    #[cfg(ossl101)]
+    pub fn CMS_unsigned_add1_attr_by_NID(
+        si: *mut ::CMS_SignerInfo,
+        nid: c_int,
+        type_: c_int,
+        bytes: *const c_void,
+        len: c_int,
+    ) -> c_int;
+}
+
+extern "C" {
+    #[cfg(ossl101)]
+    pub fn CMS_final(
+        cms: *mut ::CMS_ContentInfo,
+        data: *mut ::BIO,
+        dcont: *mut ::BIO,
+        flags: c_uint,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_set_detached(cms: *mut ::CMS_ContentInfo, detached: c_int) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_EnvelopedData_create(cipher: *const ::EVP_CIPHER) -> *mut ::CMS_ContentInfo;
+    #[cfg(ossl300)]
+    pub fn CMS_AuthEnvelopedData_create(cipher: *const ::EVP_CIPHER) -> *mut ::CMS_ContentInfo;
+
+    #[cfg(ossl101)]
+    pub fn CMS_add1_recipient_cert(
+        cms: *mut ::CMS_ContentInfo,
+        recip: *mut ::X509,
+        flags: c_uint,
+    ) -> *mut ::CMS_RecipientInfo;
+    #[cfg(ossl101)]
+    pub fn CMS_add0_recipient_key(
+        cms: *mut ::CMS_ContentInfo,
+        nid: c_int,
+        key: *mut c_uchar,
+        keylen: size_t,
+        id: *mut c_uchar,
+        idlen: size_t,
+        date: *mut ::ASN1_GENERALIZEDTIME,
+        otherTypeId: *mut ::ASN1_OBJECT,
+        otherType: *mut ::ASN1_TYPE,
+    ) -> *mut ::CMS_RecipientInfo;
+    #[cfg(ossl101)]
+    pub fn CMS_add0_recipient_password(
+        cms: *mut ::CMS_ContentInfo,
+        iter: c_int,
+        wrap_nid: c_int,
+        pbe_nid: c_int,
+        pass: *mut c_uchar,
+        passlen: ssize_t,
+        kekciph: *const ::EVP_CIPHER,
+    ) -> *mut ::CMS_RecipientInfo;
+    #[cfg(ossl101)]
+    pub fn CMS_RecipientInfo_set0_password(
+        ri: *mut ::CMS_RecipientInfo,
+        pass: *mut c_uchar,
+        passlen: ssize_t,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_decrypt_set1_key(
+        cms: *mut ::CMS_ContentInfo,
+        key: *mut c_uchar,
+        keylen: size_t,
+        id: *const c_uchar,
+        idlen: size_t,
+    ) -> c_int;
+    #[cfg(ossl101)]
+    pub fn CMS_decrypt_set1_password(
+        cms: *mut ::CMS_ContentInfo,
+        pass: *mut c_uchar,
+        passlen: ssize_t,
+    ) -> c_int;
+}
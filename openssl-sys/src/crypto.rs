@@ -17,6 +17,8 @@ cfg_if! {
     if #[cfg(ossl110)] {
         pub const CRYPTO_EX_INDEX_SSL: c_int = 0;
         pub const CRYPTO_EX_INDEX_SSL_CTX: c_int = 1;
+        pub const CRYPTO_EX_INDEX_X509_STORE: c_int = 4;
+        pub const CRYPTO_EX_INDEX_X509_STORE_CTX: c_int = 5;
     } else if #[cfg(libressl)] {
         pub const CRYPTO_EX_INDEX_SSL: c_int = 1;
         pub const CRYPTO_EX_INDEX_SSL_CTX: c_int = 2;
@@ -127,4 +129,11 @@ extern "C" {
     pub fn FIPS_mode_set(onoff: c_int) -> c_int;
 
     pub fn CRYPTO_memcmp(a: *const c_void, b: *const c_void, len: size_t) -> c_int;
+
+    #[cfg(ossl300)]
+    pub fn OSSL_LIB_CTX_new() -> *mut OSSL_LIB_CTX;
+    #[cfg(ossl300)]
+    pub fn OSSL_LIB_CTX_load_config(ctx: *mut OSSL_LIB_CTX, config_file: *const c_char) -> c_int;
+    #[cfg(ossl300)]
+    pub fn OSSL_LIB_CTX_free(ctx: *mut OSSL_LIB_CTX);
 }
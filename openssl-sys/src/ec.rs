@@ -17,6 +17,40 @@ pub enum EC_POINT {}
 
 pub const OPENSSL_EC_NAMED_CURVE: c_int = 1;
 
+pub const EVP_PKEY_CTRL_EC_PARAMGEN_CURVE_NID: c_int = EVP_PKEY_ALG_CTRL + 1;
+pub const EVP_PKEY_CTRL_EC_PARAM_ENC: c_int = EVP_PKEY_ALG_CTRL + 2;
+
+cfg_if! {
+    if #[cfg(ossl300)] {
+        extern "C" {
+            pub fn EVP_PKEY_CTX_set_ec_paramgen_curve_nid(ctx: *mut EVP_PKEY_CTX, nid: c_int) -> c_int;
+            pub fn EVP_PKEY_CTX_set_ec_param_enc(ctx: *mut EVP_PKEY_CTX, param_enc: c_int) -> c_int;
+        }
+    } else {
+        pub unsafe fn EVP_PKEY_CTX_set_ec_paramgen_curve_nid(ctx: *mut EVP_PKEY_CTX, nid: c_int) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                EVP_PKEY_EC,
+                EVP_PKEY_OP_PARAMGEN | EVP_PKEY_OP_KEYGEN,
+                EVP_PKEY_CTRL_EC_PARAMGEN_CURVE_NID,
+                nid,
+                ptr::null_mut(),
+            )
+        }
+
+        pub unsafe fn EVP_PKEY_CTX_set_ec_param_enc(ctx: *mut EVP_PKEY_CTX, param_enc: c_int) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                EVP_PKEY_EC,
+                EVP_PKEY_OP_PARAMGEN | EVP_PKEY_OP_KEYGEN,
+                EVP_PKEY_CTRL_EC_PARAM_ENC,
+                param_enc,
+                ptr::null_mut(),
+            )
+        }
+    }
+}
+
 extern "C" {
     #[cfg(not(osslconf = "OPENSSL_NO_EC2M"))]
     pub fn EC_GF2m_simple_method() -> *const EC_METHOD;
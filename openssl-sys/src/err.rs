@@ -4,8 +4,12 @@ pub const ERR_TXT_MALLOCED: c_int = 0x01;
 pub const ERR_TXT_STRING: c_int = 0x02;
 
 pub const ERR_LIB_SYS: c_int = 2;
+pub const ERR_LIB_EVP: c_int = 6;
 pub const ERR_LIB_PEM: c_int = 9;
+pub const ERR_LIB_X509: c_int = 11;
 pub const ERR_LIB_ASN1: c_int = 13;
+pub const ERR_LIB_X509V3: c_int = 34;
+pub const ERR_LIB_PKCS12: c_int = 35;
 
 cfg_if! {
     if #[cfg(ossl300)] {
@@ -107,6 +111,7 @@ extern "C" {
         data: *mut *const c_char,
         flags: *mut c_int,
     ) -> c_ulong;
+    pub fn ERR_peek_error() -> c_ulong;
     pub fn ERR_peek_last_error() -> c_ulong;
     pub fn ERR_clear_error();
     pub fn ERR_lib_error_string(err: c_ulong) -> *const c_char;
@@ -0,0 +1,165 @@
+use libc::*;
+
+use *;
+
+pub const EVP_PKEY_CTRL_TLS_MD: c_int = EVP_PKEY_ALG_CTRL;
+pub const EVP_PKEY_CTRL_TLS_SECRET: c_int = EVP_PKEY_ALG_CTRL + 1;
+pub const EVP_PKEY_CTRL_TLS_SEED: c_int = EVP_PKEY_ALG_CTRL + 2;
+pub const EVP_PKEY_CTRL_HKDF_MD: c_int = EVP_PKEY_ALG_CTRL + 3;
+pub const EVP_PKEY_CTRL_HKDF_SALT: c_int = EVP_PKEY_ALG_CTRL + 4;
+pub const EVP_PKEY_CTRL_HKDF_KEY: c_int = EVP_PKEY_ALG_CTRL + 5;
+pub const EVP_PKEY_CTRL_HKDF_INFO: c_int = EVP_PKEY_ALG_CTRL + 6;
+#[cfg(ossl111)]
+pub const EVP_PKEY_CTRL_HKDF_MODE: c_int = EVP_PKEY_ALG_CTRL + 7;
+
+#[cfg(ossl111)]
+pub const EVP_PKEY_HKDEF_MODE_EXTRACT_AND_EXPAND: c_int = 0;
+#[cfg(ossl111)]
+pub const EVP_PKEY_HKDEF_MODE_EXTRACT_ONLY: c_int = 1;
+#[cfg(ossl111)]
+pub const EVP_PKEY_HKDEF_MODE_EXPAND_ONLY: c_int = 2;
+
+cfg_if! {
+    if #[cfg(ossl300)] {
+        extern "C" {
+            pub fn EVP_PKEY_CTX_set_tls1_prf_md(ctx: *mut EVP_PKEY_CTX, md: *const EVP_MD) -> c_int;
+            pub fn EVP_PKEY_CTX_set1_tls1_prf_secret(
+                ctx: *mut EVP_PKEY_CTX,
+                sec: *const c_uchar,
+                seclen: c_int,
+            ) -> c_int;
+            pub fn EVP_PKEY_CTX_add1_tls1_prf_seed(
+                ctx: *mut EVP_PKEY_CTX,
+                seed: *const c_uchar,
+                seedlen: c_int,
+            ) -> c_int;
+
+            pub fn EVP_PKEY_CTX_set_hkdf_md(ctx: *mut EVP_PKEY_CTX, md: *const EVP_MD) -> c_int;
+            pub fn EVP_PKEY_CTX_set1_hkdf_salt(
+                ctx: *mut EVP_PKEY_CTX,
+                salt: *const c_uchar,
+                saltlen: c_int,
+            ) -> c_int;
+            pub fn EVP_PKEY_CTX_set1_hkdf_key(
+                ctx: *mut EVP_PKEY_CTX,
+                key: *const c_uchar,
+                keylen: c_int,
+            ) -> c_int;
+            pub fn EVP_PKEY_CTX_add1_hkdf_info(
+                ctx: *mut EVP_PKEY_CTX,
+                info: *const c_uchar,
+                infolen: c_int,
+            ) -> c_int;
+            pub fn EVP_PKEY_CTX_set_hkdf_mode(ctx: *mut EVP_PKEY_CTX, mode: c_int) -> c_int;
+        }
+    } else if #[cfg(ossl110)] {
+        pub unsafe fn EVP_PKEY_CTX_set_tls1_prf_md(ctx: *mut EVP_PKEY_CTX, md: *const EVP_MD) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                -1,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_TLS_MD,
+                0,
+                md as *mut c_void,
+            )
+        }
+
+        pub unsafe fn EVP_PKEY_CTX_set1_tls1_prf_secret(
+            ctx: *mut EVP_PKEY_CTX,
+            sec: *const c_uchar,
+            seclen: c_int,
+        ) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                -1,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_TLS_SECRET,
+                seclen,
+                sec as *mut c_void,
+            )
+        }
+
+        pub unsafe fn EVP_PKEY_CTX_add1_tls1_prf_seed(
+            ctx: *mut EVP_PKEY_CTX,
+            seed: *const c_uchar,
+            seedlen: c_int,
+        ) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                -1,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_TLS_SEED,
+                seedlen,
+                seed as *mut c_void,
+            )
+        }
+
+        pub unsafe fn EVP_PKEY_CTX_set_hkdf_md(ctx: *mut EVP_PKEY_CTX, md: *const EVP_MD) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                -1,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_HKDF_MD,
+                0,
+                md as *mut c_void,
+            )
+        }
+
+        pub unsafe fn EVP_PKEY_CTX_set1_hkdf_salt(
+            ctx: *mut EVP_PKEY_CTX,
+            salt: *const c_uchar,
+            saltlen: c_int,
+        ) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                -1,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_HKDF_SALT,
+                saltlen,
+                salt as *mut c_void,
+            )
+        }
+
+        pub unsafe fn EVP_PKEY_CTX_set1_hkdf_key(
+            ctx: *mut EVP_PKEY_CTX,
+            key: *const c_uchar,
+            keylen: c_int,
+        ) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                -1,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_HKDF_KEY,
+                keylen,
+                key as *mut c_void,
+            )
+        }
+
+        pub unsafe fn EVP_PKEY_CTX_add1_hkdf_info(
+            ctx: *mut EVP_PKEY_CTX,
+            info: *const c_uchar,
+            infolen: c_int,
+        ) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                -1,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_HKDF_INFO,
+                infolen,
+                info as *mut c_void,
+            )
+        }
+
+        #[cfg(ossl111)]
+        pub unsafe fn EVP_PKEY_CTX_set_hkdf_mode(ctx: *mut EVP_PKEY_CTX, mode: c_int) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                -1,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_HKDF_MODE,
+                mode,
+                std::ptr::null_mut(),
+            )
+        }
+    }
+}
@@ -31,13 +31,16 @@ pub use engine::*;
 pub use err::*;
 pub use evp::*;
 pub use hmac::*;
+pub use kdf::*;
 pub use obj_mac::*;
 pub use object::*;
 pub use ocsp::*;
 pub use ossl_typ::*;
+pub use params::*;
 pub use pem::*;
 pub use pkcs12::*;
 pub use pkcs7::*;
+pub use provider::*;
 pub use rand::*;
 pub use rsa::*;
 pub use safestack::*;
@@ -70,13 +73,16 @@ mod engine;
 mod err;
 mod evp;
 mod hmac;
+mod kdf;
 mod obj_mac;
 mod object;
 mod ocsp;
 mod ossl_typ;
+mod params;
 mod pem;
 mod pkcs12;
 mod pkcs7;
+mod provider;
 mod rand;
 mod rsa;
 mod safestack;
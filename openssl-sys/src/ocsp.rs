@@ -44,6 +44,8 @@ pub const V_OCSP_CERTSTATUS_UNKNOWN: c_int = 2;
 
 pub enum OCSP_BASICRESP {}
 
+pub enum OCSP_SINGLERESP {}
+
 const_ptr_api! {
     extern "C" {
         pub fn OCSP_cert_to_id(
@@ -56,6 +58,43 @@ const_ptr_api! {
 
 extern "C" {
     pub fn OCSP_request_add0_id(r: *mut OCSP_REQUEST, id: *mut OCSP_CERTID) -> *mut OCSP_ONEREQ;
+    pub fn OCSP_request_onereq_count(req: *mut OCSP_REQUEST) -> c_int;
+    pub fn OCSP_request_onereq_get0(req: *mut OCSP_REQUEST, i: c_int) -> *mut OCSP_ONEREQ;
+    pub fn OCSP_onereq_get0_id(one: *mut OCSP_ONEREQ) -> *mut OCSP_CERTID;
+    pub fn OCSP_id_get0_info(
+        piNameHash: *mut *mut ASN1_OCTET_STRING,
+        pmd: *mut *mut ASN1_OBJECT,
+        pikeyHash: *mut *mut ASN1_OCTET_STRING,
+        pserial: *mut *mut ASN1_INTEGER,
+        cid: *mut OCSP_CERTID,
+    ) -> c_int;
+
+    pub fn OCSP_request_add1_nonce(req: *mut OCSP_REQUEST, val: *mut c_uchar, len: c_int) -> c_int;
+    pub fn OCSP_copy_nonce(resp: *mut OCSP_BASICRESP, req: *mut OCSP_REQUEST) -> c_int;
+    pub fn OCSP_REQUEST_get1_ext_d2i(
+        x: *mut OCSP_REQUEST,
+        nid: c_int,
+        crit: *mut c_int,
+        idx: *mut c_int,
+    ) -> *mut c_void;
+
+    pub fn OCSP_basic_add1_status(
+        rsp: *mut OCSP_BASICRESP,
+        cid: *mut OCSP_CERTID,
+        status: c_int,
+        reason: c_int,
+        revtime: *mut ASN1_TIME,
+        thisupd: *mut ASN1_TIME,
+        nextupd: *mut ASN1_TIME,
+    ) -> *mut OCSP_SINGLERESP;
+    pub fn OCSP_basic_sign(
+        brsp: *mut OCSP_BASICRESP,
+        signer: *mut X509,
+        key: *mut EVP_PKEY,
+        dgst: *const EVP_MD,
+        certs: *mut stack_st_X509,
+        flags: c_ulong,
+    ) -> c_int;
 
     pub fn OCSP_resp_find_status(
         bs: *mut OCSP_BASICRESP,
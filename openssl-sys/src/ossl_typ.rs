@@ -11,6 +11,8 @@ pub enum ASN1_TIME {}
 pub enum ASN1_TYPE {}
 pub enum ASN1_OBJECT {}
 pub enum ASN1_OCTET_STRING {}
+pub enum ASN1_ENUMERATED {}
+pub enum ASN1_PCTX {}
 
 pub enum bio_st {} // FIXME remove
 cfg_if! {
@@ -129,6 +131,21 @@ pub enum EVP_PKEY_ASN1_METHOD {}
 
 pub enum EVP_PKEY_CTX {}
 
+#[cfg(ossl300)]
+pub enum EVP_MAC {}
+#[cfg(ossl300)]
+pub enum EVP_MAC_CTX {}
+
+#[cfg(ossl300)]
+#[repr(C)]
+pub struct OSSL_PARAM {
+    pub key: *const c_char,
+    pub data_type: c_uint,
+    pub data: *mut c_void,
+    pub data_size: size_t,
+    pub return_size: size_t,
+}
+
 cfg_if! {
     if #[cfg(any(ossl110, libressl280))] {
         pub enum HMAC_CTX {}
@@ -54,6 +54,16 @@ const_ptr_api! {
             callback: pem_password_cb,
             user_data: *mut c_void,
         ) -> c_int;
+        #[cfg(ossl110)]
+        pub fn PEM_write_bio_PrivateKey_traditional(
+            bio: *mut BIO,
+            pkey: #[const_ptr_if(ossl300)] EVP_PKEY,
+            cipher: *const EVP_CIPHER,
+            kstr: #[const_ptr_if(ossl300)] c_uchar,
+            klen: c_int,
+            callback: pem_password_cb,
+            user_data: *mut c_void,
+        ) -> c_int;
         pub fn PEM_write_bio_PUBKEY(bp: *mut BIO, x: #[const_ptr_if(ossl300)] EVP_PKEY) -> c_int;
         pub fn PEM_write_bio_PKCS8PrivateKey(
             bio: *mut BIO,
@@ -65,6 +75,7 @@ const_ptr_api! {
             user_data: *mut c_void,
         ) -> c_int;
         pub fn PEM_write_bio_PKCS7(bp: *mut BIO, x: #[const_ptr_if(ossl300)] PKCS7) -> c_int;
+        pub fn PEM_write_bio_PKCS8(bp: *mut BIO, x: #[const_ptr_if(ossl300)] X509_SIG) -> c_int;
         pub fn PEM_write_bio_EC_PUBKEY(bp: *mut BIO, ec: #[const_ptr_if(ossl300)] EC_KEY) -> c_int;
         pub fn i2d_PKCS8PrivateKey_bio(
             bp: *mut BIO,
@@ -97,6 +108,12 @@ extern "C" {
         callback: pem_password_cb,
         user_data: *mut c_void,
     ) -> *mut X509_CRL;
+    pub fn PEM_X509_INFO_read_bio(
+        bio: *mut BIO,
+        sk: *mut stack_st_X509_INFO,
+        callback: pem_password_cb,
+        user_data: *mut c_void,
+    ) -> *mut stack_st_X509_INFO;
     pub fn PEM_read_bio_RSAPrivateKey(
         bio: *mut BIO,
         rsa: *mut *mut RSA,
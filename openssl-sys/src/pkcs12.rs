@@ -3,6 +3,12 @@ use libc::*;
 use *;
 
 pub enum PKCS12 {}
+pub enum PKCS12_SAFEBAG {}
+
+stack!(stack_st_PKCS12_SAFEBAG);
+
+pub const PKCS12_R_MAC_VERIFY_FAILURE: c_int = 113;
+pub const PKCS12_R_CONTENT_TYPE_NOT_DATA: c_int = 121;
 
 extern "C" {
     pub fn PKCS12_free(p12: *mut PKCS12);
@@ -13,6 +19,24 @@ const_ptr_api! {
     }
 }
 extern "C" {
+    pub fn PKCS8_encrypt(
+        pbe_nid: c_int,
+        cipher: *const EVP_CIPHER,
+        pass: *const c_char,
+        passlen: c_int,
+        salt: *mut c_uchar,
+        saltlen: c_int,
+        iter: c_int,
+        p8: *mut PKCS8_PRIV_KEY_INFO,
+    ) -> *mut X509_SIG;
+    #[cfg(ossl110)]
+    pub fn PKCS8_set0_pbe(
+        pass: *const c_char,
+        passlen: c_int,
+        p8inf: *mut PKCS8_PRIV_KEY_INFO,
+        pbe: *mut X509_ALGOR,
+    ) -> *mut X509_SIG;
+
     pub fn d2i_PKCS12(a: *mut *mut PKCS12, pp: *mut *const u8, length: c_long) -> *mut PKCS12;
 
     pub fn PKCS12_parse(
@@ -41,3 +65,91 @@ const_ptr_api! {
         pub fn i2d_PKCS12_bio(b: *mut BIO, a: #[const_ptr_if(ossl300)] PKCS12) -> c_int;
     }
 }
+
+extern "C" {
+    pub fn PKCS12_SAFEBAG_free(bag: *mut PKCS12_SAFEBAG);
+    #[cfg(ossl110)]
+    pub fn PKCS12_SAFEBAG_get_nid(bag: *const PKCS12_SAFEBAG) -> c_int;
+    #[cfg(ossl110)]
+    pub fn PKCS12_SAFEBAG_get1_cert(bag: *const PKCS12_SAFEBAG) -> *mut X509;
+    #[cfg(ossl110)]
+    pub fn PKCS12_SAFEBAG_get0_p8inf(bag: *const PKCS12_SAFEBAG) -> *const PKCS8_PRIV_KEY_INFO;
+    #[cfg(ossl110)]
+    pub fn PKCS12_SAFEBAG_get0_safes(bag: *const PKCS12_SAFEBAG) -> *const stack_st_PKCS12_SAFEBAG;
+    #[cfg(ossl110)]
+    pub fn PKCS12_SAFEBAG_get0_attrs(bag: *const PKCS12_SAFEBAG) -> *const stack_st_X509_ATTRIBUTE;
+    pub fn PKCS12_get_friendlyname(bag: *mut PKCS12_SAFEBAG) -> *mut c_char;
+    #[cfg(ossl110)]
+    pub fn PKCS12_decrypt_skey(
+        bag: *const PKCS12_SAFEBAG,
+        pass: *const c_char,
+        passlen: c_int,
+    ) -> *mut PKCS8_PRIV_KEY_INFO;
+
+    #[cfg(ossl110)]
+    pub fn PKCS12_unpack_authsafes(p12: *const PKCS12) -> *mut stack_st_PKCS7;
+    pub fn PKCS12_unpack_p7data(p7: *mut PKCS7) -> *mut stack_st_PKCS12_SAFEBAG;
+    #[cfg(ossl110)]
+    pub fn PKCS12_unpack_p7encdata(
+        p7: *mut PKCS7,
+        pass: *const c_char,
+        passlen: c_int,
+    ) -> *mut stack_st_PKCS12_SAFEBAG;
+
+    pub fn PKCS12_add_cert(
+        pbags: *mut *mut stack_st_PKCS12_SAFEBAG,
+        cert: *mut X509,
+    ) -> *mut PKCS12_SAFEBAG;
+    #[cfg(ossl110)]
+    pub fn PKCS12_add_key(
+        pbags: *mut *mut stack_st_PKCS12_SAFEBAG,
+        key: *mut EVP_PKEY,
+        key_usage: c_int,
+        iter: c_int,
+        key_nid: c_int,
+        pass: *const c_char,
+    ) -> *mut PKCS12_SAFEBAG;
+    #[cfg(ossl110)]
+    pub fn PKCS12_add_safe(
+        psafes: *mut *mut stack_st_PKCS7,
+        bags: *mut stack_st_PKCS12_SAFEBAG,
+        safe_nid: c_int,
+        iter: c_int,
+        pass: *const c_char,
+    ) -> c_int;
+    pub fn PKCS12_add_safes(safes: *mut stack_st_PKCS7, p7_nid: c_int) -> *mut PKCS12;
+    pub fn PKCS12_add_localkeyid(
+        bag: *mut PKCS12_SAFEBAG,
+        name: *mut c_uchar,
+        namelen: c_int,
+    ) -> c_int;
+    #[cfg(ossl110)]
+    pub fn PKCS12_add_friendlyname_utf8(
+        bag: *mut PKCS12_SAFEBAG,
+        name: *const c_char,
+        namelen: c_int,
+    ) -> c_int;
+    #[cfg(ossl300)]
+    pub fn PKCS12_add1_attr_by_txt(
+        bag: *mut PKCS12_SAFEBAG,
+        attrname: *const c_char,
+        type_: c_int,
+        bytes: *const c_uchar,
+        len: c_int,
+    ) -> c_int;
+
+    #[cfg(ossl110)]
+    pub fn PKCS12_mac_present(p12: *const PKCS12) -> c_int;
+    #[cfg(ossl110)]
+    pub fn PKCS12_verify_mac(p12: *mut PKCS12, pass: *const c_char, passlen: c_int) -> c_int;
+    #[cfg(ossl110)]
+    pub fn PKCS12_set_mac(
+        p12: *mut PKCS12,
+        pass: *const c_char,
+        passlen: c_int,
+        salt: *mut c_uchar,
+        saltlen: c_int,
+        iter: c_int,
+        md_type: *const EVP_MD,
+    ) -> c_int;
+}
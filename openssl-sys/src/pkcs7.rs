@@ -7,7 +7,28 @@ pub enum PKCS7_ENVELOPE {}
 pub enum PKCS7_SIGN_ENVELOPE {}
 pub enum PKCS7_DIGEST {}
 pub enum PKCS7_ENCRYPT {}
-pub enum PKCS7 {}
+
+#[repr(C)]
+pub struct PKCS7 {
+    pub asn1: *mut c_uchar,
+    pub length: c_long,
+    pub state: c_int,
+    pub detached: c_int,
+    pub type_: *mut ASN1_OBJECT,
+    // FIXME should be a union
+    pub d: *mut c_void,
+    #[cfg(ossl300)]
+    pub ctx: PKCS7_CTX,
+}
+
+#[cfg(ossl300)]
+#[repr(C)]
+pub struct PKCS7_CTX {
+    pub libctx: *mut OSSL_LIB_CTX,
+    pub propq: *mut c_char,
+}
+
+stack!(stack_st_PKCS7);
 
 pub const PKCS7_TEXT: c_int = 0x1;
 pub const PKCS7_NOCERTS: c_int = 0x2;
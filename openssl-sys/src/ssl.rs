@@ -700,6 +700,11 @@ extern "C" {
     #[cfg(ossl111)]
     pub fn SSL_CTX_set_keylog_callback(ctx: *mut SSL_CTX, cb: SSL_CTX_keylog_cb_func);
 
+    #[cfg(ossl111)]
+    pub fn SSL_key_update(s: *mut SSL, updatetype: c_int) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_key_update_type(s: *const SSL) -> c_int;
+
     #[cfg(ossl111)]
     pub fn SSL_CTX_set_max_early_data(ctx: *mut SSL_CTX, max_early_data: u32) -> c_int;
     #[cfg(ossl111)]
@@ -723,9 +728,105 @@ const_ptr_api! {
     }
 }
 
+pub const SSL_AD_CLOSE_NOTIFY: c_int = SSL3_AD_CLOSE_NOTIFY;
+pub const SSL_AD_UNEXPECTED_MESSAGE: c_int = SSL3_AD_UNEXPECTED_MESSAGE;
+pub const SSL_AD_BAD_RECORD_MAC: c_int = SSL3_AD_BAD_RECORD_MAC;
+pub const SSL_AD_DECRYPTION_FAILED: c_int = TLS1_AD_DECRYPTION_FAILED;
+pub const SSL_AD_RECORD_OVERFLOW: c_int = TLS1_AD_RECORD_OVERFLOW;
+pub const SSL_AD_DECOMPRESSION_FAILURE: c_int = SSL3_AD_DECOMPRESSION_FAILURE;
+pub const SSL_AD_HANDSHAKE_FAILURE: c_int = SSL3_AD_HANDSHAKE_FAILURE;
+pub const SSL_AD_NO_CERTIFICATE: c_int = SSL3_AD_NO_CERTIFICATE;
+pub const SSL_AD_BAD_CERTIFICATE: c_int = SSL3_AD_BAD_CERTIFICATE;
+pub const SSL_AD_UNSUPPORTED_CERTIFICATE: c_int = SSL3_AD_UNSUPPORTED_CERTIFICATE;
+pub const SSL_AD_CERTIFICATE_REVOKED: c_int = SSL3_AD_CERTIFICATE_REVOKED;
+pub const SSL_AD_CERTIFICATE_EXPIRED: c_int = SSL3_AD_CERTIFICATE_EXPIRED;
+pub const SSL_AD_CERTIFICATE_UNKNOWN: c_int = SSL3_AD_CERTIFICATE_UNKNOWN;
 pub const SSL_AD_ILLEGAL_PARAMETER: c_int = SSL3_AD_ILLEGAL_PARAMETER;
+pub const SSL_AD_UNKNOWN_CA: c_int = TLS1_AD_UNKNOWN_CA;
+pub const SSL_AD_ACCESS_DENIED: c_int = TLS1_AD_ACCESS_DENIED;
 pub const SSL_AD_DECODE_ERROR: c_int = TLS1_AD_DECODE_ERROR;
+pub const SSL_AD_DECRYPT_ERROR: c_int = TLS1_AD_DECRYPT_ERROR;
+pub const SSL_AD_EXPORT_RESTRICTION: c_int = TLS1_AD_EXPORT_RESTRICTION;
+pub const SSL_AD_PROTOCOL_VERSION: c_int = TLS1_AD_PROTOCOL_VERSION;
+pub const SSL_AD_INSUFFICIENT_SECURITY: c_int = TLS1_AD_INSUFFICIENT_SECURITY;
+pub const SSL_AD_INTERNAL_ERROR: c_int = TLS1_AD_INTERNAL_ERROR;
+pub const SSL_AD_INAPPROPRIATE_FALLBACK: c_int = TLS1_AD_INAPPROPRIATE_FALLBACK;
+pub const SSL_AD_USER_CANCELLED: c_int = TLS1_AD_USER_CANCELLED;
+pub const SSL_AD_NO_RENEGOTIATION: c_int = TLS1_AD_NO_RENEGOTIATION;
+#[cfg(ossl111)]
+pub const SSL_AD_MISSING_EXTENSION: c_int = TLS13_AD_MISSING_EXTENSION;
+pub const SSL_AD_UNSUPPORTED_EXTENSION: c_int = TLS1_AD_UNSUPPORTED_EXTENSION;
+pub const SSL_AD_CERTIFICATE_UNOBTAINABLE: c_int = TLS1_AD_CERTIFICATE_UNOBTAINABLE;
 pub const SSL_AD_UNRECOGNIZED_NAME: c_int = TLS1_AD_UNRECOGNIZED_NAME;
+pub const SSL_AD_BAD_CERTIFICATE_STATUS_RESPONSE: c_int = TLS1_AD_BAD_CERTIFICATE_STATUS_RESPONSE;
+pub const SSL_AD_BAD_CERTIFICATE_HASH_VALUE: c_int = TLS1_AD_BAD_CERTIFICATE_HASH_VALUE;
+pub const SSL_AD_UNKNOWN_PSK_IDENTITY: c_int = TLS1_AD_UNKNOWN_PSK_IDENTITY;
+#[cfg(ossl111)]
+pub const SSL_AD_CERTIFICATE_REQUIRED: c_int = TLS13_AD_CERTIFICATE_REQUIRED;
+#[cfg(ossl102)]
+pub const SSL_AD_NO_APPLICATION_PROTOCOL: c_int = TLS1_AD_NO_APPLICATION_PROTOCOL;
+
+pub const SSL_ST_CONNECT: c_int = 0x1000;
+pub const SSL_ST_ACCEPT: c_int = 0x2000;
+
+pub const SSL_CB_LOOP: c_int = 0x01;
+pub const SSL_CB_EXIT: c_int = 0x02;
+pub const SSL_CB_READ: c_int = 0x04;
+pub const SSL_CB_WRITE: c_int = 0x08;
+pub const SSL_CB_ALERT: c_int = 0x4000;
+pub const SSL_CB_READ_ALERT: c_int = SSL_CB_ALERT | SSL_CB_READ;
+pub const SSL_CB_WRITE_ALERT: c_int = SSL_CB_ALERT | SSL_CB_WRITE;
+pub const SSL_CB_ACCEPT_LOOP: c_int = SSL_ST_ACCEPT | SSL_CB_LOOP;
+pub const SSL_CB_ACCEPT_EXIT: c_int = SSL_ST_ACCEPT | SSL_CB_EXIT;
+pub const SSL_CB_CONNECT_LOOP: c_int = SSL_ST_CONNECT | SSL_CB_LOOP;
+pub const SSL_CB_CONNECT_EXIT: c_int = SSL_ST_CONNECT | SSL_CB_EXIT;
+pub const SSL_CB_HANDSHAKE_START: c_int = 0x10;
+pub const SSL_CB_HANDSHAKE_DONE: c_int = 0x20;
+
+extern "C" {
+    pub fn SSL_CTX_set_info_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<unsafe extern "C" fn(ssl: *const SSL, type_: c_int, val: c_int)>,
+    );
+    pub fn SSL_set_info_callback(
+        ssl: *mut SSL,
+        cb: Option<unsafe extern "C" fn(ssl: *const SSL, type_: c_int, val: c_int)>,
+    );
+    pub fn SSL_CTX_set_msg_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<
+            unsafe extern "C" fn(
+                write_p: c_int,
+                version: c_int,
+                content_type: c_int,
+                buf: *const c_void,
+                len: size_t,
+                ssl: *mut SSL,
+                arg: *mut c_void,
+            ),
+        >,
+    );
+    pub fn SSL_set_msg_callback(
+        ssl: *mut SSL,
+        cb: Option<
+            unsafe extern "C" fn(
+                write_p: c_int,
+                version: c_int,
+                content_type: c_int,
+                buf: *const c_void,
+                len: size_t,
+                ssl: *mut SSL,
+                arg: *mut c_void,
+            ),
+        >,
+    );
+
+    pub fn SSL_alert_type_string(value: c_int) -> *const c_char;
+    pub fn SSL_alert_type_string_long(value: c_int) -> *const c_char;
+    pub fn SSL_alert_desc_string(value: c_int) -> *const c_char;
+    pub fn SSL_alert_desc_string_long(value: c_int) -> *const c_char;
+}
+
 pub const SSL_ERROR_NONE: c_int = 0;
 pub const SSL_ERROR_SSL: c_int = 1;
 pub const SSL_ERROR_SYSCALL: c_int = 5;
@@ -740,11 +841,15 @@ pub const SSL_ERROR_WANT_CLIENT_HELLO_CB: c_int = 11;
 pub const SSL_VERIFY_NONE: c_int = 0;
 pub const SSL_VERIFY_PEER: c_int = 1;
 pub const SSL_VERIFY_FAIL_IF_NO_PEER_CERT: c_int = 2;
+pub const SSL_VERIFY_CLIENT_ONCE: c_int = 4;
+#[cfg(ossl111)]
+pub const SSL_VERIFY_POST_HANDSHAKE: c_int = 8;
 pub const SSL_CTRL_SET_TMP_DH: c_int = 3;
 pub const SSL_CTRL_SET_TMP_ECDH: c_int = 4;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_GET_SESSION_REUSED: c_int = 8;
 pub const SSL_CTRL_EXTRA_CHAIN_CERT: c_int = 14;
+pub const SSL_CTRL_SET_MSG_CALLBACK_ARG: c_int = 16;
 pub const SSL_CTRL_SET_MTU: c_int = 17;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_OPTIONS: c_int = 32;
@@ -761,6 +866,7 @@ pub const SSL_CTRL_SET_TLSEXT_STATUS_REQ_CB_ARG: c_int = 64;
 pub const SSL_CTRL_SET_TLSEXT_STATUS_REQ_TYPE: c_int = 65;
 pub const SSL_CTRL_GET_TLSEXT_STATUS_REQ_OCSP_RESP: c_int = 70;
 pub const SSL_CTRL_SET_TLSEXT_STATUS_REQ_OCSP_RESP: c_int = 71;
+pub const SSL_CTRL_SET_TLSEXT_TICKET_KEY_CB: c_int = 72;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_CLEAR_OPTIONS: c_int = 77;
 pub const SSL_CTRL_GET_EXTRA_CHAIN_CERTS: c_int = 82;
@@ -799,6 +905,14 @@ pub unsafe fn SSL_set_tmp_ecdh(ssl: *mut SSL, key: *mut EC_KEY) -> c_long {
     SSL_ctrl(ssl, SSL_CTRL_SET_TMP_ECDH, 0, key as *mut c_void)
 }
 
+pub unsafe fn SSL_CTX_set_msg_callback_arg(ctx: *mut SSL_CTX, arg: *mut c_void) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SET_MSG_CALLBACK_ARG, 0, arg)
+}
+
+pub unsafe fn SSL_set_msg_callback_arg(ssl: *mut SSL, arg: *mut c_void) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_SET_MSG_CALLBACK_ARG, 0, arg)
+}
+
 pub unsafe fn SSL_CTX_add_extra_chain_cert(ctx: *mut SSL_CTX, x509: *mut X509) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_EXTRA_CHAIN_CERT, 0, x509 as *mut c_void)
 }
@@ -932,6 +1046,12 @@ pub unsafe fn SSL_get_max_proto_version(s: *mut SSL) -> c_int {
 extern "C" {
     pub fn SSL_CTX_set_cipher_list(ssl: *mut SSL_CTX, s: *const c_char) -> c_int;
     pub fn SSL_CTX_new(method: *const SSL_METHOD) -> *mut SSL_CTX;
+    #[cfg(ossl300)]
+    pub fn SSL_CTX_new_ex(
+        libctx: *mut OSSL_LIB_CTX,
+        propq: *const c_char,
+        method: *const SSL_METHOD,
+    ) -> *mut SSL_CTX;
     pub fn SSL_CTX_free(ctx: *mut SSL_CTX);
     #[cfg(any(ossl110, libressl273))]
     pub fn SSL_CTX_up_ref(x: *mut SSL_CTX) -> c_int;
@@ -1039,6 +1159,10 @@ extern "C" {
 
     #[cfg(ossl111)]
     pub fn SSL_CTX_set_post_handshake_auth(ctx: *mut SSL_CTX, val: c_int);
+    #[cfg(ossl111)]
+    pub fn SSL_set_post_handshake_auth(ssl: *mut SSL, val: c_int);
+    #[cfg(ossl111)]
+    pub fn SSL_verify_client_post_handshake(ssl: *mut SSL) -> c_int;
 
     pub fn SSL_CTX_check_private_key(ctx: *const SSL_CTX) -> c_int;
 
@@ -1067,6 +1191,72 @@ pub const SSL_CLIENT_HELLO_RETRY: c_int = -1;
 #[cfg(ossl111)]
 pub type SSL_client_hello_cb_fn =
     Option<unsafe extern "C" fn(s: *mut SSL, al: *mut c_int, arg: *mut c_void) -> c_int>;
+#[cfg(ossl111)]
+pub type SSL_TICKET_STATUS = c_int;
+#[cfg(ossl111)]
+pub const SSL_TICKET_FATAL_ERR_MALLOC: SSL_TICKET_STATUS = 0;
+#[cfg(ossl111)]
+pub const SSL_TICKET_FATAL_ERR_OTHER: SSL_TICKET_STATUS = 1;
+#[cfg(ossl111)]
+pub const SSL_TICKET_NONE: SSL_TICKET_STATUS = 2;
+#[cfg(ossl111)]
+pub const SSL_TICKET_EMPTY: SSL_TICKET_STATUS = 3;
+#[cfg(ossl111)]
+pub const SSL_TICKET_NO_DECRYPT: SSL_TICKET_STATUS = 4;
+#[cfg(ossl111)]
+pub const SSL_TICKET_SUCCESS: SSL_TICKET_STATUS = 5;
+#[cfg(ossl111)]
+pub const SSL_TICKET_SUCCESS_RENEW: SSL_TICKET_STATUS = 6;
+
+#[cfg(ossl111)]
+pub type SSL_TICKET_RETURN = c_int;
+#[cfg(ossl111)]
+pub const SSL_TICKET_RETURN_ABORT: SSL_TICKET_RETURN = 0;
+#[cfg(ossl111)]
+pub const SSL_TICKET_RETURN_IGNORE: SSL_TICKET_RETURN = 1;
+#[cfg(ossl111)]
+pub const SSL_TICKET_RETURN_IGNORE_RENEW: SSL_TICKET_RETURN = 2;
+#[cfg(ossl111)]
+pub const SSL_TICKET_RETURN_USE: SSL_TICKET_RETURN = 3;
+#[cfg(ossl111)]
+pub const SSL_TICKET_RETURN_USE_RENEW: SSL_TICKET_RETURN = 4;
+
+#[cfg(ossl111)]
+pub type SSL_CTX_generate_session_ticket_fn =
+    Option<unsafe extern "C" fn(s: *mut SSL, arg: *mut c_void) -> c_int>;
+#[cfg(ossl111)]
+pub type SSL_CTX_decrypt_session_ticket_fn = Option<
+    unsafe extern "C" fn(
+        s: *mut SSL,
+        ss: *mut SSL_SESSION,
+        keyname: *const c_uchar,
+        keyname_length: size_t,
+        status: SSL_TICKET_STATUS,
+        arg: *mut c_void,
+    ) -> SSL_TICKET_RETURN,
+>;
+extern "C" {
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_session_ticket_cb(
+        ctx: *mut SSL_CTX,
+        gen_cb: SSL_CTX_generate_session_ticket_fn,
+        dec_cb: SSL_CTX_decrypt_session_ticket_fn,
+        arg: *mut c_void,
+    ) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set1_ticket_appdata(
+        ss: *mut SSL_SESSION,
+        data: *const c_void,
+        len: size_t,
+    ) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_get0_ticket_appdata(
+        ss: *mut SSL_SESSION,
+        data: *mut *mut c_void,
+        len: *mut size_t,
+    ) -> c_int;
+}
+
 extern "C" {
     #[cfg(ossl111)]
     pub fn SSL_CTX_set_client_hello_cb(
@@ -1126,6 +1316,13 @@ pub const SSL_READ_EARLY_DATA_SUCCESS: c_int = 1;
 #[cfg(ossl111)]
 pub const SSL_READ_EARLY_DATA_FINISH: c_int = 2;
 
+#[cfg(ossl111)]
+pub const SSL_KEY_UPDATE_NONE: c_int = -1;
+#[cfg(ossl111)]
+pub const SSL_KEY_UPDATE_NOT_REQUESTED: c_int = 0;
+#[cfg(ossl111)]
+pub const SSL_KEY_UPDATE_REQUESTED: c_int = 1;
+
 extern "C" {
     pub fn SSL_write(ssl: *mut SSL, buf: *const c_void, num: c_int) -> c_int;
     #[cfg(ossl111)]
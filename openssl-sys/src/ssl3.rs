@@ -2,4 +2,27 @@ use libc::*;
 
 pub const SSL3_VERSION: c_int = 0x300;
 
+pub const SSL3_RT_CHANGE_CIPHER_SPEC: c_int = 20;
+pub const SSL3_RT_ALERT: c_int = 21;
+pub const SSL3_RT_HANDSHAKE: c_int = 22;
+pub const SSL3_RT_APPLICATION_DATA: c_int = 23;
+#[cfg(ossl110)]
+pub const SSL3_RT_HEADER: c_int = 0x100;
+#[cfg(ossl111)]
+pub const SSL3_RT_INNER_CONTENT_TYPE: c_int = 0x101;
+
+pub const SSL3_AL_WARNING: c_int = 1;
+pub const SSL3_AL_FATAL: c_int = 2;
+
+pub const SSL3_AD_CLOSE_NOTIFY: c_int = 0;
+pub const SSL3_AD_UNEXPECTED_MESSAGE: c_int = 10;
+pub const SSL3_AD_BAD_RECORD_MAC: c_int = 20;
+pub const SSL3_AD_DECOMPRESSION_FAILURE: c_int = 30;
+pub const SSL3_AD_HANDSHAKE_FAILURE: c_int = 40;
+pub const SSL3_AD_NO_CERTIFICATE: c_int = 41;
+pub const SSL3_AD_BAD_CERTIFICATE: c_int = 42;
+pub const SSL3_AD_UNSUPPORTED_CERTIFICATE: c_int = 43;
+pub const SSL3_AD_CERTIFICATE_REVOKED: c_int = 44;
+pub const SSL3_AD_CERTIFICATE_EXPIRED: c_int = 45;
+pub const SSL3_AD_CERTIFICATE_UNKNOWN: c_int = 46;
 pub const SSL3_AD_ILLEGAL_PARAMETER: c_int = 47;
@@ -10,8 +10,31 @@ pub const TLS1_2_VERSION: c_int = 0x303;
 #[cfg(ossl111)]
 pub const TLS1_3_VERSION: c_int = 0x304;
 
+pub const TLS1_AD_DECRYPTION_FAILED: c_int = 21;
+pub const TLS1_AD_RECORD_OVERFLOW: c_int = 22;
+pub const TLS1_AD_UNKNOWN_CA: c_int = 48;
+pub const TLS1_AD_ACCESS_DENIED: c_int = 49;
 pub const TLS1_AD_DECODE_ERROR: c_int = 50;
+pub const TLS1_AD_DECRYPT_ERROR: c_int = 51;
+pub const TLS1_AD_EXPORT_RESTRICTION: c_int = 60;
+pub const TLS1_AD_PROTOCOL_VERSION: c_int = 70;
+pub const TLS1_AD_INSUFFICIENT_SECURITY: c_int = 71;
+pub const TLS1_AD_INTERNAL_ERROR: c_int = 80;
+pub const TLS1_AD_INAPPROPRIATE_FALLBACK: c_int = 86;
+pub const TLS1_AD_USER_CANCELLED: c_int = 90;
+pub const TLS1_AD_NO_RENEGOTIATION: c_int = 100;
+#[cfg(ossl111)]
+pub const TLS13_AD_MISSING_EXTENSION: c_int = 109;
+pub const TLS1_AD_UNSUPPORTED_EXTENSION: c_int = 110;
+pub const TLS1_AD_CERTIFICATE_UNOBTAINABLE: c_int = 111;
 pub const TLS1_AD_UNRECOGNIZED_NAME: c_int = 112;
+pub const TLS1_AD_BAD_CERTIFICATE_STATUS_RESPONSE: c_int = 113;
+pub const TLS1_AD_BAD_CERTIFICATE_HASH_VALUE: c_int = 114;
+pub const TLS1_AD_UNKNOWN_PSK_IDENTITY: c_int = 115;
+#[cfg(ossl111)]
+pub const TLS13_AD_CERTIFICATE_REQUIRED: c_int = 116;
+#[cfg(ossl102)]
+pub const TLS1_AD_NO_APPLICATION_PROTOCOL: c_int = 120;
 
 pub const TLSEXT_NAMETYPE_host_name: c_int = 0;
 pub const TLSEXT_STATUSTYPE_ocsp: c_int = 1;
@@ -109,3 +132,36 @@ pub unsafe fn SSL_CTX_set_tlsext_status_cb(
 pub unsafe fn SSL_CTX_set_tlsext_status_arg(ctx: *mut SSL_CTX, arg: *mut c_void) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_TLSEXT_STATUS_REQ_CB_ARG, 0, arg)
 }
+
+pub unsafe fn SSL_CTX_set_tlsext_ticket_key_cb(
+    ctx: *mut SSL_CTX,
+    cb: Option<
+        unsafe extern "C" fn(
+            *mut SSL,
+            *mut c_uchar,
+            *mut c_uchar,
+            *mut EVP_CIPHER_CTX,
+            *mut HMAC_CTX,
+            c_int,
+        ) -> c_int,
+    >,
+) -> c_long {
+    SSL_CTX_callback_ctrl(ctx, SSL_CTRL_SET_TLSEXT_TICKET_KEY_CB, mem::transmute(cb))
+}
+
+extern "C" {
+    #[cfg(ossl300)]
+    pub fn SSL_CTX_set_tlsext_ticket_key_evp_cb(
+        ctx: *mut SSL_CTX,
+        fp: Option<
+            unsafe extern "C" fn(
+                *mut SSL,
+                *mut c_uchar,
+                *mut c_uchar,
+                *mut EVP_CIPHER_CTX,
+                *mut EVP_MAC_CTX,
+                c_int,
+            ) -> c_int,
+        >,
+    ) -> c_int;
+}
@@ -1,2 +1,4 @@
 #[cfg(ossl300)]
 pub enum OSSL_LIB_CTX {}
+#[cfg(ossl300)]
+pub enum OSSL_PROVIDER {}
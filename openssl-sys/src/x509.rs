@@ -7,6 +7,9 @@ pub const X509_FILETYPE_ASN1: c_int = 2;
 pub const X509_FILETYPE_DEFAULT: c_int = 3;
 
 pub const ASN1_R_HEADER_TOO_LONG: c_int = 123;
+pub const ASN1_R_WRONG_TAG: c_int = 168;
+
+pub const X509_R_CERT_ALREADY_IN_HASH_TABLE: c_int = 101;
 
 #[repr(C)]
 pub struct X509_VAL {
@@ -20,8 +23,12 @@ stack!(stack_st_X509_NAME);
 
 pub enum X509_EXTENSION {}
 
+pub enum X509_SIG {}
+
 stack!(stack_st_X509_EXTENSION);
 
+pub enum X509_ATTRIBUTE {}
+
 stack!(stack_st_X509_ATTRIBUTE);
 
 cfg_if! {
@@ -46,8 +53,8 @@ cfg_if! {
         #[repr(C)]
         pub struct X509_CRL {
             pub crl: *mut X509_CRL_INFO,
-            sig_alg: *mut X509_ALGOR,
-            signature: *mut c_void,
+            pub sig_alg: *mut X509_ALGOR,
+            pub signature: *mut ASN1_BIT_STRING,
             references: c_int,
             flags: c_int,
             akid: *mut c_void,
@@ -66,13 +73,27 @@ cfg_if! {
 
 stack!(stack_st_X509_CRL);
 
+pub enum X509_PKEY {}
+
+#[repr(C)]
+pub struct X509_INFO {
+    pub x509: *mut X509,
+    pub crl: *mut X509_CRL,
+    pub x_pkey: *mut X509_PKEY,
+    pub enc_cipher: EVP_CIPHER_INFO,
+    pub enc_len: c_int,
+    pub enc_data: *mut c_char,
+}
+
+stack!(stack_st_X509_INFO);
+
 cfg_if! {
     if #[cfg(ossl110)] {
         pub enum X509_CRL_INFO {}
     } else {
         #[repr(C)]
         pub struct X509_CRL_INFO {
-            version: *mut ASN1_INTEGER,
+            pub version: *mut ASN1_INTEGER,
             sig_alg: *mut X509_ALGOR,
             pub issuer: *mut X509_NAME,
             pub lastUpdate: *mut ASN1_TIME,
@@ -177,6 +198,10 @@ extern "C" {
 
     pub fn X509_sign(x: *mut X509, pkey: *mut EVP_PKEY, md: *const EVP_MD) -> c_int;
 
+    pub fn X509_print(bp: *mut BIO, x: *mut X509) -> c_int;
+    pub fn X509_REQ_print(bp: *mut BIO, req: *mut X509_REQ) -> c_int;
+    pub fn X509_CRL_print(bp: *mut BIO, x: *mut X509_CRL) -> c_int;
+
     pub fn X509_digest(
         x: *const X509,
         digest: *const EVP_MD,
@@ -238,6 +263,27 @@ extern "C" {
 
     pub fn X509_ALGOR_free(x: *mut X509_ALGOR);
 
+    pub fn X509_SIG_free(x: *mut X509_SIG);
+
+    pub fn PKCS5_pbe2_set_iv(
+        cipher: *const EVP_CIPHER,
+        iter: c_int,
+        salt: *mut c_uchar,
+        saltlen: c_int,
+        aiv: *mut c_uchar,
+        prf_nid: c_int,
+    ) -> *mut X509_ALGOR;
+    #[cfg(ossl110)]
+    pub fn PKCS5_pbe2_set_scrypt(
+        cipher: *const EVP_CIPHER,
+        salt: *const c_uchar,
+        saltlen: c_int,
+        aiv: *mut c_uchar,
+        N: u64,
+        r: u64,
+        p: u64,
+    ) -> *mut X509_ALGOR;
+
     pub fn X509_REVOKED_new() -> *mut X509_REVOKED;
     pub fn X509_REVOKED_free(x: *mut X509_REVOKED);
 }
@@ -263,6 +309,7 @@ const_ptr_api! {
 extern "C" {
     pub fn X509_CRL_new() -> *mut X509_CRL;
     pub fn X509_CRL_free(x: *mut X509_CRL);
+    pub fn X509_INFO_free(x: *mut X509_INFO);
     pub fn d2i_X509_CRL(
         a: *mut *mut X509_CRL,
         pp: *mut *const c_uchar,
@@ -272,6 +319,7 @@ extern "C" {
 const_ptr_api! {
     extern "C" {
         pub fn i2d_X509_CRL(x: #[const_ptr_if(ossl300)] X509_CRL, buf: *mut *mut u8) -> c_int;
+        pub fn i2d_X509_SIG(x: #[const_ptr_if(ossl300)] X509_SIG, buf: *mut *mut u8) -> c_int;
     }
 }
 
@@ -431,6 +479,14 @@ extern "C" {
     pub fn X509_CRL_get0_lastUpdate(x: *const X509_CRL) -> *const ASN1_TIME;
     #[cfg(any(ossl110, libressl281))]
     pub fn X509_CRL_get_issuer(x: *const X509_CRL) -> *mut X509_NAME;
+    #[cfg(ossl110)]
+    pub fn X509_CRL_get_version(crl: *const X509_CRL) -> c_long;
+    #[cfg(any(ossl110, libressl273))]
+    pub fn X509_CRL_get0_signature(
+        crl: *const X509_CRL,
+        psig: *mut *const ASN1_BIT_STRING,
+        palg: *mut *const X509_ALGOR,
+    );
 
     #[cfg(ossl110)]
     pub fn X509_get0_extensions(req: *const ::X509) -> *const stack_st_X509_EXTENSION;
@@ -643,6 +699,27 @@ cfg_if! {
     }
 }
 
+const_ptr_api! {
+    extern "C" {
+        pub fn X509_ATTRIBUTE_count(attr: #[const_ptr_if(ossl110)] X509_ATTRIBUTE) -> c_int;
+    }
+}
+extern "C" {
+    pub fn X509at_get_attr_by_NID(
+        x: *const stack_st_X509_ATTRIBUTE,
+        nid: c_int,
+        lastpos: c_int,
+    ) -> c_int;
+    pub fn X509at_get_attr(x: *const stack_st_X509_ATTRIBUTE, loc: c_int) -> *mut X509_ATTRIBUTE;
+    pub fn X509_ATTRIBUTE_get0_type(attr: *mut X509_ATTRIBUTE, idx: c_int) -> *mut ASN1_TYPE;
+    pub fn X509_ATTRIBUTE_get0_data(
+        attr: *mut X509_ATTRIBUTE,
+        idx: c_int,
+        atrtype: c_int,
+        data: *mut c_void,
+    ) -> *mut c_void;
+}
+
 extern "C" {
     pub fn X509_get_default_cert_file_env() -> *const c_char;
     pub fn X509_get_default_cert_file() -> *const c_char;
@@ -134,14 +134,44 @@ pub const X509_V_FLAG_NO_ALT_CHAINS: c_ulong = 0x100000;
 #[cfg(ossl110)]
 pub const X509_V_FLAG_NO_CHECK_TIME: c_ulong = 0x200000;
 
+pub const X509_TRUST_COMPAT: c_int = 1;
+pub const X509_TRUST_SSL_CLIENT: c_int = 2;
+pub const X509_TRUST_SSL_SERVER: c_int = 3;
+pub const X509_TRUST_EMAIL: c_int = 4;
+pub const X509_TRUST_OBJECT_SIGN: c_int = 5;
+pub const X509_TRUST_OCSP_SIGN: c_int = 6;
+pub const X509_TRUST_OCSP_REQUEST: c_int = 7;
+pub const X509_TRUST_TSA: c_int = 8;
+
+#[cfg(ossl110)]
+pub type X509_LOOKUP_ctrl_fn = Option<
+    unsafe extern "C" fn(
+        ctx: *mut X509_LOOKUP,
+        cmd: c_int,
+        argc: *const c_char,
+        argl: c_long,
+        ret: *mut *mut c_char,
+    ) -> c_int,
+>;
+
 extern "C" {
+    #[cfg(ossl110)]
+    pub fn X509_LOOKUP_meth_new(name: *const c_char) -> *mut X509_LOOKUP_METHOD;
     #[cfg(ossl110)]
     pub fn X509_LOOKUP_meth_free(method: *mut X509_LOOKUP_METHOD);
+    #[cfg(ossl110)]
+    pub fn X509_LOOKUP_meth_set_ctrl(
+        method: *mut X509_LOOKUP_METHOD,
+        ctrl_fn: X509_LOOKUP_ctrl_fn,
+    ) -> c_int;
+    #[cfg(ossl110)]
+    pub fn X509_LOOKUP_get_store(ctx: *const X509_LOOKUP) -> *mut X509_STORE;
 }
 
 extern "C" {
     pub fn X509_LOOKUP_free(ctx: *mut X509_LOOKUP);
     pub fn X509_LOOKUP_hash_dir() -> *mut X509_LOOKUP_METHOD;
+    pub fn X509_LOOKUP_file() -> *mut X509_LOOKUP_METHOD;
     pub fn X509_LOOKUP_ctrl(
         ctx: *mut X509_LOOKUP,
         cmd: c_int,
@@ -166,6 +196,16 @@ pub unsafe fn X509_LOOKUP_add_dir(
     )
 }
 
+extern "C" {
+    pub fn X509_load_cert_file(ctx: *mut X509_LOOKUP, file: *const c_char, _type: c_int) -> c_int;
+    pub fn X509_load_crl_file(ctx: *mut X509_LOOKUP, file: *const c_char, _type: c_int) -> c_int;
+    pub fn X509_load_cert_crl_file(
+        ctx: *mut X509_LOOKUP,
+        file: *const c_char,
+        _type: c_int,
+    ) -> c_int;
+}
+
 extern "C" {
     pub fn X509_STORE_CTX_new() -> *mut X509_STORE_CTX;
 
@@ -183,6 +223,46 @@ extern "C" {
     pub fn X509_STORE_CTX_set_error(ctx: *mut X509_STORE_CTX, error: c_int);
 
     pub fn X509_STORE_CTX_set0_param(ctx: *mut X509_STORE_CTX, param: *mut X509_VERIFY_PARAM);
+
+    pub fn X509_STORE_CTX_set_ex_data(
+        ctx: *mut X509_STORE_CTX,
+        idx: c_int,
+        data: *mut c_void,
+    ) -> c_int;
+
+    pub fn X509_STORE_CTX_set_verify_cb(
+        ctx: *mut X509_STORE_CTX,
+        verify_cb: Option<extern "C" fn(c_int, *mut X509_STORE_CTX) -> c_int>,
+    );
+    #[cfg(ossl110)]
+    pub fn X509_STORE_CTX_get_verify_cb(ctx: *const X509_STORE_CTX) -> X509_STORE_CTX_verify_cb;
+}
+
+#[cfg(ossl110)]
+pub type X509_STORE_CTX_verify_cb = Option<extern "C" fn(c_int, *mut X509_STORE_CTX) -> c_int>;
+
+cfg_if! {
+    if #[cfg(ossl110)] {
+        pub unsafe fn X509_STORE_CTX_get_ex_new_index(
+            l: c_long,
+            p: *mut c_void,
+            newf: Option<CRYPTO_EX_new>,
+            dupf: Option<CRYPTO_EX_dup>,
+            freef: Option<CRYPTO_EX_free>,
+        ) -> c_int {
+            CRYPTO_get_ex_new_index(CRYPTO_EX_INDEX_X509_STORE_CTX, l, p, newf, dupf, freef)
+        }
+    } else {
+        extern "C" {
+            pub fn X509_STORE_CTX_get_ex_new_index(
+                argl: c_long,
+                argp: *mut c_void,
+                new_func: Option<CRYPTO_EX_new>,
+                dup_func: Option<CRYPTO_EX_dup>,
+                free_func: Option<CRYPTO_EX_free>,
+            ) -> c_int;
+        }
+    }
 }
 
 extern "C" {
@@ -202,6 +282,14 @@ extern "C" {
     pub fn X509_STORE_set_default_paths(store: *mut X509_STORE) -> c_int;
 
     pub fn X509_STORE_set_flags(store: *mut X509_STORE, flags: c_ulong) -> c_int;
+
+    pub fn X509_STORE_set_verify_cb(
+        store: *mut X509_STORE,
+        verify_cb: Option<extern "C" fn(c_int, *mut X509_STORE_CTX) -> c_int>,
+    );
+
+    #[cfg(ossl110)]
+    pub fn X509_STORE_set_ex_data(store: *mut X509_STORE, idx: c_int, data: *mut c_void) -> c_int;
 }
 
 const_ptr_api! {
@@ -213,6 +301,17 @@ const_ptr_api! {
         pub fn X509_STORE_CTX_get_error(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> c_int;
         pub fn X509_STORE_CTX_get_error_depth(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> c_int;
         pub fn X509_STORE_CTX_get_current_cert(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> *mut X509;
+
+        #[cfg(ossl110)]
+        pub fn X509_STORE_get_ex_data(store: #[const_ptr_if(ossl300)] X509_STORE, idx: c_int) -> *mut c_void;
+        #[cfg(ossl110)]
+        pub fn X509_STORE_CTX_get0_store(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> *mut X509_STORE;
+        #[cfg(ossl110)]
+        pub fn X509_STORE_CTX_get0_current_issuer(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> *mut X509;
+        #[cfg(ossl110)]
+        pub fn X509_STORE_CTX_get0_current_crl(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> *mut X509_CRL;
+        #[cfg(ossl110)]
+        pub fn X509_STORE_CTX_get_num_untrusted(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> c_int;
     }
 }
 
@@ -237,6 +336,30 @@ extern "C" {
     #[cfg(any(ossl102, libressl261))]
     pub fn X509_VERIFY_PARAM_free(param: *mut X509_VERIFY_PARAM);
 
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set1(
+        to: *mut X509_VERIFY_PARAM,
+        from: *const X509_VERIFY_PARAM,
+    ) -> c_int;
+
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set_purpose(param: *mut X509_VERIFY_PARAM, purpose: c_int) -> c_int;
+
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set_trust(param: *mut X509_VERIFY_PARAM, trust: c_int) -> c_int;
+
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_add0_policy(
+        param: *mut X509_VERIFY_PARAM,
+        policy: *mut ASN1_OBJECT,
+    ) -> c_int;
+
+    #[cfg(ossl110)]
+    pub fn X509_VERIFY_PARAM_set_auth_level(param: *mut X509_VERIFY_PARAM, auth_level: c_int);
+
+    #[cfg(ossl110)]
+    pub fn X509_VERIFY_PARAM_get_auth_level(param: *const X509_VERIFY_PARAM) -> c_int;
+
     #[cfg(any(ossl102, libressl261))]
     pub fn X509_VERIFY_PARAM_set_flags(param: *mut X509_VERIFY_PARAM, flags: c_ulong) -> c_int;
 
@@ -256,6 +379,20 @@ extern "C" {
         namelen: size_t,
     ) -> c_int;
 
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_add1_host(
+        param: *mut X509_VERIFY_PARAM,
+        name: *const c_char,
+        namelen: size_t,
+    ) -> c_int;
+
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_VERIFY_PARAM_set1_email(
+        param: *mut X509_VERIFY_PARAM,
+        email: *const c_char,
+        emaillen: size_t,
+    ) -> c_int;
+
     #[cfg(any(ossl102, libressl261))]
     pub fn X509_VERIFY_PARAM_set_hostflags(param: *mut X509_VERIFY_PARAM, flags: c_uint);
 
@@ -277,5 +414,8 @@ const_ptr_api! {
 
         #[cfg(any(ossl102, libressl261))]
         pub fn X509_VERIFY_PARAM_get_depth(param: #[const_ptr_if(ossl102)] X509_VERIFY_PARAM) -> c_int;
+
+        #[cfg(any(ossl102, libressl261))]
+        pub fn X509_VERIFY_PARAM_get0_peername(param: #[const_ptr_if(ossl110)] X509_VERIFY_PARAM) -> *mut c_char;
     }
 }
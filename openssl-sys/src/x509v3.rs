@@ -3,6 +3,7 @@ use libc::*;
 use *;
 
 pub enum CONF_METHOD {}
+pub enum X509_PURPOSE {}
 
 pub const GEN_OTHERNAME: c_int = 0;
 pub const GEN_EMAIL: c_int = 1;
@@ -50,6 +51,54 @@ extern "C" {
     pub fn AUTHORITY_KEYID_free(akid: *mut AUTHORITY_KEYID);
 }
 
+pub const CRL_REASON_NONE: c_int = -1;
+pub const CRL_REASON_UNSPECIFIED: c_int = 0;
+pub const CRL_REASON_KEY_COMPROMISE: c_int = 1;
+pub const CRL_REASON_CA_COMPROMISE: c_int = 2;
+pub const CRL_REASON_AFFILIATION_CHANGED: c_int = 3;
+pub const CRL_REASON_SUPERSEDED: c_int = 4;
+pub const CRL_REASON_CESSATION_OF_OPERATION: c_int = 5;
+pub const CRL_REASON_CERTIFICATE_HOLD: c_int = 6;
+pub const CRL_REASON_REMOVE_FROM_CRL: c_int = 8;
+pub const CRL_REASON_PRIVILEGE_WITHDRAWN: c_int = 9;
+pub const CRL_REASON_AA_COMPROMISE: c_int = 10;
+
+pub const X509_PURPOSE_SSL_CLIENT: c_int = 1;
+pub const X509_PURPOSE_SSL_SERVER: c_int = 2;
+pub const X509_PURPOSE_NS_SSL_SERVER: c_int = 3;
+pub const X509_PURPOSE_SMIME_SIGN: c_int = 4;
+pub const X509_PURPOSE_SMIME_ENCRYPT: c_int = 5;
+pub const X509_PURPOSE_CRL_SIGN: c_int = 6;
+pub const X509_PURPOSE_ANY: c_int = 7;
+pub const X509_PURPOSE_OCSP_HELPER: c_int = 8;
+pub const X509_PURPOSE_TIMESTAMP_SIGN: c_int = 9;
+#[cfg(ossl340)]
+pub const X509_PURPOSE_CODE_SIGN: c_int = 10;
+
+pub const X509V3_R_INVALID_PURPOSE: c_int = 146;
+
+#[repr(C)]
+pub struct DIST_POINT_NAME {
+    pub type_: c_int,
+    // FIXME should be a union
+    pub name: *mut c_void,
+    pub dpname: *mut X509_NAME,
+}
+
+#[repr(C)]
+pub struct ISSUING_DIST_POINT {
+    pub distpoint: *mut DIST_POINT_NAME,
+    pub onlyuser: c_int,
+    pub onlyCA: c_int,
+    pub onlysomereasons: *mut ASN1_BIT_STRING,
+    pub indirectCRL: c_int,
+    pub onlyattr: c_int,
+}
+
+extern "C" {
+    pub fn ISSUING_DIST_POINT_free(idp: *mut ISSUING_DIST_POINT);
+}
+
 #[cfg(any(ossl102, libressl261))]
 pub const X509_CHECK_FLAG_ALWAYS_CHECK_SUBJECT: c_uint = 0x1;
 #[cfg(any(ossl102, libressl261))]
@@ -63,6 +112,27 @@ pub const X509_CHECK_FLAG_SINGLE_LABEL_SUBDOMAINS: c_uint = 0x10;
 #[cfg(ossl110)]
 pub const X509_CHECK_FLAG_NEVER_CHECK_SUBJECT: c_uint = 0x20;
 
+extern "C" {
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_check_host(
+        x: *mut X509,
+        chk: *const c_char,
+        chklen: size_t,
+        flags: c_uint,
+        peername: *mut *mut c_char,
+    ) -> c_int;
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_check_email(
+        x: *mut X509,
+        chk: *const c_char,
+        chklen: size_t,
+        flags: c_uint,
+    ) -> c_int;
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_check_ip(x: *mut X509, chk: *const c_uchar, chklen: size_t, flags: c_uint)
+        -> c_int;
+}
+
 const_ptr_api! {
     extern "C" {
         pub fn X509V3_EXT_nconf_nid(
@@ -83,6 +153,9 @@ const_ptr_api! {
 extern "C" {
     pub fn X509_check_issued(issuer: *mut X509, subject: *mut X509) -> c_int;
     pub fn X509_verify(req: *mut X509, pkey: *mut EVP_PKEY) -> c_int;
+    pub fn X509_check_ca(x: *mut X509) -> c_int;
+    pub fn X509_check_purpose(x: *mut X509, id: c_int, ca: c_int) -> c_int;
+    pub fn X509_PURPOSE_get0(idx: c_int) -> *mut X509_PURPOSE;
 
     pub fn X509V3_set_nconf(ctx: *mut X509V3_CTX, conf: *mut CONF);
 
@@ -107,6 +180,8 @@ const_ptr_api! {
             idx: *mut c_int,
         ) -> *mut c_void;
         pub fn X509V3_extensions_print(out: *mut BIO, title: #[const_ptr_if(any(ossl110, libressl280))] c_char, exts: #[const_ptr_if(any(ossl110, libressl280))] stack_st_X509_EXTENSION, flag: c_ulong, indent: c_int) -> c_int;
+        pub fn X509_PURPOSE_get_by_sname(sname: #[const_ptr_if(any(ossl110, libressl280))] c_char) -> c_int;
+        pub fn X509_PURPOSE_get_id(purpose: #[const_ptr_if(any(ossl110, libressl280))] X509_PURPOSE) -> c_int;
     }
 }
 
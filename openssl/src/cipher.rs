@@ -0,0 +1,162 @@
+//! Symmetric cipher algorithms fetched from providers.
+//!
+//! Unlike [`symm::Cipher`], which wraps the built-in implementations, a [`Cipher`] is fetched by
+//! name from the providers loaded in a library context, optionally restricted by a property query.
+//! It can be used with [`Crypter::with_cipher`].
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+//!
+//! [`symm::Cipher`]: ../symm/struct.Cipher.html
+//! [`Cipher`]: struct.Cipher.html
+//! [`Crypter::with_cipher`]: ../symm/struct.Crypter.html#method.with_cipher
+use foreign_types::{ForeignType, ForeignTypeRef};
+use std::ffi::{CStr, CString};
+use std::ptr;
+use std::str;
+
+use crate::cvt_p;
+use crate::error::ErrorStack;
+use crate::lib_ctx::LibCtxRef;
+use crate::nid::Nid;
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::EVP_CIPHER;
+    fn drop = ffi::EVP_CIPHER_free;
+
+    /// A symmetric cipher algorithm fetched from a provider.
+    pub struct Cipher;
+    /// Reference to a [`Cipher`].
+    ///
+    /// [`Cipher`]: struct.Cipher.html
+    pub struct CipherRef;
+}
+
+impl Clone for Cipher {
+    fn clone(&self) -> Cipher {
+        CipherRef::to_owned(self)
+    }
+}
+
+impl ToOwned for CipherRef {
+    type Owned = Cipher;
+
+    fn to_owned(&self) -> Cipher {
+        unsafe {
+            ffi::EVP_CIPHER_up_ref(self.as_ptr());
+            Cipher::from_ptr(self.as_ptr())
+        }
+    }
+}
+
+impl Cipher {
+    /// Fetches a cipher implementation by name from the default library context.
+    ///
+    /// `properties` is a property query such as `"fips=yes"` or `"provider=default"` restricting
+    /// which implementation is selected.
+    ///
+    /// This corresponds to [`EVP_CIPHER_fetch`].
+    ///
+    /// [`EVP_CIPHER_fetch`]: https://www.openssl.org/docs/man3.0/man3/EVP_CIPHER_fetch.html
+    pub fn fetch(algorithm: &str, properties: Option<&str>) -> Result<Cipher, ErrorStack> {
+        unsafe { Cipher::fetch_raw(ptr::null_mut(), algorithm, properties) }
+    }
+
+    /// Like [`fetch`], but fetches the cipher from the library context `ctx`.
+    ///
+    /// This corresponds to [`EVP_CIPHER_fetch`].
+    ///
+    /// # Safety
+    ///
+    /// The returned `Cipher`, and anything created from it, must be dropped before `ctx`.
+    ///
+    /// [`fetch`]: #method.fetch
+    /// [`EVP_CIPHER_fetch`]: https://www.openssl.org/docs/man3.0/man3/EVP_CIPHER_fetch.html
+    pub unsafe fn fetch_in(
+        ctx: &LibCtxRef,
+        algorithm: &str,
+        properties: Option<&str>,
+    ) -> Result<Cipher, ErrorStack> {
+        Cipher::fetch_raw(ctx.as_ptr(), algorithm, properties)
+    }
+
+    unsafe fn fetch_raw(
+        ctx: *mut ffi::OSSL_LIB_CTX,
+        algorithm: &str,
+        properties: Option<&str>,
+    ) -> Result<Cipher, ErrorStack> {
+        let algorithm = CString::new(algorithm).unwrap();
+        let properties = properties.map(|s| CString::new(s).unwrap());
+        ffi::init();
+        let ptr = cvt_p(ffi::EVP_CIPHER_fetch(
+            ctx,
+            algorithm.as_ptr(),
+            properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+        ))?;
+        Ok(Cipher::from_ptr(ptr))
+    }
+}
+
+impl CipherRef {
+    /// Returns the name of the cipher.
+    ///
+    /// This corresponds to [`EVP_CIPHER_get0_name`].
+    ///
+    /// [`EVP_CIPHER_get0_name`]: https://www.openssl.org/docs/man3.0/man3/EVP_CIPHER_get0_name.html
+    pub fn name(&self) -> &str {
+        unsafe {
+            let name = ffi::EVP_CIPHER_get0_name(self.as_ptr());
+            str::from_utf8(CStr::from_ptr(name).to_bytes()).unwrap()
+        }
+    }
+
+    /// Returns the length of keys used with this cipher.
+    pub fn key_length(&self) -> usize {
+        unsafe { ffi::EVP_CIPHER_key_length(self.as_ptr()) as usize }
+    }
+
+    /// Returns the length of the IV used with this cipher, or `None` if the cipher does not use an
+    /// IV.
+    pub fn iv_length(&self) -> Option<usize> {
+        unsafe {
+            let len = ffi::EVP_CIPHER_iv_length(self.as_ptr()) as usize;
+            if len == 0 {
+                None
+            } else {
+                Some(len)
+            }
+        }
+    }
+
+    /// Returns the block size of the cipher.
+    ///
+    /// Stream ciphers have a block size of 1.
+    pub fn block_size(&self) -> usize {
+        unsafe { ffi::EVP_CIPHER_block_size(self.as_ptr()) as usize }
+    }
+
+    /// Returns the `Nid` of the cipher.
+    pub fn nid(&self) -> Nid {
+        unsafe { Nid::from_raw(ffi::EVP_CIPHER_nid(self.as_ptr())) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fetch() {
+        let cipher = Cipher::fetch("AES-128-GCM", Some("provider=default")).unwrap();
+        assert_eq!(cipher.name(), "AES-128-GCM");
+        assert_eq!(cipher.key_length(), 16);
+        assert_eq!(cipher.iv_length(), Some(12));
+        assert_eq!(cipher.block_size(), 1);
+        assert_eq!(cipher.nid(), Nid::AES_128_GCM);
+
+        let cipher = Cipher::fetch("AES-256-ECB", None).unwrap().clone();
+        assert_eq!(cipher.iv_length(), None);
+        assert_eq!(cipher.block_size(), 16);
+
+        assert!(Cipher::fetch("NOT-A-CIPHER", None).is_err());
+    }
+}
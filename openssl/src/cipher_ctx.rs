@@ -0,0 +1,728 @@
+//! A low level interface to symmetric ciphers.
+//!
+//! Unlike [`Crypter`], a [`CipherCtx`] can be reinitialized with a new key or IV without
+//! allocating a new context, which makes it suitable for encrypting many short messages such as
+//! network packets. The cipher, key and IV may be supplied in separate calls, and AEAD parameters
+//! such as the IV and tag length can be configured in between.
+//!
+//! [`Crypter`]: ../symm/struct.Crypter.html
+//! [`CipherCtx`]: struct.CipherCtx.html
+//!
+//! # Examples
+//!
+//! Encrypt several messages with AES-128-GCM, using a fresh nonce for each one:
+//!
+//! ```
+//! use openssl::cipher_ctx::CipherCtx;
+//! use openssl::symm::Cipher;
+//!
+//! let key = b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0A\x0B\x0C\x0D\x0E\x0F";
+//!
+//! let mut ctx = CipherCtx::new().unwrap();
+//! ctx.encrypt_init(Some(Cipher::aes_128_gcm()), Some(key), None)
+//!     .unwrap();
+//!
+//! for (i, message) in [&b"first"[..], &b"second"[..]].iter().enumerate() {
+//!     let mut nonce = [0; 12];
+//!     nonce[11] = i as u8;
+//!     ctx.encrypt_init(None, None, Some(&nonce)).unwrap();
+//!
+//!     ctx.aad_update(b"header").unwrap();
+//!     let mut ciphertext = vec![0; message.len()];
+//!     let len = ctx.update(message, &mut ciphertext).unwrap();
+//!     ctx.finalize(&mut ciphertext[len..]).unwrap();
+//!
+//!     let mut tag = [0; 16];
+//!     ctx.tag(&mut tag).unwrap();
+//! }
+//! ```
+use cfg_if::cfg_if;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::c_int;
+use std::ptr;
+
+#[cfg(ossl300)]
+use crate::cipher::CipherRef;
+use crate::error::ErrorStack;
+use crate::symm::Cipher;
+use crate::{cvt, cvt_p};
+
+cfg_if! {
+    if #[cfg(any(ossl110, libressl270))] {
+        use ffi::EVP_CIPHER_CTX_reset;
+    } else {
+        use ffi::EVP_CIPHER_CTX_cleanup as EVP_CIPHER_CTX_reset;
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::EVP_CIPHER_CTX;
+    fn drop = ffi::EVP_CIPHER_CTX_free;
+
+    /// A context object used to perform symmetric encryption and decryption.
+    pub struct CipherCtx;
+    /// Reference to a [`CipherCtx`].
+    ///
+    /// [`CipherCtx`]: struct.CipherCtx.html
+    pub struct CipherCtxRef;
+}
+
+impl CipherCtx {
+    /// Creates a new, uninitialized context.
+    ///
+    /// This corresponds to [`EVP_CIPHER_CTX_new`].
+    ///
+    /// [`EVP_CIPHER_CTX_new`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CIPHER_CTX_new.html
+    pub fn new() -> Result<CipherCtx, ErrorStack> {
+        ffi::init();
+
+        unsafe {
+            let ptr = cvt_p(ffi::EVP_CIPHER_CTX_new())?;
+            Ok(CipherCtx::from_ptr(ptr))
+        }
+    }
+}
+
+impl CipherCtxRef {
+    /// Initializes the context for encryption.
+    ///
+    /// Any of the cipher, key and IV may be omitted, in which case the values from a previous call
+    /// are retained. This allows parameters such as the IV length to be configured after the
+    /// cipher is set but before the IV is provided, and allows the IV to be changed for each
+    /// message without reinitializing the key schedule.
+    ///
+    /// This corresponds to [`EVP_EncryptInit_ex`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key or IV is provided before a cipher has been set, or if the key or IV is
+    /// shorter than the cipher requires.
+    ///
+    /// [`EVP_EncryptInit_ex`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_EncryptInit_ex.html
+    pub fn encrypt_init(
+        &mut self,
+        type_: Option<Cipher>,
+        key: Option<&[u8]>,
+        iv: Option<&[u8]>,
+    ) -> Result<(), ErrorStack> {
+        self.cipher_init(type_.map_or(ptr::null(), |c| c.as_ptr()), key, iv, 1)
+    }
+
+    /// Initializes the context for decryption.
+    ///
+    /// This behaves in the same way as [`encrypt_init`].
+    ///
+    /// This corresponds to [`EVP_DecryptInit_ex`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key or IV is provided before a cipher has been set, or if the key or IV is
+    /// shorter than the cipher requires.
+    ///
+    /// [`encrypt_init`]: #method.encrypt_init
+    /// [`EVP_DecryptInit_ex`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_DecryptInit_ex.html
+    pub fn decrypt_init(
+        &mut self,
+        type_: Option<Cipher>,
+        key: Option<&[u8]>,
+        iv: Option<&[u8]>,
+    ) -> Result<(), ErrorStack> {
+        self.cipher_init(type_.map_or(ptr::null(), |c| c.as_ptr()), key, iv, 0)
+    }
+
+    /// Initializes the context for encryption with a cipher fetched from a provider.
+    ///
+    /// The context holds a reference to the cipher, so it remains usable after `type_` is
+    /// dropped.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key or IV is provided before a cipher has been set, or if the key or IV is
+    /// shorter than the cipher requires.
+    #[cfg(ossl300)]
+    pub fn encrypt_init_with_cipher(
+        &mut self,
+        type_: Option<&CipherRef>,
+        key: Option<&[u8]>,
+        iv: Option<&[u8]>,
+    ) -> Result<(), ErrorStack> {
+        self.cipher_init(type_.map_or(ptr::null(), |c| c.as_ptr()), key, iv, 1)
+    }
+
+    /// Initializes the context for decryption with a cipher fetched from a provider.
+    ///
+    /// The context holds a reference to the cipher, so it remains usable after `type_` is
+    /// dropped.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key or IV is provided before a cipher has been set, or if the key or IV is
+    /// shorter than the cipher requires.
+    #[cfg(ossl300)]
+    pub fn decrypt_init_with_cipher(
+        &mut self,
+        type_: Option<&CipherRef>,
+        key: Option<&[u8]>,
+        iv: Option<&[u8]>,
+    ) -> Result<(), ErrorStack> {
+        self.cipher_init(type_.map_or(ptr::null(), |c| c.as_ptr()), key, iv, 0)
+    }
+
+    fn cipher_init(
+        &mut self,
+        type_: *const ffi::EVP_CIPHER,
+        key: Option<&[u8]>,
+        iv: Option<&[u8]>,
+        enc: c_int,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            if !type_.is_null() {
+                cvt(ffi::EVP_CipherInit_ex(
+                    self.as_ptr(),
+                    type_,
+                    ptr::null_mut(),
+                    ptr::null(),
+                    ptr::null(),
+                    enc,
+                ))?;
+            }
+
+            if key.is_none() && iv.is_none() {
+                return Ok(());
+            }
+
+            assert!(
+                !ffi::EVP_CIPHER_CTX_cipher(self.as_ptr()).is_null(),
+                "a cipher must be set before the key or IV"
+            );
+            if let Some(key) = key {
+                assert!(key.len() >= self.key_length());
+            }
+            if let Some(iv) = iv {
+                assert!(iv.len() >= self.iv_length());
+            }
+
+            cvt(ffi::EVP_CipherInit_ex(
+                self.as_ptr(),
+                ptr::null(),
+                ptr::null_mut(),
+                key.map_or(ptr::null(), |k| k.as_ptr()),
+                iv.map_or(ptr::null(), |iv| iv.as_ptr()),
+                enc,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the block size of the context's cipher.
+    ///
+    /// Stream ciphers and AEAD modes such as GCM have a block size of 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context has not been initialized with a cipher.
+    pub fn block_size(&self) -> usize {
+        self.assert_cipher();
+        unsafe { ffi::EVP_CIPHER_CTX_block_size(self.as_ptr()) as usize }
+    }
+
+    /// Returns the length of keys expected by the context.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context has not been initialized with a cipher.
+    pub fn key_length(&self) -> usize {
+        self.assert_cipher();
+        unsafe { ffi::EVP_CIPHER_CTX_key_length(self.as_ptr()) as usize }
+    }
+
+    /// Sets the length of the key expected by the context.
+    ///
+    /// Only some ciphers support configurable key lengths.
+    ///
+    /// This corresponds to [`EVP_CIPHER_CTX_set_key_length`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context has not been initialized with a cipher.
+    ///
+    /// [`EVP_CIPHER_CTX_set_key_length`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CIPHER_CTX_set_key_length.html
+    pub fn set_key_length(&mut self, len: usize) -> Result<(), ErrorStack> {
+        self.assert_cipher();
+        assert!(len <= c_int::max_value() as usize);
+        unsafe {
+            cvt(ffi::EVP_CIPHER_CTX_set_key_length(
+                self.as_ptr(),
+                len as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the length of the IV expected by the context.
+    ///
+    /// Returns 0 if the cipher does not use an IV.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context has not been initialized with a cipher.
+    pub fn iv_length(&self) -> usize {
+        self.assert_cipher();
+        unsafe { ffi::EVP_CIPHER_CTX_iv_length(self.as_ptr()) as usize }
+    }
+
+    /// Sets the length of the IV expected by the context.
+    ///
+    /// Only some AEAD ciphers such as GCM, CCM and OCB support configurable IV lengths. This must
+    /// be called after the cipher is set and before the IV is provided.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context has not been initialized with a cipher.
+    pub fn set_iv_length(&mut self, len: usize) -> Result<(), ErrorStack> {
+        self.assert_cipher();
+        assert!(len <= c_int::max_value() as usize);
+        unsafe {
+            cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                self.as_ptr(),
+                ffi::EVP_CTRL_GCM_SET_IVLEN,
+                len as c_int,
+                ptr::null_mut(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the length of the authentication tag produced when encrypting with CCM or OCB.
+    ///
+    /// This must be called after the cipher is set and before the key is provided. GCM always
+    /// produces a full length tag, which may be truncated by the caller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context has not been initialized with a cipher.
+    pub fn set_tag_length(&mut self, len: usize) -> Result<(), ErrorStack> {
+        self.assert_cipher();
+        assert!(len <= c_int::max_value() as usize);
+        unsafe {
+            cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                self.as_ptr(),
+                ffi::EVP_CTRL_GCM_SET_TAG,
+                len as c_int,
+                ptr::null_mut(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Retrieves the authentication tag after encrypting with an AEAD cipher.
+    ///
+    /// This must be called after [`finalize`]. The length of `tag` determines the length of the
+    /// tag retrieved.
+    ///
+    /// [`finalize`]: #method.finalize
+    pub fn tag(&self, tag: &mut [u8]) -> Result<(), ErrorStack> {
+        assert!(tag.len() <= c_int::max_value() as usize);
+        unsafe {
+            cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                self.as_ptr(),
+                ffi::EVP_CTRL_GCM_GET_TAG,
+                tag.len() as c_int,
+                tag.as_mut_ptr() as *mut _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the expected authentication tag when decrypting with an AEAD cipher.
+    ///
+    /// For GCM and OCB this must be called before [`finalize`]. For CCM it must be called before
+    /// the key is provided.
+    ///
+    /// [`finalize`]: #method.finalize
+    pub fn set_tag(&mut self, tag: &[u8]) -> Result<(), ErrorStack> {
+        assert!(tag.len() <= c_int::max_value() as usize);
+        unsafe {
+            cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                self.as_ptr(),
+                ffi::EVP_CTRL_GCM_SET_TAG,
+                tag.len() as c_int,
+                tag.as_ptr() as *mut _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Enables or disables padding.
+    ///
+    /// If padding is disabled, the total amount of data processed must be a multiple of the
+    /// cipher's block size. Padding is enabled by default.
+    ///
+    /// This corresponds to [`EVP_CIPHER_CTX_set_padding`].
+    ///
+    /// [`EVP_CIPHER_CTX_set_padding`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CIPHER_CTX_set_padding.html
+    pub fn set_padding(&mut self, padding: bool) {
+        unsafe {
+            ffi::EVP_CIPHER_CTX_set_padding(self.as_ptr(), padding as c_int);
+        }
+    }
+
+    /// Sets the total length of the plaintext or ciphertext when using CCM.
+    ///
+    /// CCM requires the length of the data to be known up front. This must be called after the
+    /// key and IV are provided, and before any AAD or data is processed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context has not been initialized with a cipher.
+    pub fn set_data_len(&mut self, len: usize) -> Result<(), ErrorStack> {
+        self.assert_cipher();
+        assert!(len <= c_int::max_value() as usize);
+        unsafe {
+            let mut outl = 0;
+            cvt(ffi::EVP_CipherUpdate(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut outl,
+                ptr::null(),
+                len as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Feeds additional authenticated data into an AEAD cipher.
+    ///
+    /// This must be called before any plaintext or ciphertext is processed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context has not been initialized with a cipher.
+    pub fn aad_update(&mut self, aad: &[u8]) -> Result<(), ErrorStack> {
+        self.assert_cipher();
+        assert!(aad.len() <= c_int::max_value() as usize);
+        unsafe {
+            let mut outl = 0;
+            cvt(ffi::EVP_CipherUpdate(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut outl,
+                aad.as_ptr(),
+                aad.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Feeds data from `input` through the cipher, writing encrypted/decrypted bytes into
+    /// `output`.
+    ///
+    /// The number of bytes written to `output` is returned. Note that this may not be equal to
+    /// the length of `input`.
+    ///
+    /// This corresponds to [`EVP_CipherUpdate`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output.len()` is less than `input.len()` plus the cipher's block size (or
+    /// `input.len()` for ciphers with a block size of 1).
+    ///
+    /// [`EVP_CipherUpdate`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CipherUpdate.html
+    pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, ErrorStack> {
+        assert!(output.len() >= self.min_output_len(input.len()));
+        unsafe { self.update_unchecked(input.as_ptr(), input.len(), output.as_mut_ptr()) }
+    }
+
+    /// Feeds the first `inlen` bytes of `data` through the cipher, writing the output back into
+    /// `data`.
+    ///
+    /// This avoids the need for a separate output buffer. The number of bytes written to `data`
+    /// is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data.len()` is less than `inlen` plus the cipher's block size (or `inlen` for
+    /// ciphers with a block size of 1).
+    pub fn update_inplace(&mut self, data: &mut [u8], inlen: usize) -> Result<usize, ErrorStack> {
+        assert!(inlen <= data.len());
+        assert!(data.len() >= self.min_output_len(inlen));
+        let ptr = data.as_mut_ptr();
+        unsafe { self.update_unchecked(ptr, inlen, ptr) }
+    }
+
+    unsafe fn update_unchecked(
+        &mut self,
+        input: *const u8,
+        inlen: usize,
+        output: *mut u8,
+    ) -> Result<usize, ErrorStack> {
+        assert!(inlen <= c_int::max_value() as usize);
+        let mut outl = 0;
+        cvt(ffi::EVP_CipherUpdate(
+            self.as_ptr(),
+            output,
+            &mut outl,
+            input,
+            inlen as c_int,
+        ))?;
+        Ok(outl as usize)
+    }
+
+    /// Finishes the encryption/decryption process, writing any remaining data to `output`.
+    ///
+    /// The number of bytes written to `output` is returned. When decrypting with an AEAD cipher,
+    /// this returns an error if the authentication tag does not match.
+    ///
+    /// This corresponds to [`EVP_CipherFinal_ex`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output` is shorter than the cipher's block size, for ciphers with a block size
+    /// greater than 1.
+    ///
+    /// [`EVP_CipherFinal_ex`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CipherFinal_ex.html
+    pub fn finalize(&mut self, output: &mut [u8]) -> Result<usize, ErrorStack> {
+        let block_size = self.block_size();
+        if block_size > 1 {
+            assert!(output.len() >= block_size);
+        }
+        unsafe {
+            let mut outl = 0;
+            cvt(ffi::EVP_CipherFinal_ex(
+                self.as_ptr(),
+                output.as_mut_ptr(),
+                &mut outl,
+            ))?;
+            Ok(outl as usize)
+        }
+    }
+
+    /// Clears all state from the context, including the cipher, key and IV.
+    ///
+    /// The context may be reused by initializing it again.
+    ///
+    /// This corresponds to [`EVP_CIPHER_CTX_reset`].
+    ///
+    /// [`EVP_CIPHER_CTX_reset`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CIPHER_CTX_reset.html
+    pub fn reset(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(EVP_CIPHER_CTX_reset(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Replaces the state of this context with a copy of the state of `src`.
+    ///
+    /// This can be used to set up a key schedule once and cheaply clone it for each message.
+    ///
+    /// This corresponds to [`EVP_CIPHER_CTX_copy`].
+    ///
+    /// [`EVP_CIPHER_CTX_copy`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_CIPHER_CTX_copy.html
+    pub fn copy(&mut self, src: &CipherCtxRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::EVP_CIPHER_CTX_copy(self.as_ptr(), src.as_ptr())).map(|_| ()) }
+    }
+
+    fn min_output_len(&self, inlen: usize) -> usize {
+        let block_size = self.block_size();
+        if block_size > 1 {
+            inlen + block_size
+        } else {
+            inlen
+        }
+    }
+
+    fn assert_cipher(&self) {
+        unsafe {
+            assert!(
+                !ffi::EVP_CIPHER_CTX_cipher(self.as_ptr()).is_null(),
+                "the context has not been initialized with a cipher"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex::FromHex;
+
+    // taken from the symm module's AES-128-GCM test
+    const GCM_KEY: &str = "23dc8d23d95b6fd1251741a64f7d4f41";
+    const GCM_IV: &str = "f416f48ad44d9efa1179e167";
+    const GCM_PT: &str = "6cb9b71dd0ccd42cdf87e8e396fc581fd8e0d700e360f590593b748e105390de";
+    const GCM_AAD: &str = "45074844c97d515c65bbe37c210a5a4b08c21c588efe5c5f73c4d9c17d34dacddc0bb6a8a53f7bf477b9780c1c2a928660df87016b2873fe876b2b887fb5886bfd63216b7eaecc046372a82c047eb043f0b063226ee52a12c69b";
+    const GCM_CT: &str = "8ad20486778e87387efb3f2574e509951c0626816722018129e578b2787969d3";
+    const GCM_TAG: &str = "91e1bc09";
+
+    fn gcm_encrypt(ctx: &mut CipherCtxRef, iv: &[u8]) -> (Vec<u8>, [u8; 4]) {
+        ctx.encrypt_init(None, None, Some(iv)).unwrap();
+        ctx.aad_update(&Vec::from_hex(GCM_AAD).unwrap()).unwrap();
+        let pt = Vec::from_hex(GCM_PT).unwrap();
+        let mut ct = vec![0; pt.len()];
+        let len = ctx.update(&pt, &mut ct).unwrap();
+        assert_eq!(ctx.finalize(&mut ct[len..]).unwrap(), 0);
+        let mut tag = [0; 4];
+        ctx.tag(&mut tag).unwrap();
+        (ct, tag)
+    }
+
+    #[test]
+    fn gcm_reuse() {
+        let key = Vec::from_hex(GCM_KEY).unwrap();
+        let iv = Vec::from_hex(GCM_IV).unwrap();
+
+        let mut ctx = CipherCtx::new().unwrap();
+        ctx.encrypt_init(Some(Cipher::aes_128_gcm()), Some(&key), None)
+            .unwrap();
+        assert_eq!(ctx.block_size(), 1);
+        assert_eq!(ctx.key_length(), 16);
+        assert_eq!(ctx.iv_length(), 12);
+
+        let (ct, tag) = gcm_encrypt(&mut ctx, &iv);
+        assert_eq!(hex::encode(&ct), GCM_CT);
+        assert_eq!(hex::encode(&tag), GCM_TAG);
+
+        // a new IV produces a different ciphertext without rekeying
+        let (ct2, _) = gcm_encrypt(&mut ctx, &[0; 12]);
+        assert_ne!(ct, ct2);
+
+        // and going back to the original IV reproduces the original output
+        let (ct3, tag3) = gcm_encrypt(&mut ctx, &iv);
+        assert_eq!(ct, ct3);
+        assert_eq!(tag, tag3);
+    }
+
+    #[test]
+    fn gcm_decrypt_inplace() {
+        let mut ctx = CipherCtx::new().unwrap();
+        ctx.decrypt_init(Some(Cipher::aes_128_gcm()), None, None)
+            .unwrap();
+        ctx.set_iv_length(12).unwrap();
+        ctx.decrypt_init(
+            None,
+            Some(&Vec::from_hex(GCM_KEY).unwrap()),
+            Some(&Vec::from_hex(GCM_IV).unwrap()),
+        )
+        .unwrap();
+        ctx.aad_update(&Vec::from_hex(GCM_AAD).unwrap()).unwrap();
+
+        let mut data = Vec::from_hex(GCM_CT).unwrap();
+        let len = data.len();
+        assert_eq!(ctx.update_inplace(&mut data, len).unwrap(), len);
+        ctx.set_tag(&Vec::from_hex(GCM_TAG).unwrap()).unwrap();
+        assert_eq!(ctx.finalize(&mut []).unwrap(), 0);
+        assert_eq!(hex::encode(&data), GCM_PT);
+
+        // a bad tag is rejected
+        ctx.decrypt_init(None, None, Some(&Vec::from_hex(GCM_IV).unwrap()))
+            .unwrap();
+        ctx.aad_update(&Vec::from_hex(GCM_AAD).unwrap()).unwrap();
+        let mut data = Vec::from_hex(GCM_CT).unwrap();
+        ctx.update_inplace(&mut data, len).unwrap();
+        ctx.set_tag(&[0; 4]).unwrap();
+        assert!(ctx.finalize(&mut []).is_err());
+    }
+
+    // taken from the symm module's AES-128-CCM test
+    #[test]
+    fn ccm() {
+        let key = Vec::from_hex("3ee186594f110fb788a8bf8aa8be5d4a").unwrap();
+        let nonce = Vec::from_hex("44f705d52acf27b7f17196aa9b").unwrap();
+        let aad = Vec::from_hex("2c16724296ff85e079627be3053ea95adf35722c21886baba343bd6c79b5cb57")
+            .unwrap();
+        let pt = Vec::from_hex("d71864877f2578db092daba2d6a1f9f4698a9c356c7830a1").unwrap();
+        let ct = "b4dd74e7a0cc51aea45dfb401a41d5822c96901a83247ea0";
+        let tag = "d6965f5aa6e31302a9cc2b36";
+
+        let mut ctx = CipherCtx::new().unwrap();
+        ctx.encrypt_init(Some(Cipher::aes_128_ccm()), None, None)
+            .unwrap();
+        ctx.set_iv_length(nonce.len()).unwrap();
+        ctx.set_tag_length(12).unwrap();
+        ctx.encrypt_init(None, Some(&key), Some(&nonce)).unwrap();
+        ctx.set_data_len(pt.len()).unwrap();
+        ctx.aad_update(&aad).unwrap();
+        let mut out = vec![0; pt.len()];
+        ctx.update(&pt, &mut out).unwrap();
+        ctx.finalize(&mut []).unwrap();
+        let mut actual_tag = [0; 12];
+        ctx.tag(&mut actual_tag).unwrap();
+        assert_eq!(hex::encode(&out), ct);
+        assert_eq!(hex::encode(&actual_tag), tag);
+
+        ctx.reset().unwrap();
+        ctx.decrypt_init(Some(Cipher::aes_128_ccm()), None, None)
+            .unwrap();
+        ctx.set_iv_length(nonce.len()).unwrap();
+        ctx.set_tag(&Vec::from_hex(tag).unwrap()).unwrap();
+        ctx.decrypt_init(None, Some(&key), Some(&nonce)).unwrap();
+        ctx.set_data_len(out.len()).unwrap();
+        ctx.aad_update(&aad).unwrap();
+        let mut decrypted = vec![0; out.len()];
+        ctx.update(&out, &mut decrypted).unwrap();
+        assert_eq!(decrypted, pt);
+    }
+
+    #[test]
+    fn cbc_copy() {
+        let key = [0x42; 16];
+        let iv = [0x24; 16];
+        let pt = b"a message longer than a single block";
+
+        let mut template = CipherCtx::new().unwrap();
+        template
+            .encrypt_init(Some(Cipher::aes_128_cbc()), Some(&key), Some(&iv))
+            .unwrap();
+        assert_eq!(template.block_size(), 16);
+
+        let expected = crate::symm::encrypt(Cipher::aes_128_cbc(), &key, Some(&iv), pt).unwrap();
+        for _ in 0..2 {
+            let mut ctx = CipherCtx::new().unwrap();
+            ctx.copy(&template).unwrap();
+            let mut out = vec![0; pt.len() + 16];
+            let mut len = ctx.update(pt, &mut out).unwrap();
+            len += ctx.finalize(&mut out[len..]).unwrap();
+            out.truncate(len);
+            assert_eq!(out, expected);
+        }
+
+        // in place, with padding disabled
+        let mut ctx = CipherCtx::new().unwrap();
+        ctx.copy(&template).unwrap();
+        ctx.set_padding(false);
+        let mut data = pt[..32].to_vec();
+        data.resize(48, 0);
+        assert_eq!(ctx.update_inplace(&mut data, 32).unwrap(), 32);
+        assert_eq!(ctx.finalize(&mut data[32..]).unwrap(), 0);
+        assert_eq!(&data[..32], &expected[..32]);
+    }
+
+    #[test]
+    #[should_panic(expected = "a cipher must be set before the key or IV")]
+    fn key_without_cipher() {
+        let mut ctx = CipherCtx::new().unwrap();
+        let _ = ctx.encrypt_init(None, Some(&[0; 16]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "the context has not been initialized with a cipher")]
+    fn aad_without_cipher() {
+        let mut ctx = CipherCtx::new().unwrap();
+        let _ = ctx.aad_update(b"aad");
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn fetched_cipher() {
+        let cipher = crate::cipher::Cipher::fetch("AES-128-GCM", Some("provider=default")).unwrap();
+        let mut ctx = CipherCtx::new().unwrap();
+        ctx.encrypt_init_with_cipher(Some(&cipher), Some(&Vec::from_hex(GCM_KEY).unwrap()), None)
+            .unwrap();
+        drop(cipher);
+
+        let (ct, tag) = gcm_encrypt(&mut ctx, &Vec::from_hex(GCM_IV).unwrap());
+        assert_eq!(hex::encode(&ct), GCM_CT);
+        assert_eq!(hex::encode(&tag), GCM_TAG);
+    }
+}
@@ -4,21 +4,37 @@
 //! X.509 certificates.  The OpenSSL implementation of CMS is used in email encryption
 //! generated from a `Vec` of bytes.  This `Vec` follows the smime protocol standards.
 //! Data accepted by this module will be smime type `enveloped-data`.
+//!
+//! `SignedData` structures with several signers, custom attributes, or content too large to be
+//! held in memory are built with [`CmsSignedDataBuilder`], and verified per signer with
+//! [`CmsContentInfoRef::verify_stream`].
+//!
+//! Likewise, [`CmsEnvelopedDataBuilder`] encrypts to recipients using key agreement,
+//! pre-shared keys or passwords in addition to RSA key transport.
+//!
+//! [`CmsSignedDataBuilder`]: struct.CmsSignedDataBuilder.html
+//! [`CmsEnvelopedDataBuilder`]: struct.CmsEnvelopedDataBuilder.html
+//! [`CmsContentInfoRef::verify_stream`]: struct.CmsContentInfoRef.html#method.verify_stream
 
 use bitflags::bitflags;
 use foreign_types::{ForeignType, ForeignTypeRef};
-use libc::c_uint;
+use libc::{c_int, c_uint, ssize_t};
+use std::cmp;
+use std::io::{self, Write};
+use std::mem;
 use std::ptr;
 
-use crate::asn1::Asn1OctetStringRef;
+use crate::asn1::{Asn1ObjectRef, Asn1OctetStringRef, Asn1StringRef, Asn1TimeRef, Asn1Type};
 use crate::bio::{MemBio, MemBioSlice};
 use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
 use crate::pkey::{HasPrivate, PKeyRef};
-use crate::stack::{Stack, StackRef};
+use crate::stack::{Stack, StackRef, Stackable};
 use crate::symm::Cipher;
+use crate::util::ForeignTypeRefExt;
 use crate::x509::crl::X509CRL;
 use crate::x509::store::X509StoreRef;
-use crate::x509::{X509Ref, X509};
+use crate::x509::{X509AlgorithmRef, X509Ref, X509};
 use crate::{cvt, cvt_p};
 
 bitflags! {
@@ -69,6 +85,30 @@ foreign_type_and_impl_send_sync! {
     pub struct CmsContentInfoRef;
 }
 
+/// fake free method, since a `CMS_SignerInfo` is owned by its `CMS_ContentInfo`
+unsafe fn free_signer_info(_si: *mut ffi::CMS_SignerInfo) {}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::CMS_SignerInfo;
+    fn drop = free_signer_info;
+
+    /// A signer of a CMS `SignedData` structure.
+    ///
+    /// Signers belong to the [`CmsContentInfo`] they were read from or added to, and are only
+    /// handed out by reference.
+    ///
+    /// [`CmsContentInfo`]: struct.CmsContentInfo.html
+    pub struct CmsSignerInfo;
+    /// Reference to [`CmsSignerInfo`]
+    ///
+    /// [`CmsSignerInfo`]: struct.CmsSignerInfo.html
+    pub struct CmsSignerInfoRef;
+}
+
+impl Stackable for CmsSignerInfo {
+    type StackType = ffi::stack_st_CMS_SignerInfo;
+}
+
 impl CmsContentInfoRef {
     /// Given the sender's private key, `pkey` and the recipient's certificiate, `cert`,
     /// decrypt the data in `self`.
@@ -127,6 +167,191 @@ impl CmsContentInfoRef {
         }
     }
 
+    /// Decrypts the data in `self` for a recipient using a pre-shared key-encryption key.
+    ///
+    /// If `key_id` is set, only the recipient with that key identifier is tried.
+    ///
+    /// This corresponds to [`CMS_decrypt_set1_key`].
+    ///
+    /// [`CMS_decrypt_set1_key`]: https://www.openssl.org/docs/manmaster/man3/CMS_decrypt_set1_key.html
+    pub fn decrypt_with_key(
+        &self,
+        key_id: Option<&[u8]>,
+        key: &[u8],
+    ) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let (id, idlen) = key_id.map_or((ptr::null(), 0), |id| (id.as_ptr(), id.len()));
+            cvt(ffi::CMS_decrypt_set1_key(
+                self.as_ptr(),
+                key.as_ptr() as *mut _,
+                key.len(),
+                id,
+                idlen,
+            ))?;
+
+            self.decrypt_content()
+        }
+    }
+
+    /// Decrypts the data in `self` for a password recipient.
+    ///
+    /// This corresponds to [`CMS_decrypt_set1_password`].
+    ///
+    /// [`CMS_decrypt_set1_password`]: https://www.openssl.org/docs/manmaster/man3/CMS_decrypt_set1_password.html
+    pub fn decrypt_with_password(&self, password: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            cvt(ffi::CMS_decrypt_set1_password(
+                self.as_ptr(),
+                password.as_ptr() as *mut _,
+                password.len() as ssize_t,
+            ))?;
+
+            self.decrypt_content()
+        }
+    }
+
+    // decrypts with the content-encryption key recovered by CMS_decrypt_set1_*
+    fn decrypt_content(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let out = MemBio::new()?;
+
+            cvt(ffi::CMS_decrypt(
+                self.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                out.as_ptr(),
+                0,
+            ))?;
+
+            Ok(out.get_buf().to_owned())
+        }
+    }
+
+    /// Returns the signers of a `SignedData` structure.
+    ///
+    /// This corresponds to [`CMS_get0_SignerInfos`].
+    ///
+    /// [`CMS_get0_SignerInfos`]: https://www.openssl.org/docs/manmaster/man3/CMS_get0_SignerInfos.html
+    pub fn signer_infos(&self) -> Result<&StackRef<CmsSignerInfo>, ErrorStack> {
+        unsafe {
+            let signers = cvt_p(ffi::CMS_get0_SignerInfos(self.as_ptr()))?;
+            Ok(StackRef::from_ptr(signers))
+        }
+    }
+
+    /// Like [`signer_infos`], but returns mutable references to the signers.
+    ///
+    /// [`signer_infos`]: #method.signer_infos
+    pub fn signer_infos_mut(&mut self) -> Result<&mut StackRef<CmsSignerInfo>, ErrorStack> {
+        unsafe {
+            let signers = cvt_p(ffi::CMS_get0_SignerInfos(self.as_ptr()))?;
+            Ok(StackRef::from_ptr_mut(signers))
+        }
+    }
+
+    /// Adds a certificate to a `SignedData` structure.
+    ///
+    /// This corresponds to [`CMS_add1_cert`].
+    ///
+    /// [`CMS_add1_cert`]: https://www.openssl.org/docs/manmaster/man3/CMS_add1_cert.html
+    pub fn add_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::CMS_add1_cert(self.as_ptr(), cert.as_ptr())).map(|_| ()) }
+    }
+
+    /// Adds a signer to a `SignedData` structure.
+    ///
+    /// `signcert` and `pkey` are the certificate and private key of the signer, and `md` the
+    /// digest it signs with. Unless `CMSOptions::NOATTR` is set, the signer carries signed
+    /// attributes, which may be extended through the returned reference until the structure is
+    /// finalized.
+    ///
+    /// To add a signer to a structure which has already been finalized, set
+    /// `CMSOptions::REUSE_DIGEST`. The content digest is then copied from an existing signer using
+    /// the same digest, and the new signer is signed immediately. If `CMSOptions::PARTIAL` is set
+    /// as well, the signer is left unsigned so that attributes can be added, and
+    /// [`CmsSignerInfoRef::sign`] must be called afterwards.
+    ///
+    /// This corresponds to [`CMS_add1_signer`].
+    ///
+    /// [`CmsSignerInfoRef::sign`]: struct.CmsSignerInfoRef.html#method.sign
+    /// [`CMS_add1_signer`]: https://www.openssl.org/docs/manmaster/man3/CMS_add1_signer.html
+    pub fn add_signer<T>(
+        &mut self,
+        signcert: &X509Ref,
+        pkey: &PKeyRef<T>,
+        md: MessageDigest,
+        flags: CMSOptions,
+    ) -> Result<&mut CmsSignerInfoRef, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            let si = cvt_p(ffi::CMS_add1_signer(
+                self.as_ptr(),
+                signcert.as_ptr(),
+                pkey.as_ptr(),
+                md.as_ptr(),
+                flags.bits(),
+            ))?;
+            Ok(CmsSignerInfoRef::from_ptr_mut(si))
+        }
+    }
+
+    /// Starts the verification of a `SignedData` structure, reporting a result for each signer.
+    ///
+    /// The signer certificates are located and verified against `trust` as by
+    /// [`CmsContentInfo::verify`]. The signed content is then written to the returned stream,
+    /// which makes it possible to verify detached signatures over content too large to be held in
+    /// memory. For a structure with attached content, write the content returned by
+    /// [`get_content`].
+    ///
+    /// `CMSOptions::NO_ATTR_VERIFY` and `CMSOptions::NO_CONTENT_VERIFY` are applied by the stream
+    /// to each signer.
+    ///
+    /// [`CmsContentInfo::verify`]: struct.CmsContentInfo.html#method.verify
+    /// [`get_content`]: struct.CmsContentInfo.html#method.get_content
+    pub fn verify_stream(
+        &mut self,
+        certs: Option<&StackRef<X509>>,
+        trust: &X509StoreRef,
+        flags: CMSOptions,
+    ) -> Result<CmsVerifyStream<'_>, ErrorStack> {
+        unsafe {
+            let certs = certs.map_or(ptr::null_mut(), |p| p.as_ptr());
+            // CMS_verify requires the content of detached signatures, but with content
+            // verification disabled it never looks at it.
+            let empty = MemBioSlice::new(&[])?;
+            let verify_flags = flags
+                | CMSOptions::NO_ATTR_VERIFY
+                | CMSOptions::NO_CONTENT_VERIFY
+                | CMSOptions::BINARY;
+            cvt(ffi::CMS_verify(
+                self.as_ptr(),
+                certs,
+                trust.as_ptr(),
+                empty.as_ptr(),
+                ptr::null_mut(),
+                verify_flags.bits(),
+            ))?;
+
+            let null = cvt_p(ffi::BIO_new(ffi::BIO_s_null()))?;
+            let bio = match cvt_p(ffi::CMS_dataInit(self.as_ptr(), null)) {
+                Ok(bio) => CmsBio(bio),
+                Err(e) => {
+                    ffi::BIO_free_all(null);
+                    return Err(e);
+                }
+            };
+
+            Ok(CmsVerifyStream {
+                bio,
+                cms: self,
+                flags,
+            })
+        }
+    }
+
     to_der! {
         /// Serializes this CmsContentInfo using DER.
         ///
@@ -306,12 +531,860 @@ impl CmsContentInfo {
     }
 }
 
+impl CmsSignerInfoRef {
+    fn algs(&self) -> (*mut ffi::X509, *mut ffi::X509_ALGOR, *mut ffi::X509_ALGOR) {
+        unsafe {
+            let mut signer = ptr::null_mut();
+            let mut digest = ptr::null_mut();
+            let mut signature = ptr::null_mut();
+            ffi::CMS_SignerInfo_get0_algs(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut signer,
+                &mut digest,
+                &mut signature,
+            );
+            (signer, digest, signature)
+        }
+    }
+
+    /// Returns the certificate of the signer, if known.
+    ///
+    /// The certificate is known for signers added to a structure, and for signers of a structure
+    /// once it has been verified.
+    ///
+    /// This corresponds to [`CMS_SignerInfo_get0_algs`].
+    ///
+    /// [`CMS_SignerInfo_get0_algs`]: https://www.openssl.org/docs/manmaster/man3/CMS_SignerInfo_get0_algs.html
+    pub fn signer_cert(&self) -> Option<&X509Ref> {
+        unsafe { X509Ref::from_const_ptr_opt(self.algs().0) }
+    }
+
+    /// Returns the algorithm used to digest the content and signed attributes.
+    ///
+    /// This corresponds to [`CMS_SignerInfo_get0_algs`].
+    ///
+    /// [`CMS_SignerInfo_get0_algs`]: https://www.openssl.org/docs/manmaster/man3/CMS_SignerInfo_get0_algs.html
+    pub fn digest_algorithm(&self) -> &X509AlgorithmRef {
+        unsafe { X509AlgorithmRef::from_const_ptr(self.algs().1) }
+    }
+
+    /// Returns the signature algorithm.
+    ///
+    /// This corresponds to [`CMS_SignerInfo_get0_algs`].
+    ///
+    /// [`CMS_SignerInfo_get0_algs`]: https://www.openssl.org/docs/manmaster/man3/CMS_SignerInfo_get0_algs.html
+    pub fn signature_algorithm(&self) -> &X509AlgorithmRef {
+        unsafe { X509AlgorithmRef::from_const_ptr(self.algs().2) }
+    }
+
+    /// Returns the signature value.
+    ///
+    /// The signature is empty until the signer has been signed.
+    ///
+    /// This corresponds to [`CMS_SignerInfo_get0_signature`].
+    ///
+    /// [`CMS_SignerInfo_get0_signature`]: https://www.openssl.org/docs/manmaster/man3/CMS_SignerInfo_get0_signature.html
+    #[cfg(ossl110)]
+    pub fn signature(&self) -> &[u8] {
+        unsafe {
+            let signature = ffi::CMS_SignerInfo_get0_signature(self.as_ptr());
+            Asn1OctetStringRef::from_ptr(signature).as_slice()
+        }
+    }
+
+    /// Determines if `cert` is the certificate identified by this signer.
+    ///
+    /// This corresponds to [`CMS_SignerInfo_cert_cmp`].
+    ///
+    /// [`CMS_SignerInfo_cert_cmp`]: https://www.openssl.org/docs/manmaster/man3/CMS_SignerInfo_cert_cmp.html
+    pub fn matches_cert(&self, cert: &X509Ref) -> bool {
+        unsafe { ffi::CMS_SignerInfo_cert_cmp(self.as_ptr(), cert.as_ptr()) == 0 }
+    }
+
+    /// Returns the signing time, if the signer carries one.
+    pub fn signing_time(&self) -> Option<&Asn1TimeRef> {
+        unsafe {
+            let loc =
+                ffi::CMS_signed_get_attr_by_NID(self.as_ptr(), ffi::NID_pkcs9_signingTime, -1);
+            if loc < 0 {
+                return None;
+            }
+            let attr = ffi::CMS_signed_get_attr(self.as_ptr(), loc);
+            let value = ffi::X509_ATTRIBUTE_get0_type(attr, 0);
+            if value.is_null() {
+                return None;
+            }
+            let ty = ffi::ASN1_TYPE_get(value);
+            if ty != ffi::V_ASN1_UTCTIME && ty != ffi::V_ASN1_GENERALIZEDTIME {
+                return None;
+            }
+            let time = ffi::X509_ATTRIBUTE_get0_data(attr, 0, ty, ptr::null_mut());
+            Asn1TimeRef::from_const_ptr_opt(time as *const ffi::ASN1_TIME)
+        }
+    }
+
+    /// Sets the signing time.
+    ///
+    /// By default, OpenSSL records the current time when signing.
+    pub fn set_signing_time(&mut self, time: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            let time = Asn1StringRef::from_ptr(time.as_ptr() as *mut ffi::ASN1_STRING);
+            let value = time.as_slice();
+            cvt(ffi::CMS_signed_add1_attr_by_NID(
+                self.as_ptr(),
+                ffi::NID_pkcs9_signingTime,
+                ffi::ASN1_STRING_type(time.as_ptr()),
+                value.as_ptr() as *const _,
+                value.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds a signed attribute.
+    ///
+    /// For string types, `value` holds the contents of the string. For `Asn1Type::SEQUENCE` and
+    /// `Asn1Type::SET`, it holds the complete DER encoding of the value.
+    ///
+    /// Signed attributes must be added before the signer is signed.
+    ///
+    /// This corresponds to [`CMS_signed_add1_attr_by_OBJ`].
+    ///
+    /// [`CMS_signed_add1_attr_by_OBJ`]: https://www.openssl.org/docs/manmaster/man3/CMS_signed_add1_attr_by_OBJ.html
+    pub fn add_signed_attribute(
+        &mut self,
+        oid: &Asn1ObjectRef,
+        ty: Asn1Type,
+        value: &[u8],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(value.len() <= c_int::max_value() as usize);
+            cvt(ffi::CMS_signed_add1_attr_by_OBJ(
+                self.as_ptr(),
+                oid.as_ptr(),
+                ty.as_raw(),
+                value.as_ptr() as *const _,
+                value.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds an unsigned attribute.
+    ///
+    /// `value` is interpreted as by [`add_signed_attribute`]. Unsigned attributes are not covered
+    /// by the signature, and may be added at any time.
+    ///
+    /// This corresponds to [`CMS_unsigned_add1_attr_by_OBJ`].
+    ///
+    /// [`add_signed_attribute`]: #method.add_signed_attribute
+    /// [`CMS_unsigned_add1_attr_by_OBJ`]: https://www.openssl.org/docs/manmaster/man3/CMS_unsigned_add1_attr_by_OBJ.html
+    pub fn add_unsigned_attribute(
+        &mut self,
+        oid: &Asn1ObjectRef,
+        ty: Asn1Type,
+        value: &[u8],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(value.len() <= c_int::max_value() as usize);
+            cvt(ffi::CMS_unsigned_add1_attr_by_OBJ(
+                self.as_ptr(),
+                oid.as_ptr(),
+                ty.as_raw(),
+                value.as_ptr() as *const _,
+                value.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Signs a signer added with `CMSOptions::REUSE_DIGEST` and `CMSOptions::PARTIAL`.
+    ///
+    /// This corresponds to [`CMS_SignerInfo_sign`].
+    ///
+    /// [`CMS_SignerInfo_sign`]: https://www.openssl.org/docs/manmaster/man3/CMS_SignerInfo_sign.html
+    pub fn sign(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::CMS_SignerInfo_sign(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Counter-signs the signature of this signer.
+    ///
+    /// The counter-signature is added as an unsigned attribute, as described in [RFC 5652]. It
+    /// carries no signed attributes and no certificates, so the certificate of the
+    /// counter-signer should be added to the structure with [`CmsContentInfoRef::add_cert`].
+    ///
+    /// [RFC 5652]: https://tools.ietf.org/html/rfc5652#section-11.4
+    /// [`CmsContentInfoRef::add_cert`]: struct.CmsContentInfoRef.html#method.add_cert
+    #[cfg(ossl110)]
+    pub fn add_counter_signature<T>(
+        &mut self,
+        signcert: &X509Ref,
+        pkey: &PKeyRef<T>,
+        md: MessageDigest,
+        flags: CMSOptions,
+    ) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        let mut builder = CmsSignedDataBuilder::new(CMSOptions::DETACHED | CMSOptions::BINARY)?;
+        builder.add_signer(
+            signcert,
+            pkey,
+            md,
+            flags | CMSOptions::NOATTR | CMSOptions::CMS_NOCERTS,
+        )?;
+        let mut stream = builder.stream()?;
+        stream.bio.write_all(self.signature())?;
+        let counter_signature = stream.finish()?.to_der()?;
+        let signer_info = der_signer_info(&counter_signature)?;
+
+        unsafe {
+            cvt(ffi::CMS_unsigned_add1_attr_by_NID(
+                self.as_ptr(),
+                ffi::NID_pkcs9_countersignature,
+                ffi::V_ASN1_SEQUENCE,
+                signer_info.as_ptr() as *const _,
+                signer_info.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the counter-signatures of this signer.
+    ///
+    /// Each counter-signature is returned as a `SignedData` structure with detached content. It
+    /// is verified by passing this signer's signature as the content to
+    /// [`CmsContentInfoRef::verify_stream`].
+    ///
+    /// [`CmsContentInfoRef::verify_stream`]: struct.CmsContentInfoRef.html#method.verify_stream
+    pub fn counter_signatures(&self) -> Result<Vec<CmsContentInfo>, ErrorStack> {
+        let mut counter_signatures = vec![];
+        unsafe {
+            let mut loc = -1;
+            loop {
+                loc = ffi::CMS_unsigned_get_attr_by_NID(
+                    self.as_ptr(),
+                    ffi::NID_pkcs9_countersignature,
+                    loc,
+                );
+                if loc < 0 {
+                    break;
+                }
+                let attr = ffi::CMS_unsigned_get_attr(self.as_ptr(), loc);
+                for i in 0..ffi::X509_ATTRIBUTE_count(attr) {
+                    let value = ffi::X509_ATTRIBUTE_get0_data(
+                        attr,
+                        i,
+                        ffi::V_ASN1_SEQUENCE,
+                        ptr::null_mut(),
+                    );
+                    let value = Asn1StringRef::from_ptr(cvt_p(value as *mut ffi::ASN1_STRING)?);
+                    let der = der_signed_data(value.as_slice())?;
+                    counter_signatures.push(CmsContentInfo::from_der(&der)?);
+                }
+            }
+        }
+        Ok(counter_signatures)
+    }
+}
+
+/// A builder for CMS `SignedData` structures.
+///
+/// Unlike [`CmsContentInfo::sign`], the builder supports any number of signers, each with its own
+/// digest and attributes, and signs content streamed to it rather than held in memory.
+///
+/// # Examples
+///
+/// ```no_run
+/// use openssl::cms::{CMSOptions, CmsSignedDataBuilder};
+/// use openssl::hash::MessageDigest;
+/// use openssl::pkey::PKey;
+/// use openssl::x509::X509;
+/// use std::fs::{self, File};
+/// use std::io;
+///
+/// let cert = X509::from_pem(&fs::read("signer.pem").unwrap()).unwrap();
+/// let key = PKey::private_key_from_pem(&fs::read("signer.key").unwrap()).unwrap();
+///
+/// let mut builder = CmsSignedDataBuilder::new(CMSOptions::DETACHED).unwrap();
+/// builder
+///     .add_signer(&cert, &key, MessageDigest::sha256(), CMSOptions::empty())
+///     .unwrap();
+/// let mut stream = builder.stream().unwrap();
+/// io::copy(&mut File::open("artifact.tar").unwrap(), &mut stream).unwrap();
+/// let cms = stream.finish().unwrap();
+/// fs::write("artifact.tar.p7s", cms.to_der().unwrap()).unwrap();
+/// ```
+///
+/// [`CmsContentInfo::sign`]: struct.CmsContentInfo.html#method.sign
+pub struct CmsSignedDataBuilder(CmsContentInfo);
+
+impl CmsSignedDataBuilder {
+    /// Creates a new builder.
+    ///
+    /// Set `CMSOptions::DETACHED` to leave the content out of the structure.
+    ///
+    /// This corresponds to [`CMS_sign`].
+    ///
+    /// [`CMS_sign`]: https://www.openssl.org/docs/manmaster/man3/CMS_sign.html
+    pub fn new(flags: CMSOptions) -> Result<CmsSignedDataBuilder, ErrorStack> {
+        unsafe {
+            let cms = cvt_p(ffi::CMS_sign(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                (flags | CMSOptions::PARTIAL).bits(),
+            ))?;
+            Ok(CmsSignedDataBuilder(CmsContentInfo::from_ptr(cms)))
+        }
+    }
+
+    /// Adds a certificate to the structure.
+    ///
+    /// This corresponds to [`CMS_add1_cert`].
+    ///
+    /// [`CMS_add1_cert`]: https://www.openssl.org/docs/manmaster/man3/CMS_add1_cert.html
+    pub fn add_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        self.0.add_cert(cert)
+    }
+
+    /// Adds a signer to the structure.
+    ///
+    /// The returned reference can be used to add attributes to the signer.
+    ///
+    /// This corresponds to [`CMS_add1_signer`].
+    ///
+    /// [`CMS_add1_signer`]: https://www.openssl.org/docs/manmaster/man3/CMS_add1_signer.html
+    pub fn add_signer<T>(
+        &mut self,
+        signcert: &X509Ref,
+        pkey: &PKeyRef<T>,
+        md: MessageDigest,
+        flags: CMSOptions,
+    ) -> Result<&mut CmsSignerInfoRef, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        self.0.add_signer(signcert, pkey, md, flags)
+    }
+
+    /// Returns a stream to which the content to sign is written.
+    ///
+    /// The content is signed as written, without any of the MIME canonicalization applied by
+    /// [`CmsContentInfo::sign`] in the absence of `CMSOptions::BINARY`.
+    ///
+    /// This corresponds to [`CMS_dataInit`].
+    ///
+    /// [`CmsContentInfo::sign`]: struct.CmsContentInfo.html#method.sign
+    /// [`CMS_dataInit`]: https://www.openssl.org/docs/manmaster/man3/CMS_dataInit.html
+    pub fn stream(self) -> Result<CmsSignStream, ErrorStack> {
+        unsafe {
+            let bio = cvt_p(ffi::CMS_dataInit(self.0.as_ptr(), ptr::null_mut()))?;
+            Ok(CmsSignStream {
+                bio: CmsBio(bio),
+                cms: self.0,
+            })
+        }
+    }
+}
+
+/// A stream of content being signed into a CMS `SignedData` structure.
+///
+/// Created by [`CmsSignedDataBuilder::stream`].
+///
+/// [`CmsSignedDataBuilder::stream`]: struct.CmsSignedDataBuilder.html#method.stream
+pub struct CmsSignStream {
+    // must be dropped before the structure
+    bio: CmsBio,
+    cms: CmsContentInfo,
+}
+
+impl CmsSignStream {
+    /// Signs the content written so far, and returns the finished structure.
+    ///
+    /// This corresponds to [`CMS_dataFinal`].
+    ///
+    /// [`CMS_dataFinal`]: https://www.openssl.org/docs/manmaster/man3/CMS_dataInit.html
+    pub fn finish(mut self) -> Result<CmsContentInfo, ErrorStack> {
+        self.bio.flush()?;
+        unsafe {
+            cvt(ffi::CMS_dataFinal(self.cms.as_ptr(), self.bio.0))?;
+        }
+        let CmsSignStream { bio, cms } = self;
+        drop(bio);
+        Ok(cms)
+    }
+}
+
+impl Write for CmsSignStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(self.bio.write(buf)?)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A stream of signed content being verified against a CMS `SignedData` structure.
+///
+/// Created by [`CmsContentInfoRef::verify_stream`].
+///
+/// [`CmsContentInfoRef::verify_stream`]: struct.CmsContentInfoRef.html#method.verify_stream
+pub struct CmsVerifyStream<'a> {
+    bio: CmsBio,
+    cms: &'a mut CmsContentInfoRef,
+    flags: CMSOptions,
+}
+
+impl<'a> CmsVerifyStream<'a> {
+    /// Verifies each signer against the content written so far.
+    ///
+    /// The results are in the same order as [`CmsContentInfoRef::signer_infos`].
+    ///
+    /// This corresponds to [`CMS_SignerInfo_verify`] and [`CMS_SignerInfo_verify_content`].
+    ///
+    /// [`CmsContentInfoRef::signer_infos`]: struct.CmsContentInfoRef.html#method.signer_infos
+    /// [`CMS_SignerInfo_verify`]: https://www.openssl.org/docs/manmaster/man3/CMS_SignerInfo_verify.html
+    /// [`CMS_SignerInfo_verify_content`]: https://www.openssl.org/docs/manmaster/man3/CMS_SignerInfo_verify_content.html
+    pub fn finish(mut self) -> Result<Vec<Result<(), ErrorStack>>, ErrorStack> {
+        self.bio.flush()?;
+        let mut results = vec![];
+        for si in self.cms.signer_infos()? {
+            results.push(unsafe { self.verify_signer(si) });
+        }
+        Ok(results)
+    }
+
+    unsafe fn verify_signer(&self, si: &CmsSignerInfoRef) -> Result<(), ErrorStack> {
+        if !self.flags.contains(CMSOptions::NO_ATTR_VERIFY)
+            && ffi::CMS_signed_get_attr_count(si.as_ptr()) >= 0
+        {
+            cvt(ffi::CMS_SignerInfo_verify(si.as_ptr()))?;
+        }
+        if !self.flags.contains(CMSOptions::NO_CONTENT_VERIFY) {
+            cvt(ffi::CMS_SignerInfo_verify_content(si.as_ptr(), self.bio.0))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Write for CmsVerifyStream<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(self.bio.write(buf)?)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A builder for CMS `EnvelopedData` and `AuthEnvelopedData` structures.
+///
+/// Unlike [`CmsContentInfo::encrypt`], the builder supports recipients using key agreement,
+/// pre-shared key-encryption keys and passwords.
+///
+/// [`CmsContentInfo::encrypt`]: struct.CmsContentInfo.html#method.encrypt
+pub struct CmsEnvelopedDataBuilder(CmsContentInfo);
+
+impl CmsEnvelopedDataBuilder {
+    /// Creates a builder for an `EnvelopedData` structure encrypted with `cipher`.
+    ///
+    /// This corresponds to [`CMS_EnvelopedData_create`].
+    ///
+    /// [`CMS_EnvelopedData_create`]: https://www.openssl.org/docs/manmaster/man3/CMS_EnvelopedData_create.html
+    pub fn new(cipher: Cipher) -> Result<CmsEnvelopedDataBuilder, ErrorStack> {
+        unsafe {
+            let cms = cvt_p(ffi::CMS_EnvelopedData_create(cipher.as_ptr()))?;
+            CmsEnvelopedDataBuilder::attach(CmsContentInfo::from_ptr(cms))
+        }
+    }
+
+    /// Creates a builder for an `AuthEnvelopedData` structure encrypted with the AEAD `cipher`,
+    /// such as AES-GCM.
+    ///
+    /// This corresponds to [`CMS_AuthEnvelopedData_create`].
+    ///
+    /// [`CMS_AuthEnvelopedData_create`]: https://www.openssl.org/docs/manmaster/man3/CMS_AuthEnvelopedData_create.html
+    #[cfg(ossl300)]
+    pub fn new_authenticated(cipher: Cipher) -> Result<CmsEnvelopedDataBuilder, ErrorStack> {
+        unsafe {
+            let cms = cvt_p(ffi::CMS_AuthEnvelopedData_create(cipher.as_ptr()))?;
+            CmsEnvelopedDataBuilder::attach(CmsContentInfo::from_ptr(cms))
+        }
+    }
+
+    // the encrypted content is embedded in the structure rather than detached
+    fn attach(cms: CmsContentInfo) -> Result<CmsEnvelopedDataBuilder, ErrorStack> {
+        unsafe {
+            cvt(ffi::CMS_set_detached(cms.as_ptr(), 0))?;
+        }
+        Ok(CmsEnvelopedDataBuilder(cms))
+    }
+
+    /// Adds a recipient identified by its certificate.
+    ///
+    /// RSA recipients use key transport, while EC and DH recipients use ephemeral-static key
+    /// agreement.
+    ///
+    /// This corresponds to [`CMS_add1_recipient_cert`].
+    ///
+    /// [`CMS_add1_recipient_cert`]: https://www.openssl.org/docs/manmaster/man3/CMS_add1_recipient_cert.html
+    pub fn add_recipient_cert(
+        &mut self,
+        cert: &X509Ref,
+        flags: CMSOptions,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt_p(ffi::CMS_add1_recipient_cert(
+                self.0.as_ptr(),
+                cert.as_ptr(),
+                flags.bits(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds a recipient holding the pre-shared key-encryption key `key`, identified by `key_id`.
+    ///
+    /// The key is used with AES key wrap, so it must be 16, 24 or 32 bytes long.
+    ///
+    /// This corresponds to [`CMS_add0_recipient_key`].
+    ///
+    /// [`CMS_add0_recipient_key`]: https://www.openssl.org/docs/manmaster/man3/CMS_add0_recipient_key.html
+    pub fn add_kek_recipient(&mut self, key_id: &[u8], key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            let key_copy = openssl_copy(key)?;
+            let id_copy = match openssl_copy(key_id) {
+                Ok(id) => id,
+                Err(e) => {
+                    openssl_free(key_copy);
+                    return Err(e);
+                }
+            };
+
+            // OpenSSL only takes ownership of the key and identifier once the recipient has been
+            // added, so they are still ours if it fails.
+            cvt_p(ffi::CMS_add0_recipient_key(
+                self.0.as_ptr(),
+                ffi::NID_undef,
+                key_copy,
+                key.len(),
+                id_copy,
+                key_id.len(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            ))
+            .map(|_| ())
+            .map_err(|e| {
+                openssl_free(key_copy);
+                openssl_free(id_copy);
+                e
+            })
+        }
+    }
+
+    /// Adds a recipient deriving the key-encryption key from `password`.
+    ///
+    /// The key is derived with PBKDF2 and wrapped with `kek_cipher` as described in [RFC 3211].
+    /// `kek_cipher` defaults to the content cipher, and must be a CBC mode cipher, so it has to be
+    /// set when the content is encrypted with an AEAD cipher.
+    ///
+    /// This corresponds to [`CMS_add0_recipient_password`].
+    ///
+    /// [RFC 3211]: https://tools.ietf.org/html/rfc3211
+    /// [`CMS_add0_recipient_password`]: https://www.openssl.org/docs/manmaster/man3/CMS_add0_recipient_password.html
+    pub fn add_password_recipient(
+        &mut self,
+        password: &[u8],
+        kek_cipher: Option<Cipher>,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            let pass = openssl_copy(password)?;
+
+            // OpenSSL frees a password it has been handed if adding the recipient fails, so it is
+            // only attached once the recipient exists.
+            let r = cvt_p(ffi::CMS_add0_recipient_password(
+                self.0.as_ptr(),
+                -1,
+                ffi::NID_undef,
+                ffi::NID_undef,
+                ptr::null_mut(),
+                0,
+                kek_cipher.map_or(ptr::null(), |c| c.as_ptr()),
+            ))
+            .and_then(|ri| {
+                cvt(ffi::CMS_RecipientInfo_set0_password(
+                    ri,
+                    pass,
+                    password.len() as ssize_t,
+                ))
+            });
+            if r.is_err() {
+                openssl_free(pass);
+            }
+            r.map(|_| ())
+        }
+    }
+
+    /// Encrypts `data` for the recipients, and returns the finished structure.
+    ///
+    /// This corresponds to [`CMS_final`].
+    ///
+    /// [`CMS_final`]: https://www.openssl.org/docs/manmaster/man3/CMS_final.html
+    pub fn encrypt(self, data: &[u8], flags: CMSOptions) -> Result<CmsContentInfo, ErrorStack> {
+        unsafe {
+            let data = MemBioSlice::new(data)?;
+            cvt(ffi::CMS_final(
+                self.0.as_ptr(),
+                data.as_ptr(),
+                ptr::null_mut(),
+                flags.bits(),
+            ))?;
+            Ok(self.0)
+        }
+    }
+}
+
+/// Copies `data` into memory allocated by OpenSSL, to be handed to functions taking ownership.
+unsafe fn openssl_copy(data: &[u8]) -> Result<*mut u8, ErrorStack> {
+    // OpenSSL may return null for empty allocations
+    let p = cvt_p(ffi::CRYPTO_malloc(
+        cmp::max(data.len(), 1) as _,
+        concat!(file!(), "\0").as_ptr() as *const _,
+        line!() as c_int,
+    ))? as *mut u8;
+    ptr::copy_nonoverlapping(data.as_ptr(), p, data.len());
+    Ok(p)
+}
+
+unsafe fn openssl_free(p: *mut u8) {
+    #[cfg(not(ossl110))]
+    ffi::CRYPTO_free(p as *mut _);
+    #[cfg(ossl110)]
+    ffi::CRYPTO_free(
+        p as *mut _,
+        concat!(file!(), "\0").as_ptr() as *const _,
+        line!() as c_int,
+    );
+}
+
+/// The BIO chain returned by `CMS_dataInit`, which digests the content written to it.
+struct CmsBio(*mut ffi::BIO);
+
+impl Drop for CmsBio {
+    fn drop(&mut self) {
+        unsafe { ffi::BIO_free_all(self.0) }
+    }
+}
+
+impl CmsBio {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorStack> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let len = cmp::min(buf.len(), c_int::max_value() as usize) as c_int;
+        unsafe {
+            let written = ffi::BIO_write(self.0, buf.as_ptr() as *const _, len);
+            if written <= 0 {
+                return Err(ErrorStack::get());
+            }
+            Ok(written as usize)
+        }
+    }
+
+    #[cfg(ossl110)]
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<(), ErrorStack> {
+        while !buf.is_empty() {
+            let written = self.write(buf)?;
+            buf = &buf[written..];
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ErrorStack> {
+        unsafe {
+            if ffi::BIO_ctrl(self.0, ffi::BIO_CTRL_FLUSH, 0, ptr::null_mut()) <= 0 {
+                return Err(ErrorStack::get());
+            }
+        }
+        Ok(())
+    }
+}
+
+const OID_DATA: &[u8] = &[
+    0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01,
+];
+const OID_SIGNED_DATA: &[u8] = &[
+    0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02,
+];
+
+// OpenSSL does not expose the encoding of a lone SignerInfo, which is what a counter-signature
+// attribute holds, so it is moved in and out of an enclosing ContentInfo by hand.
+
+/// Extracts the first `SignerInfo` of a DER-encoded `SignedData` `ContentInfo`.
+#[cfg(ossl110)]
+fn der_signer_info(content_info: &[u8]) -> Result<&[u8], ErrorStack> {
+    let content_info = der_expect(content_info, 0x30)?;
+    let content_type = der_next(content_info.contents)?;
+    let content = der_expect(content_type.rest, 0xa0)?;
+    let signed_data = der_expect(content.contents, 0x30)?;
+
+    // signerInfos is the last field of SignedData
+    let mut field = der_next(signed_data.contents)?;
+    while !field.rest.is_empty() {
+        field = der_next(field.rest)?;
+    }
+    let signer_infos = der_expect(field.tlv, 0x31)?;
+    let signer_info = der_expect(signer_infos.contents, 0x30)?;
+    Ok(signer_info.tlv)
+}
+
+/// Wraps a DER-encoded `SignerInfo` into a `SignedData` `ContentInfo` with detached content.
+fn der_signed_data(signer_info: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let fields = der_expect(signer_info, 0x30)?;
+    let version = der_expect(fields.contents, 0x02)?;
+    let sid = der_next(version.rest)?;
+    let digest_algorithm = der_expect(sid.rest, 0x30)?;
+
+    // SignedData is version 3 if it has a version 3 SignerInfo, and version 1 otherwise
+    let version = if version.contents == [3] { 3 } else { 1 };
+    let mut signed_data = der_encode(0x02, &[version]);
+    signed_data.extend(der_encode(0x31, digest_algorithm.tlv));
+    signed_data.extend(der_encode(0x30, OID_DATA));
+    signed_data.extend(der_encode(0x31, signer_info));
+
+    let mut content_info = OID_SIGNED_DATA.to_vec();
+    content_info.extend(der_encode(0xa0, &der_encode(0x30, &signed_data)));
+    Ok(der_encode(0x30, &content_info))
+}
+
+/// A DER element split off the front of a buffer.
+struct DerElement<'a> {
+    tlv: &'a [u8],
+    contents: &'a [u8],
+    rest: &'a [u8],
+}
+
+fn der_next(data: &[u8]) -> Result<DerElement<'_>, ErrorStack> {
+    let truncated = || {
+        ErrorStack::internal(
+            ffi::ERR_LIB_ASN1,
+            ffi::ASN1_R_HEADER_TOO_LONG,
+            "truncated DER element",
+        )
+    };
+
+    if data.len() < 2 {
+        return Err(truncated());
+    }
+    let (header_len, len) = if data[1] < 0x80 {
+        (2, data[1] as usize)
+    } else {
+        let n = (data[1] & 0x7f) as usize;
+        if n == 0 || n > mem::size_of::<usize>() || data.len() < 2 + n {
+            return Err(truncated());
+        }
+        let len = data[2..2 + n]
+            .iter()
+            .fold(0, |len, &b| (len << 8) | b as usize);
+        (2 + n, len)
+    };
+    if data.len() - header_len < len {
+        return Err(truncated());
+    }
+
+    let (tlv, rest) = data.split_at(header_len + len);
+    Ok(DerElement {
+        tlv,
+        contents: &tlv[header_len..],
+        rest,
+    })
+}
+
+fn der_expect(data: &[u8], tag: u8) -> Result<DerElement<'_>, ErrorStack> {
+    let element = der_next(data)?;
+    if element.tlv[0] != tag {
+        return Err(ErrorStack::internal(
+            ffi::ERR_LIB_ASN1,
+            ffi::ASN1_R_WRONG_TAG,
+            "unexpected DER tag",
+        ));
+    }
+    Ok(element)
+}
+
+fn der_encode(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    if contents.len() < 0x80 {
+        out.push(contents.len() as u8);
+    } else {
+        let len = contents.len().to_be_bytes();
+        let skip = len.iter().take_while(|&&b| b == 0).count();
+        out.push(0x80 | (len.len() - skip) as u8);
+        out.extend_from_slice(&len[skip..]);
+    }
+    out.extend_from_slice(contents);
+    out
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::asn1::{Asn1Object, Asn1Time};
+    use crate::bn::BigNum;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
     use crate::pkcs12::Pkcs12;
+    use crate::pkey::{PKey, Private};
     use crate::stack::Stack;
-    use crate::x509::X509;
+    use crate::x509::store::{X509Store, X509StoreBuilder};
+    use crate::x509::{X509Name, X509};
+
+    fn self_signed(pkey: &PKey<Private>, cn: &str) -> X509 {
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, cn).unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder
+            .set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap())
+            .unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        builder.set_pubkey(pkey).unwrap();
+        builder.sign(pkey, MessageDigest::sha256()).unwrap();
+        builder.build()
+    }
+
+    fn signers() -> (X509, PKey<Private>, X509, PKey<Private>) {
+        let rsa_key = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+        let rsa_cert = self_signed(&rsa_key, "rsa signer");
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ec_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let ec_cert = self_signed(&ec_key, "ec signer");
+
+        (rsa_cert, rsa_key, ec_cert, ec_key)
+    }
+
+    fn trust(certs: &[&X509]) -> X509Store {
+        let mut store = X509StoreBuilder::new().unwrap();
+        for cert in certs {
+            store.add_cert(cert).unwrap();
+        }
+        store.build()
+    }
 
     #[test]
     #[cfg_attr(ossl300, ignore)] // 3.0.0 can't load RC2-40-CBC
@@ -431,4 +1504,238 @@ mod test {
             .expect("Verify CMS");
         assert!(valid);
     }
+
+    #[test]
+    fn cms_sign_stream_detached() {
+        let (rsa_cert, rsa_key, ec_cert, ec_key) = signers();
+        let signing_time = Asn1Time::from_unix(1_600_000_000).unwrap();
+        let oid = Asn1Object::from_str("1.3.6.1.4.1.55555.1").unwrap();
+
+        let mut builder = CmsSignedDataBuilder::new(CMSOptions::DETACHED).unwrap();
+        let signer = builder
+            .add_signer(
+                &rsa_cert,
+                &rsa_key,
+                MessageDigest::sha256(),
+                CMSOptions::empty(),
+            )
+            .unwrap();
+        signer.set_signing_time(&signing_time).unwrap();
+        signer
+            .add_signed_attribute(&oid, Asn1Type::UTF8STRING, b"build 42")
+            .unwrap();
+        signer
+            .add_unsigned_attribute(&oid, Asn1Type::OCTET_STRING, b"unsigned")
+            .unwrap();
+        builder
+            .add_signer(
+                &ec_cert,
+                &ec_key,
+                MessageDigest::sha384(),
+                CMSOptions::empty(),
+            )
+            .unwrap();
+
+        let chunk = [0x5a; 4096];
+        let mut stream = builder.stream().unwrap();
+        for _ in 0..256 {
+            stream.write_all(&chunk).unwrap();
+        }
+        let cms = stream.finish().unwrap();
+
+        let mut cms = CmsContentInfo::from_der(&cms.to_der().unwrap()).unwrap();
+        {
+            let signers = cms.signer_infos().unwrap();
+            assert_eq!(signers.len(), 2);
+            // signerInfos is a SET OF, which is reordered when encoded
+            let rsa_signer = signers.iter().find(|s| s.matches_cert(&rsa_cert)).unwrap();
+            let ec_signer = signers.iter().find(|s| s.matches_cert(&ec_cert)).unwrap();
+            assert!(!rsa_signer.matches_cert(&ec_cert));
+            assert_eq!(rsa_signer.digest_algorithm().object().nid(), Nid::SHA256);
+            assert_eq!(ec_signer.digest_algorithm().object().nid(), Nid::SHA384);
+            assert_eq!(
+                rsa_signer.signing_time().unwrap().to_string(),
+                signing_time.to_string()
+            );
+            assert!(rsa_signer.signer_cert().is_none());
+        }
+
+        let store = trust(&[&rsa_cert, &ec_cert]);
+        let mut stream = cms
+            .verify_stream(None, &store, CMSOptions::empty())
+            .unwrap();
+        for _ in 0..256 {
+            stream.write_all(&chunk).unwrap();
+        }
+        let results = stream.finish().unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.is_ok()));
+
+        for signer in cms.signer_infos().unwrap() {
+            let cert = signer.signer_cert().unwrap();
+            assert!(signer.matches_cert(cert));
+        }
+
+        let mut stream = cms
+            .verify_stream(None, &store, CMSOptions::empty())
+            .unwrap();
+        stream.write_all(b"tampered").unwrap();
+        let results = stream.finish().unwrap();
+        assert!(results.iter().all(|r| r.is_err()));
+    }
+
+    #[test]
+    fn cms_append_signer() {
+        let (rsa_cert, rsa_key, ec_cert, ec_key) = signers();
+        let input = b"My Message";
+
+        let cms = CmsContentInfo::sign(
+            Some(&rsa_cert),
+            Some(&rsa_key),
+            None,
+            Some(input),
+            CMSOptions::BINARY,
+        )
+        .unwrap();
+        let mut cms = CmsContentInfo::from_der(&cms.to_der().unwrap()).unwrap();
+        cms.add_signer(
+            &ec_cert,
+            &ec_key,
+            MessageDigest::sha256(),
+            CMSOptions::REUSE_DIGEST,
+        )
+        .unwrap();
+        let mut cms = CmsContentInfo::from_der(&cms.to_der().unwrap()).unwrap();
+        assert_eq!(cms.signer_infos().unwrap().len(), 2);
+
+        let store = trust(&[&rsa_cert, &ec_cert]);
+        assert!(cms.verify(None, &store, CMSOptions::BINARY).unwrap());
+
+        let content = cms.get_content().unwrap().to_vec();
+        let mut stream = cms
+            .verify_stream(None, &store, CMSOptions::empty())
+            .unwrap();
+        stream.write_all(&content).unwrap();
+        let results = stream.finish().unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.is_ok()));
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn cms_counter_signature() {
+        let (rsa_cert, rsa_key, ec_cert, ec_key) = signers();
+
+        let cms = CmsContentInfo::sign(
+            Some(&rsa_cert),
+            Some(&rsa_key),
+            None,
+            Some(b"My Message"),
+            CMSOptions::BINARY,
+        )
+        .unwrap();
+        let mut cms = CmsContentInfo::from_der(&cms.to_der().unwrap()).unwrap();
+        cms.signer_infos_mut()
+            .unwrap()
+            .get_mut(0)
+            .unwrap()
+            .add_counter_signature(
+                &ec_cert,
+                &ec_key,
+                MessageDigest::sha256(),
+                CMSOptions::empty(),
+            )
+            .unwrap();
+        cms.add_cert(&ec_cert).unwrap();
+        let cms = CmsContentInfo::from_der(&cms.to_der().unwrap()).unwrap();
+
+        let store = trust(&[&rsa_cert, &ec_cert]);
+        assert!(cms.verify(None, &store, CMSOptions::BINARY).unwrap());
+
+        let signer = cms.signer_infos().unwrap().get(0).unwrap();
+        let mut counter_signatures = signer.counter_signatures().unwrap();
+        assert_eq!(counter_signatures.len(), 1);
+        let counter_signature = &mut counter_signatures[0];
+
+        let certs = cms.get_certs().unwrap();
+        let mut stream = counter_signature
+            .verify_stream(Some(&certs), &store, CMSOptions::empty())
+            .unwrap();
+        stream.write_all(signer.signature()).unwrap();
+        let results = stream.finish().unwrap();
+        assert_eq!(results.len(), 1);
+        results[0].as_ref().unwrap();
+        assert!(counter_signature.signer_infos().unwrap()[0].matches_cert(&ec_cert));
+
+        let mut stream = counter_signature
+            .verify_stream(Some(&certs), &store, CMSOptions::empty())
+            .unwrap();
+        stream.write_all(b"not the signature").unwrap();
+        assert!(stream.finish().unwrap()[0].is_err());
+    }
+
+    fn envelope_recipients(
+        builder: &mut CmsEnvelopedDataBuilder,
+        ec_cert: &X509,
+        kek_cipher: Option<Cipher>,
+    ) {
+        builder
+            .add_recipient_cert(ec_cert, CMSOptions::empty())
+            .unwrap();
+        builder.add_kek_recipient(b"kek-1", &[0x42; 32]).unwrap();
+        builder
+            .add_password_recipient(b"hunter2", kek_cipher)
+            .unwrap();
+    }
+
+    fn check_recipients(cms: &CmsContentInfoRef, ec_key: &PKey<Private>, ec_cert: &X509) {
+        let input = b"My Message";
+        assert_eq!(cms.decrypt(ec_key, ec_cert).unwrap(), input);
+        assert_eq!(
+            cms.decrypt_with_key(Some(b"kek-1"), &[0x42; 32]).unwrap(),
+            input
+        );
+        assert_eq!(cms.decrypt_with_key(None, &[0x42; 32]).unwrap(), input);
+        assert_eq!(cms.decrypt_with_password(b"hunter2").unwrap(), input);
+
+        assert!(cms.decrypt_with_key(Some(b"kek-2"), &[0x42; 32]).is_err());
+        assert!(cms.decrypt_with_key(Some(b"kek-1"), &[0x24; 32]).is_err());
+        assert!(cms.decrypt_with_password(b"hunter3").is_err());
+    }
+
+    #[test]
+    fn cms_envelope_recipients() {
+        let (_, _, ec_cert, ec_key) = signers();
+
+        let mut builder = CmsEnvelopedDataBuilder::new(Cipher::aes_256_cbc()).unwrap();
+        // a key of the wrong length is rejected without adding a recipient
+        assert!(builder.add_kek_recipient(b"kek-2", &[0x42; 7]).is_err());
+        envelope_recipients(&mut builder, &ec_cert, None);
+        let cms = builder.encrypt(b"My Message", CMSOptions::BINARY).unwrap();
+
+        let cms = CmsContentInfo::from_der(&cms.to_der().unwrap()).unwrap();
+        check_recipients(&cms, &ec_key, &ec_cert);
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn cms_auth_envelope_recipients() {
+        let (_, _, ec_cert, ec_key) = signers();
+
+        let mut builder =
+            CmsEnvelopedDataBuilder::new_authenticated(Cipher::aes_256_gcm()).unwrap();
+        // password recipients can't wrap keys with AEAD ciphers
+        envelope_recipients(&mut builder, &ec_cert, Some(Cipher::aes_128_cbc()));
+        let cms = builder.encrypt(b"My Message", CMSOptions::BINARY).unwrap();
+
+        let der = cms.to_der().unwrap();
+        let cms = CmsContentInfo::from_der(&der).unwrap();
+        check_recipients(&cms, &ec_key, &ec_cert);
+
+        // the last byte is part of the authentication tag
+        let mut tampered = der;
+        *tampered.last_mut().unwrap() ^= 1;
+        let cms = CmsContentInfo::from_der(&tampered).unwrap();
+        assert!(cms.decrypt_with_password(b"hunter2").is_err());
+    }
 }
@@ -1,17 +1,11 @@
 //! Shared secret derivation.
-use foreign_types::ForeignTypeRef;
 use std::marker::PhantomData;
-use std::ptr;
 
 use crate::error::ErrorStack;
-use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
-use crate::{cvt, cvt_p};
+use crate::pkey::{HasPrivate, HasPublic, PKeyCtx, PKeyRef};
 
 /// A type used to derive a shared secret between two keys.
-pub struct Deriver<'a>(*mut ffi::EVP_PKEY_CTX, PhantomData<&'a ()>);
-
-unsafe impl<'a> Sync for Deriver<'a> {}
-unsafe impl<'a> Send for Deriver<'a> {}
+pub struct Deriver<'a>(PKeyCtx, PhantomData<&'a ()>);
 
 #[allow(clippy::len_without_is_empty)]
 impl<'a> Deriver<'a> {
@@ -24,11 +18,9 @@ impl<'a> Deriver<'a> {
     where
         T: HasPrivate,
     {
-        unsafe {
-            cvt_p(ffi::EVP_PKEY_CTX_new(key.as_ptr(), ptr::null_mut()))
-                .map(|p| Deriver(p, PhantomData))
-                .and_then(|ctx| cvt(ffi::EVP_PKEY_derive_init(ctx.0)).map(|_| ctx))
-        }
+        let ctx = PKeyCtx::from_pkey(key)?;
+        ctx.derive_init()?;
+        Ok(Deriver(ctx, PhantomData))
     }
 
     /// Sets the peer key used for secret derivation.
@@ -40,7 +32,7 @@ impl<'a> Deriver<'a> {
     where
         T: HasPublic,
     {
-        unsafe { cvt(ffi::EVP_PKEY_derive_set_peer(self.0, key.as_ptr())).map(|_| ()) }
+        self.0.derive_set_peer(key)
     }
 
     /// Returns the size of the shared secret.
@@ -52,10 +44,7 @@ impl<'a> Deriver<'a> {
     /// [`Deriver::derive`]: #method.derive
     /// [`EVP_PKEY_derive`]: https://www.openssl.org/docs/man1.0.2/crypto/EVP_PKEY_derive_init.html
     pub fn len(&mut self) -> Result<usize, ErrorStack> {
-        unsafe {
-            let mut len = 0;
-            cvt(ffi::EVP_PKEY_derive(self.0, ptr::null_mut(), &mut len)).map(|_| len)
-        }
+        self.0.derive_len()
     }
 
     /// Derives a shared secret between the two keys, writing it into the buffer.
@@ -66,15 +55,7 @@ impl<'a> Deriver<'a> {
     ///
     /// [`EVP_PKEY_derive`]: https://www.openssl.org/docs/man1.0.2/crypto/EVP_PKEY_derive_init.html
     pub fn derive(&mut self, buf: &mut [u8]) -> Result<usize, ErrorStack> {
-        let mut len = buf.len();
-        unsafe {
-            cvt(ffi::EVP_PKEY_derive(
-                self.0,
-                buf.as_mut_ptr() as *mut _,
-                &mut len,
-            ))
-            .map(|_| len)
-        }
+        self.0.derive(buf)
     }
 
     /// A convenience function which derives a shared secret and returns it in a new buffer.
@@ -84,19 +65,7 @@ impl<'a> Deriver<'a> {
     /// [`Deriver::len`]: #method.len
     /// [`Deriver::derive`]: #method.derive
     pub fn derive_to_vec(&mut self) -> Result<Vec<u8>, ErrorStack> {
-        let len = self.len()?;
-        let mut buf = vec![0; len];
-        let len = self.derive(&mut buf)?;
-        buf.truncate(len);
-        Ok(buf)
-    }
-}
-
-impl<'a> Drop for Deriver<'a> {
-    fn drop(&mut self) {
-        unsafe {
-            ffi::EVP_PKEY_CTX_free(self.0);
-        }
+        self.0.derive_to_vec()
     }
 }
 
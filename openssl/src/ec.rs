@@ -84,6 +84,11 @@ impl Asn1Flag {
     ///
     /// [`EC_GROUP`]: https://www.openssl.org/docs/manmaster/man3/EC_GROUP_order_bits.html
     pub const NAMED_CURVE: Asn1Flag = Asn1Flag(ffi::OPENSSL_EC_NAMED_CURVE);
+
+    /// Returns the integer representation of `Asn1Flag`.
+    pub(crate) fn as_raw(&self) -> c_int {
+        self.0
+    }
 }
 
 foreign_type_and_impl_send_sync! {
@@ -0,0 +1,430 @@
+//! JSON Web Keys, as defined in [RFC 7517] and [RFC 7518].
+//!
+//! Only the members describing the key material are interpreted. Other members such as `use`
+//! or `kid` are ignored when decoding and never emitted when encoding.
+//!
+//! [RFC 7517]: https://tools.ietf.org/html/rfc7517
+//! [RFC 7518]: https://tools.ietf.org/html/rfc7518#section-6
+use std::char;
+use std::str;
+
+use super::{base64url_decode, base64url_encode, decode_error, unsupported};
+use crate::bn::{BigNum, BigNumContext, BigNumRef};
+use crate::ec::{EcGroup, EcKey};
+use crate::error::ErrorStack;
+use crate::nid::Nid;
+use crate::pkey::{HasPrivate, HasPublic, Id, PKey, PKeyRef, Private, Public};
+use crate::rsa::{Rsa, RsaPrivateKeyBuilder};
+
+const CURVES: &[(&str, Nid)] = &[
+    ("P-256", Nid::X9_62_PRIME256V1),
+    ("P-384", Nid::SECP384R1),
+    ("P-521", Nid::SECP521R1),
+    ("secp256k1", Nid::SECP256K1),
+];
+
+#[cfg(ossl111)]
+const OKP_CURVES: &[(&str, Id)] = &[
+    ("Ed25519", Id::ED25519),
+    ("Ed448", Id::ED448),
+    ("X25519", Id::X25519),
+    ("X448", Id::X448),
+];
+
+pub(super) fn encode_public<T>(pkey: &PKeyRef<T>) -> Result<Vec<u8>, ErrorStack>
+where
+    T: HasPublic,
+{
+    let mut members = vec![];
+    public_members(pkey, &mut members)?;
+    Ok(to_json(&members))
+}
+
+pub(super) fn encode_private<T>(pkey: &PKeyRef<T>) -> Result<Vec<u8>, ErrorStack>
+where
+    T: HasPrivate,
+{
+    let mut members = vec![];
+    public_members(pkey, &mut members)?;
+    match pkey.id() {
+        Id::RSA => {
+            let rsa = pkey.rsa()?;
+            members.push(("d", base64url_encode(&rsa.d().to_vec())));
+            let crt = [
+                ("p", rsa.p()),
+                ("q", rsa.q()),
+                ("dp", rsa.dmp1()),
+                ("dq", rsa.dmq1()),
+                ("qi", rsa.iqmp()),
+            ];
+            for &(name, value) in &crt {
+                if let Some(value) = value {
+                    members.push((name, base64url_encode(&value.to_vec())));
+                }
+            }
+        }
+        Id::EC => {
+            let ec = pkey.ec_key()?;
+            let len = field_len(ec.group().degree());
+            members.push(("d", base64url_encode(&padded(ec.private_key(), len)?)));
+        }
+        #[cfg(ossl111)]
+        _ => members.push(("d", base64url_encode(&pkey.raw_private_key()?))),
+        #[cfg(not(ossl111))]
+        _ => unreachable!(),
+    }
+    Ok(to_json(&members))
+}
+
+pub(super) fn decode_public(data: &[u8]) -> Result<PKey<Public>, ErrorStack> {
+    let jwk = Jwk::parse(data)?;
+    match jwk.get("kty")? {
+        "RSA" => {
+            let rsa = Rsa::from_public_components(jwk.bn("n")?, jwk.bn("e")?)?;
+            PKey::from_rsa(rsa)
+        }
+        "EC" => PKey::from_ec_key(jwk.ec_public_key()?),
+        #[cfg(ossl111)]
+        "OKP" => PKey::public_key_from_raw_bytes(&jwk.bytes("x")?, jwk.okp_id()?),
+        _ => Err(unsupported("unsupported JWK key type")),
+    }
+}
+
+pub(super) fn decode_private(data: &[u8]) -> Result<PKey<Private>, ErrorStack> {
+    let jwk = Jwk::parse(data)?;
+    match jwk.get("kty")? {
+        "RSA" => {
+            let n = jwk.bn("n")?;
+            let e = jwk.bn("e")?;
+            let d = jwk.bn("d")?;
+            let rsa = if jwk.has("p") {
+                Rsa::from_private_components(
+                    n,
+                    e,
+                    d,
+                    jwk.bn("p")?,
+                    jwk.bn("q")?,
+                    jwk.bn("dp")?,
+                    jwk.bn("dq")?,
+                    jwk.bn("qi")?,
+                )?
+            } else {
+                RsaPrivateKeyBuilder::new(n, e, d)?.build()
+            };
+            PKey::from_rsa(rsa)
+        }
+        "EC" => {
+            let public = jwk.ec_public_key()?;
+            let d = jwk.bn("d")?;
+            let ec = EcKey::from_private_components(public.group(), &d, public.public_key())?;
+            ec.check_key()?;
+            PKey::from_ec_key(ec)
+        }
+        #[cfg(ossl111)]
+        "OKP" => {
+            let pkey = PKey::private_key_from_raw_bytes(&jwk.bytes("d")?, jwk.okp_id()?)?;
+            if pkey.raw_public_key()? != jwk.bytes("x")? {
+                return Err(decode_error("JWK public and private keys do not match"));
+            }
+            Ok(pkey)
+        }
+        _ => Err(unsupported("unsupported JWK key type")),
+    }
+}
+
+fn public_members<T>(
+    pkey: &PKeyRef<T>,
+    members: &mut Vec<(&'static str, String)>,
+) -> Result<(), ErrorStack>
+where
+    T: HasPublic,
+{
+    match pkey.id() {
+        Id::RSA => {
+            let rsa = pkey.rsa()?;
+            members.push(("kty", "RSA".to_string()));
+            members.push(("n", base64url_encode(&rsa.n().to_vec())));
+            members.push(("e", base64url_encode(&rsa.e().to_vec())));
+        }
+        Id::EC => {
+            let ec = pkey.ec_key()?;
+            let group = ec.group();
+            let nid = group.curve_name();
+            let crv = CURVES
+                .iter()
+                .find(|&&(_, n)| Some(n) == nid)
+                .map(|&(crv, _)| crv)
+                .ok_or_else(|| unsupported("unsupported JWK curve"))?;
+
+            let mut ctx = BigNumContext::new()?;
+            let mut x = BigNum::new()?;
+            let mut y = BigNum::new()?;
+            ec.public_key()
+                .affine_coordinates_gfp(group, &mut x, &mut y, &mut ctx)?;
+            let len = field_len(group.degree());
+
+            members.push(("kty", "EC".to_string()));
+            members.push(("crv", crv.to_string()));
+            members.push(("x", base64url_encode(&padded(&x, len)?)));
+            members.push(("y", base64url_encode(&padded(&y, len)?)));
+        }
+        #[cfg(ossl111)]
+        id => {
+            let crv = OKP_CURVES
+                .iter()
+                .find(|&&(_, i)| i == id)
+                .map(|&(crv, _)| crv)
+                .ok_or_else(|| unsupported("unsupported JWK key type"))?;
+            members.push(("kty", "OKP".to_string()));
+            members.push(("crv", crv.to_string()));
+            members.push(("x", base64url_encode(&pkey.raw_public_key()?)));
+        }
+        #[cfg(not(ossl111))]
+        _ => return Err(unsupported("unsupported JWK key type")),
+    }
+    Ok(())
+}
+
+fn field_len(degree: u32) -> usize {
+    (degree as usize + 7) / 8
+}
+
+/// Returns the big-endian representation of `n`, left-padded with zeros to `len` bytes.
+fn padded(n: &BigNumRef, len: usize) -> Result<Vec<u8>, ErrorStack> {
+    let bytes = n.to_vec();
+    if bytes.len() > len {
+        return Err(decode_error("EC coordinate is larger than the field"));
+    }
+    let mut out = vec![0; len - bytes.len()];
+    out.extend_from_slice(&bytes);
+    Ok(out)
+}
+
+fn to_json(members: &[(&str, String)]) -> Vec<u8> {
+    // Every value is either a fixed identifier or base64url, so nothing needs escaping.
+    let members = members
+        .iter()
+        .map(|(name, value)| format!("\"{}\":\"{}\"", name, value))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", members.join(",")).into_bytes()
+}
+
+/// The string-valued members of a JWK.
+struct Jwk(Vec<(String, String)>);
+
+impl Jwk {
+    fn parse(data: &[u8]) -> Result<Jwk, ErrorStack> {
+        let data = str::from_utf8(data).map_err(|_| decode_error("invalid JWK"))?;
+        let mut parser = Parser(data.as_bytes());
+        let members = parser.object(0)?;
+        parser.whitespace();
+        if !parser.0.is_empty() {
+            return Err(decode_error("trailing data after JWK"));
+        }
+        Ok(Jwk(members))
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.0.iter().any(|(n, _)| n == name)
+    }
+
+    fn get(&self, name: &str) -> Result<&str, ErrorStack> {
+        self.0
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| &**v)
+            .ok_or_else(|| decode_error("missing JWK member"))
+    }
+
+    fn bytes(&self, name: &str) -> Result<Vec<u8>, ErrorStack> {
+        base64url_decode(self.get(name)?)
+    }
+
+    fn bn(&self, name: &str) -> Result<BigNum, ErrorStack> {
+        BigNum::from_slice(&self.bytes(name)?)
+    }
+
+    fn ec_public_key(&self) -> Result<EcKey<Public>, ErrorStack> {
+        let crv = self.get("crv")?;
+        let nid = CURVES
+            .iter()
+            .find(|&&(c, _)| c == crv)
+            .map(|&(_, nid)| nid)
+            .ok_or_else(|| unsupported("unsupported JWK curve"))?;
+        let group = EcGroup::from_curve_name(nid)?;
+        let x = self.bn("x")?;
+        let y = self.bn("y")?;
+        EcKey::from_public_key_affine_coordinates(&group, &x, &y)
+    }
+
+    #[cfg(ossl111)]
+    fn okp_id(&self) -> Result<Id, ErrorStack> {
+        let crv = self.get("crv")?;
+        OKP_CURVES
+            .iter()
+            .find(|&&(c, _)| c == crv)
+            .map(|&(_, id)| id)
+            .ok_or_else(|| unsupported("unsupported JWK curve"))
+    }
+}
+
+/// The deepest nesting of objects and arrays accepted in a JWK.
+const MAX_DEPTH: usize = 32;
+
+/// A minimal JSON parser which keeps the string members of the top-level object and skips all
+/// other values.
+struct Parser<'a>(&'a [u8]);
+
+impl<'a> Parser<'a> {
+    fn whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.0.first() {
+            self.0 = &self.0[1..];
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.whitespace();
+        self.0.first().cloned()
+    }
+
+    fn next(&mut self) -> Result<u8, ErrorStack> {
+        let b = self.peek().ok_or_else(|| decode_error("truncated JWK"))?;
+        self.0 = &self.0[1..];
+        Ok(b)
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), ErrorStack> {
+        if self.next()? == b {
+            Ok(())
+        } else {
+            Err(decode_error("invalid JWK"))
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Vec<(String, String)>, ErrorStack> {
+        if depth > MAX_DEPTH {
+            return Err(decode_error("JWK nested too deeply"));
+        }
+        let mut members = vec![];
+        self.expect(b'{')?;
+        if self.peek() == Some(b'}') {
+            self.0 = &self.0[1..];
+            return Ok(members);
+        }
+        loop {
+            let name = self.string()?;
+            self.expect(b':')?;
+            if self.peek() == Some(b'"') {
+                let value = self.string()?;
+                members.push((name, value));
+            } else {
+                self.skip_value(depth + 1)?;
+            }
+            match self.next()? {
+                b',' => {}
+                b'}' => return Ok(members),
+                _ => return Err(decode_error("invalid JWK")),
+            }
+        }
+    }
+
+    fn skip_value(&mut self, depth: usize) -> Result<(), ErrorStack> {
+        match self.peek() {
+            Some(b'"') => self.string().map(|_| ()),
+            Some(b'{') => {
+                self.object(depth)?;
+                Ok(())
+            }
+            Some(b'[') => {
+                if depth > MAX_DEPTH {
+                    return Err(decode_error("JWK nested too deeply"));
+                }
+                self.0 = &self.0[1..];
+                if self.peek() == Some(b']') {
+                    self.0 = &self.0[1..];
+                    return Ok(());
+                }
+                loop {
+                    self.skip_value(depth + 1)?;
+                    match self.next()? {
+                        b',' => {}
+                        b']' => return Ok(()),
+                        _ => return Err(decode_error("invalid JWK")),
+                    }
+                }
+            }
+            Some(_) => {
+                // numbers, booleans and null
+                let len = self
+                    .0
+                    .iter()
+                    .position(|&b| !(b.is_ascii_alphanumeric() || b"+-.".contains(&b)))
+                    .unwrap_or_else(|| self.0.len());
+                if len == 0 {
+                    return Err(decode_error("invalid JWK"));
+                }
+                self.0 = &self.0[len..];
+                Ok(())
+            }
+            None => Err(decode_error("truncated JWK")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ErrorStack> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let end = self
+                .0
+                .iter()
+                .position(|&b| b == b'"' || b == b'\\')
+                .ok_or_else(|| decode_error("truncated JWK"))?;
+            // the input was validated as UTF-8 and we only split at ASCII characters
+            out.push_str(str::from_utf8(&self.0[..end]).unwrap());
+            let b = self.0[end];
+            self.0 = &self.0[end + 1..];
+            if b == b'"' {
+                return Ok(out);
+            }
+
+            let escape = *self
+                .0
+                .first()
+                .ok_or_else(|| decode_error("truncated JWK"))?;
+            self.0 = &self.0[1..];
+            let c = match escape {
+                b'"' => '"',
+                b'\\' => '\\',
+                b'/' => '/',
+                b'b' => '\u{8}',
+                b'f' => '\u{c}',
+                b'n' => '\n',
+                b'r' => '\r',
+                b't' => '\t',
+                b'u' => {
+                    let mut c = self.hex4()?;
+                    if (0xd800..0xdc00).contains(&c) && self.0.starts_with(b"\\u") {
+                        self.0 = &self.0[2..];
+                        let low = self.hex4()?;
+                        c = 0x10000 + ((c - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                    }
+                    char::from_u32(c).ok_or_else(|| decode_error("invalid JWK"))?
+                }
+                _ => return Err(decode_error("invalid JWK")),
+            };
+            out.push(c);
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, ErrorStack> {
+        if self.0.len() < 4 {
+            return Err(decode_error("truncated JWK"));
+        }
+        let (digits, rest) = self.0.split_at(4);
+        self.0 = rest;
+        str::from_utf8(digits)
+            .ok()
+            .and_then(|s| u32::from_str_radix(s, 16).ok())
+            .ok_or_else(|| decode_error("invalid JWK"))
+    }
+}
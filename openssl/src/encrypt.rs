@@ -39,34 +39,19 @@
 //! decrypted.truncate(decrypted_len);
 //! assert_eq!(&*decrypted, data);
 //! ```
-#[cfg(any(ossl102, libressl310))]
-use libc::{c_int, c_void};
-use std::{marker::PhantomData, ptr};
+use std::marker::PhantomData;
 
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
-use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
+use crate::pkey::{HasPrivate, HasPublic, PKeyCtx, PKeyRef};
 use crate::rsa::Padding;
-use crate::{cvt, cvt_p};
-use foreign_types::ForeignTypeRef;
 
 /// A type which encrypts data.
 pub struct Encrypter<'a> {
-    pctx: *mut ffi::EVP_PKEY_CTX,
+    ctx: PKeyCtx,
     _p: PhantomData<&'a ()>,
 }
 
-unsafe impl<'a> Sync for Encrypter<'a> {}
-unsafe impl<'a> Send for Encrypter<'a> {}
-
-impl<'a> Drop for Encrypter<'a> {
-    fn drop(&mut self) {
-        unsafe {
-            ffi::EVP_PKEY_CTX_free(self.pctx);
-        }
-    }
-}
-
 impl<'a> Encrypter<'a> {
     /// Creates a new `Encrypter`.
     ///
@@ -77,21 +62,13 @@ impl<'a> Encrypter<'a> {
     where
         T: HasPublic,
     {
-        unsafe {
-            ffi::init();
-
-            let pctx = cvt_p(ffi::EVP_PKEY_CTX_new(pkey.as_ptr(), ptr::null_mut()))?;
-            let r = ffi::EVP_PKEY_encrypt_init(pctx);
-            if r != 1 {
-                ffi::EVP_PKEY_CTX_free(pctx);
-                return Err(ErrorStack::get());
-            }
-
-            Ok(Encrypter {
-                pctx,
-                _p: PhantomData,
-            })
-        }
+        let ctx = PKeyCtx::from_pkey(pkey)?;
+        ctx.encrypt_init()?;
+
+        Ok(Encrypter {
+            ctx,
+            _p: PhantomData,
+        })
     }
 
     /// Returns the RSA padding mode in use.
@@ -100,11 +77,7 @@ impl<'a> Encrypter<'a> {
     ///
     /// This corresponds to `EVP_PKEY_CTX_get_rsa_padding`.
     pub fn rsa_padding(&self) -> Result<Padding, ErrorStack> {
-        unsafe {
-            let mut pad = 0;
-            cvt(ffi::EVP_PKEY_CTX_get_rsa_padding(self.pctx, &mut pad))
-                .map(|_| Padding::from_raw(pad))
-        }
+        self.ctx.rsa_padding()
     }
 
     /// Sets the RSA padding mode.
@@ -115,13 +88,7 @@ impl<'a> Encrypter<'a> {
     ///
     /// [`EVP_PKEY_CTX_set_rsa_padding`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_rsa_padding.html
     pub fn set_rsa_padding(&mut self, padding: Padding) -> Result<(), ErrorStack> {
-        unsafe {
-            cvt(ffi::EVP_PKEY_CTX_set_rsa_padding(
-                self.pctx,
-                padding.as_raw(),
-            ))
-            .map(|_| ())
-        }
+        self.ctx.set_rsa_padding(padding)
     }
 
     /// Sets the RSA MGF1 algorithm.
@@ -132,13 +99,7 @@ impl<'a> Encrypter<'a> {
     ///
     /// [`EVP_PKEY_CTX_set_rsa_mgf1_md`]: https://www.openssl.org/docs/manmaster/man7/RSA-PSS.html
     pub fn set_rsa_mgf1_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
-        unsafe {
-            cvt(ffi::EVP_PKEY_CTX_set_rsa_mgf1_md(
-                self.pctx,
-                md.as_ptr() as *mut _,
-            ))
-            .map(|_| ())
-        }
+        self.ctx.set_rsa_mgf1_md(md)
     }
 
     /// Sets the RSA OAEP algorithm.
@@ -150,13 +111,7 @@ impl<'a> Encrypter<'a> {
     /// [`EVP_PKEY_CTX_set_rsa_oaep_md`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_CTX_set_rsa_oaep_md.html
     #[cfg(any(ossl102, libressl310))]
     pub fn set_rsa_oaep_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
-        unsafe {
-            cvt(ffi::EVP_PKEY_CTX_set_rsa_oaep_md(
-                self.pctx,
-                md.as_ptr() as *mut _,
-            ))
-            .map(|_| ())
-        }
+        self.ctx.set_rsa_oaep_md(md)
     }
 
     /// Sets the RSA OAEP label.
@@ -168,32 +123,7 @@ impl<'a> Encrypter<'a> {
     /// [`EVP_PKEY_CTX_set0_rsa_oaep_label`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_CTX_set0_rsa_oaep_label.html
     #[cfg(any(ossl102, libressl310))]
     pub fn set_rsa_oaep_label(&mut self, label: &[u8]) -> Result<(), ErrorStack> {
-        unsafe {
-            let p = cvt_p(ffi::CRYPTO_malloc(
-                label.len() as _,
-                concat!(file!(), "\0").as_ptr() as *const _,
-                line!() as c_int,
-            ))?;
-            ptr::copy_nonoverlapping(label.as_ptr(), p as *mut u8, label.len());
-
-            cvt(ffi::EVP_PKEY_CTX_set0_rsa_oaep_label(
-                self.pctx,
-                p as *mut c_void,
-                label.len() as c_int,
-            ))
-            .map(|_| ())
-            .map_err(|e| {
-                #[cfg(not(ossl110))]
-                ::ffi::CRYPTO_free(p as *mut c_void);
-                #[cfg(ossl110)]
-                ::ffi::CRYPTO_free(
-                    p as *mut c_void,
-                    concat!(file!(), "\0").as_ptr() as *const _,
-                    line!() as c_int,
-                );
-                e
-            })
-        }
+        self.ctx.set_rsa_oaep_label(label)
     }
 
     /// Performs public key encryption.
@@ -230,18 +160,7 @@ impl<'a> Encrypter<'a> {
     ///
     /// [`EVP_PKEY_encrypt`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_encrypt.html
     pub fn encrypt(&self, from: &[u8], to: &mut [u8]) -> Result<usize, ErrorStack> {
-        let mut written = to.len();
-        unsafe {
-            cvt(ffi::EVP_PKEY_encrypt(
-                self.pctx,
-                to.as_mut_ptr(),
-                &mut written,
-                from.as_ptr(),
-                from.len(),
-            ))?;
-        }
-
-        Ok(written)
+        self.ctx.encrypt(from, to)
     }
 
     /// Gets the size of the buffer needed to encrypt the input data.
@@ -250,38 +169,16 @@ impl<'a> Encrypter<'a> {
     ///
     /// [`EVP_PKEY_encrypt`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_encrypt.html
     pub fn encrypt_len(&self, from: &[u8]) -> Result<usize, ErrorStack> {
-        let mut written = 0;
-        unsafe {
-            cvt(ffi::EVP_PKEY_encrypt(
-                self.pctx,
-                ptr::null_mut(),
-                &mut written,
-                from.as_ptr(),
-                from.len(),
-            ))?;
-        }
-
-        Ok(written)
+        self.ctx.encrypt_len(from)
     }
 }
 
 /// A type which decrypts data.
 pub struct Decrypter<'a> {
-    pctx: *mut ffi::EVP_PKEY_CTX,
+    ctx: PKeyCtx,
     _p: PhantomData<&'a ()>,
 }
 
-unsafe impl<'a> Sync for Decrypter<'a> {}
-unsafe impl<'a> Send for Decrypter<'a> {}
-
-impl<'a> Drop for Decrypter<'a> {
-    fn drop(&mut self) {
-        unsafe {
-            ffi::EVP_PKEY_CTX_free(self.pctx);
-        }
-    }
-}
-
 impl<'a> Decrypter<'a> {
     /// Creates a new `Decrypter`.
     ///
@@ -292,21 +189,13 @@ impl<'a> Decrypter<'a> {
     where
         T: HasPrivate,
     {
-        unsafe {
-            ffi::init();
-
-            let pctx = cvt_p(ffi::EVP_PKEY_CTX_new(pkey.as_ptr(), ptr::null_mut()))?;
-            let r = ffi::EVP_PKEY_decrypt_init(pctx);
-            if r != 1 {
-                ffi::EVP_PKEY_CTX_free(pctx);
-                return Err(ErrorStack::get());
-            }
-
-            Ok(Decrypter {
-                pctx,
-                _p: PhantomData,
-            })
-        }
+        let ctx = PKeyCtx::from_pkey(pkey)?;
+        ctx.decrypt_init()?;
+
+        Ok(Decrypter {
+            ctx,
+            _p: PhantomData,
+        })
     }
 
     /// Returns the RSA padding mode in use.
@@ -315,11 +204,7 @@ impl<'a> Decrypter<'a> {
     ///
     /// This corresponds to `EVP_PKEY_CTX_get_rsa_padding`.
     pub fn rsa_padding(&self) -> Result<Padding, ErrorStack> {
-        unsafe {
-            let mut pad = 0;
-            cvt(ffi::EVP_PKEY_CTX_get_rsa_padding(self.pctx, &mut pad))
-                .map(|_| Padding::from_raw(pad))
-        }
+        self.ctx.rsa_padding()
     }
 
     /// Sets the RSA padding mode.
@@ -330,13 +215,7 @@ impl<'a> Decrypter<'a> {
     ///
     /// [`EVP_PKEY_CTX_set_rsa_padding`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_rsa_padding.html
     pub fn set_rsa_padding(&mut self, padding: Padding) -> Result<(), ErrorStack> {
-        unsafe {
-            cvt(ffi::EVP_PKEY_CTX_set_rsa_padding(
-                self.pctx,
-                padding.as_raw(),
-            ))
-            .map(|_| ())
-        }
+        self.ctx.set_rsa_padding(padding)
     }
 
     /// Sets the RSA MGF1 algorithm.
@@ -347,13 +226,7 @@ impl<'a> Decrypter<'a> {
     ///
     /// [`EVP_PKEY_CTX_set_rsa_mgf1_md`]: https://www.openssl.org/docs/manmaster/man7/RSA-PSS.html
     pub fn set_rsa_mgf1_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
-        unsafe {
-            cvt(ffi::EVP_PKEY_CTX_set_rsa_mgf1_md(
-                self.pctx,
-                md.as_ptr() as *mut _,
-            ))
-            .map(|_| ())
-        }
+        self.ctx.set_rsa_mgf1_md(md)
     }
 
     /// Sets the RSA OAEP algorithm.
@@ -365,13 +238,7 @@ impl<'a> Decrypter<'a> {
     /// [`EVP_PKEY_CTX_set_rsa_oaep_md`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_CTX_set_rsa_oaep_md.html
     #[cfg(any(ossl102, libressl310))]
     pub fn set_rsa_oaep_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
-        unsafe {
-            cvt(ffi::EVP_PKEY_CTX_set_rsa_oaep_md(
-                self.pctx,
-                md.as_ptr() as *mut _,
-            ))
-            .map(|_| ())
-        }
+        self.ctx.set_rsa_oaep_md(md)
     }
 
     /// Performs public key decryption.
@@ -423,18 +290,7 @@ impl<'a> Decrypter<'a> {
     ///
     /// [`EVP_PKEY_decrypt`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_decrypt.html
     pub fn decrypt(&self, from: &[u8], to: &mut [u8]) -> Result<usize, ErrorStack> {
-        let mut written = to.len();
-        unsafe {
-            cvt(ffi::EVP_PKEY_decrypt(
-                self.pctx,
-                to.as_mut_ptr(),
-                &mut written,
-                from.as_ptr(),
-                from.len(),
-            ))?;
-        }
-
-        Ok(written)
+        self.ctx.decrypt(from, to)
     }
 
     /// Gets the size of the buffer needed to decrypt the input data.
@@ -443,18 +299,7 @@ impl<'a> Decrypter<'a> {
     ///
     /// [`EVP_PKEY_decrypt`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_decrypt.html
     pub fn decrypt_len(&self, from: &[u8]) -> Result<usize, ErrorStack> {
-        let mut written = 0;
-        unsafe {
-            cvt(ffi::EVP_PKEY_decrypt(
-                self.pctx,
-                ptr::null_mut(),
-                &mut written,
-                from.as_ptr(),
-                from.len(),
-            ))?;
-        }
-
-        Ok(written)
+        self.ctx.decrypt_len(from)
     }
 }
 
@@ -47,6 +47,17 @@ impl ErrorStack {
             error.put();
         }
     }
+
+    /// Creates a stack holding a single error detected by this crate rather than by OpenSSL.
+    pub(crate) fn internal(lib: c_int, reason: c_int, data: &'static str) -> ErrorStack {
+        ErrorStack(vec![Error {
+            code: ffi::ERR_PACK(lib, 0, reason),
+            file: concat!(file!(), "\0").as_ptr() as *const c_char,
+            line: line!() as c_int,
+            func: ptr::null(),
+            data: Some(Cow::Borrowed(data)),
+        }])
+    }
 }
 
 impl ErrorStack {
@@ -1,5 +1,14 @@
-use libc::c_int;
+use libc::{c_int, c_long, c_void};
+use once_cell::sync::Lazy;
+use std::any::TypeId;
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::Mutex;
+
+use crate::error::ErrorStack;
+
+static INDEXES: Lazy<Mutex<HashMap<(TypeId, TypeId), c_int>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 
 /// A slot in a type's "extra data" structure.
 ///
@@ -30,3 +39,39 @@ impl<T, U> Index<T, U> {
         self.0
     }
 }
+
+/// Returns the index used to store a `U` in a `T`, creating it with `new_index` on first use.
+///
+/// Indexes are shared by every value of the same type, so callbacks can find their data.
+pub(crate) fn cached_index<T, U, F>(new_index: F) -> Result<Index<T, U>, ErrorStack>
+where
+    T: 'static,
+    U: 'static,
+    F: FnOnce() -> Result<Index<T, U>, ErrorStack>,
+{
+    let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+    let key = (TypeId::of::<T>(), TypeId::of::<U>());
+    let idx = match indexes.get(&key) {
+        Some(idx) => *idx,
+        None => {
+            let idx = new_index()?.as_raw();
+            indexes.insert(key, idx);
+            idx
+        }
+    };
+    unsafe { Ok(Index::from_raw(idx)) }
+}
+
+/// Frees extra data stored as a `Box<T>`, for use as the free function of an index.
+pub(crate) unsafe extern "C" fn free_data_box<T>(
+    _parent: *mut c_void,
+    ptr: *mut c_void,
+    _ad: *mut ffi::CRYPTO_EX_DATA,
+    _idx: c_int,
+    _argl: c_long,
+    _argp: *mut c_void,
+) {
+    if !ptr.is_null() {
+        drop(Box::<T>::from_raw(ptr as *mut T));
+    }
+}
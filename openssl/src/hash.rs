@@ -1,12 +1,21 @@
+use bitflags::bitflags;
 use cfg_if::cfg_if;
-use std::ffi::CString;
+#[cfg(ossl300)]
+use foreign_types::ForeignTypeRef;
+use libc::{c_char, c_ulong, c_void};
+use std::ffi::{CStr, CString};
 use std::fmt;
+use std::fs::File;
 use std::io;
 use std::io::prelude::*;
 use std::ops::{Deref, DerefMut};
+use std::path::Path;
 use std::ptr;
+use std::str;
 
 use crate::error::ErrorStack;
+#[cfg(ossl300)]
+use crate::md::{Md, MdRef};
 use crate::nid::Nid;
 use crate::{cvt, cvt_p};
 
@@ -18,6 +27,20 @@ cfg_if! {
     }
 }
 
+bitflags! {
+    /// Properties of a message digest algorithm.
+    pub struct MessageDigestFlags: c_ulong {
+        /// The digest is only usable in a single update call.
+        const ONESHOT = ffi::EVP_MD_FLAG_ONESHOT;
+
+        /// The digest is an extendable output function such as SHAKE.
+        ///
+        /// Requires OpenSSL 1.1.1 or newer.
+        #[cfg(ossl111)]
+        const XOF = ffi::EVP_MD_FLAG_XOF;
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct MessageDigest(*const ffi::EVP_MD);
 
@@ -149,6 +172,50 @@ impl MessageDigest {
     pub fn type_(&self) -> Nid {
         Nid::from_raw(unsafe { ffi::EVP_MD_type(self.0) })
     }
+
+    /// The canonical short name of the digest, such as `SHA256`.
+    ///
+    /// This is the name accepted by [`from_name`]. Digests without a registered name return
+    /// `UNDEF`.
+    ///
+    /// [`from_name`]: #method.from_name
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn name(&self) -> &'static str {
+        self.type_().short_name().unwrap_or("UNDEF")
+    }
+
+    /// The block size of the digest in bytes.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn block_size(&self) -> usize {
+        unsafe { ffi::EVP_MD_block_size(self.0) as usize }
+    }
+
+    /// Returns the flags describing the digest.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn flags(&self) -> MessageDigestFlags {
+        MessageDigestFlags::from_bits_truncate(unsafe { ffi::EVP_MD_flags(self.0) })
+    }
+
+    /// Determines if the digest is an extendable output function.
+    ///
+    /// The output of such digests must be retrieved with [`Hasher::finish_xof`].
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// [`Hasher::finish_xof`]: struct.Hasher.html#method.finish_xof
+    #[cfg(ossl111)]
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn is_xof(&self) -> bool {
+        self.flags().contains(MessageDigestFlags::XOF)
+    }
+}
+
+impl fmt::Debug for MessageDigest {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("MessageDigest")
+            .field(&self.name())
+            .finish()
+    }
 }
 
 unsafe impl Sync for MessageDigest {}
@@ -220,6 +287,8 @@ pub struct Hasher {
     md: *const ffi::EVP_MD,
     type_: MessageDigest,
     state: State,
+    #[cfg(ossl300)]
+    fetched: Option<Md>,
 }
 
 unsafe impl Sync for Hasher {}
@@ -237,6 +306,30 @@ impl Hasher {
             md: ty.as_ptr(),
             type_: ty,
             state: Finalized,
+            #[cfg(ossl300)]
+            fetched: None,
+        };
+        h.init()?;
+        Ok(h)
+    }
+
+    /// Creates a new `Hasher` using a digest fetched from a provider.
+    ///
+    /// The `Hasher` holds a reference to the digest, so it remains usable after `md` is dropped.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn with_md(md: &MdRef) -> Result<Hasher, ErrorStack> {
+        ffi::init();
+
+        let ctx = unsafe { cvt_p(EVP_MD_CTX_new())? };
+
+        let mut h = Hasher {
+            ctx,
+            md: md.as_ptr(),
+            type_: unsafe { MessageDigest::from_ptr(md.as_ptr()) },
+            state: Finalized,
+            fetched: Some(md.to_owned()),
         };
         h.init()?;
         Ok(h)
@@ -339,6 +432,8 @@ impl Clone for Hasher {
             md: self.md,
             type_: self.type_,
             state: self.state,
+            #[cfg(ossl300)]
+            fetched: self.fetched.clone(),
         }
     }
 }
@@ -400,6 +495,85 @@ impl fmt::Debug for DigestBytes {
     }
 }
 
+/// A digest name registered with OpenSSL.
+///
+/// Entries are returned by [`digests`].
+///
+/// [`digests`]: fn.digests.html
+#[derive(Debug, Clone)]
+pub struct DigestEntry {
+    name: String,
+    alias_of: Option<String>,
+    digest: Option<MessageDigest>,
+}
+
+impl DigestEntry {
+    /// Returns the registered name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// If this entry is an alias, returns the name of the digest it refers to.
+    pub fn alias_of(&self) -> Option<&str> {
+        self.alias_of.as_deref()
+    }
+
+    /// Returns the digest the name resolves to.
+    ///
+    /// This may be `None` for aliases of digests which are unavailable.
+    pub fn digest(&self) -> Option<MessageDigest> {
+        self.digest
+    }
+}
+
+/// Returns all digest names registered with OpenSSL, sorted by name.
+///
+/// Both the canonical names of the digests and their aliases are included.
+///
+/// This corresponds to [`EVP_MD_do_all_sorted`].
+///
+/// [`EVP_MD_do_all_sorted`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_MD_do_all_sorted.html
+pub fn digests() -> Vec<DigestEntry> {
+    unsafe extern "C" fn callback(
+        md: *const ffi::EVP_MD,
+        from: *const c_char,
+        to: *const c_char,
+        arg: *mut c_void,
+    ) {
+        if from.is_null() {
+            return;
+        }
+        let entries = &mut *(arg as *mut Vec<DigestEntry>);
+        let to_string = |s: *const c_char| String::from_utf8_lossy(CStr::from_ptr(s).to_bytes());
+        entries.push(DigestEntry {
+            name: to_string(from).into_owned(),
+            alias_of: if to.is_null() {
+                None
+            } else {
+                Some(to_string(to).into_owned())
+            },
+            digest: if md.is_null() {
+                None
+            } else {
+                Some(MessageDigest(md))
+            },
+        });
+    }
+
+    ffi::init();
+    let mut entries: Vec<DigestEntry> = vec![];
+    unsafe {
+        ffi::EVP_MD_do_all_sorted(Some(callback), &mut entries as *mut _ as *mut c_void);
+    }
+    // resolve aliases outside of the callback to avoid reentering the name table
+    for entry in &mut entries {
+        if entry.digest.is_none() {
+            entry.digest = MessageDigest::from_name(&entry.name);
+        }
+    }
+    entries
+}
+
 /// Computes the hash of the `data` with the non-XOF hasher `t`.
 pub fn hash(t: MessageDigest, data: &[u8]) -> Result<DigestBytes, ErrorStack> {
     let mut h = Hasher::new(t)?;
@@ -415,6 +589,53 @@ pub fn hash_xof(t: MessageDigest, data: &[u8], buf: &mut [u8]) -> Result<(), Err
     h.finish_xof(buf)
 }
 
+/// Computes the hash of all data read from `reader` with the non-XOF hasher `t`.
+///
+/// The reader is consumed until it returns end of file.
+pub fn hash_reader<R>(t: MessageDigest, reader: R) -> io::Result<DigestBytes>
+where
+    R: Read,
+{
+    hash_reader_with_capacity(t, reader, 8 * 1024)
+}
+
+/// Computes the hash of the contents of the file at `path` with the non-XOF hasher `t`.
+///
+/// The file is read in chunks of `buf_size` bytes.
+///
+/// # Panics
+///
+/// Panics if `buf_size` is 0.
+pub fn hash_file<P>(t: MessageDigest, path: P, buf_size: usize) -> io::Result<DigestBytes>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(path)?;
+    hash_reader_with_capacity(t, file, buf_size)
+}
+
+fn hash_reader_with_capacity<R>(
+    t: MessageDigest,
+    mut reader: R,
+    buf_size: usize,
+) -> io::Result<DigestBytes>
+where
+    R: Read,
+{
+    assert!(buf_size > 0);
+    let mut h = Hasher::new(t)?;
+    let mut buf = vec![0; buf_size];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => h.write_all(&buf[..len])?,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(h.finish()?)
+}
+
 #[cfg(test)]
 mod tests {
     use hex::{self, FromHex};
@@ -645,6 +866,88 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(ossl300)]
+    fn test_fetched_md() {
+        let md = Md::fetch("SHA256", Some("provider=default")).unwrap();
+        let mut h = Hasher::with_md(&md).unwrap();
+        drop(md);
+
+        h.update(b"hello").unwrap();
+        let mut h2 = h.clone();
+        h2.update(b", world").unwrap();
+        assert_eq!(
+            hex::encode(h.finish().unwrap()),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(
+            &*h2.finish().unwrap(),
+            &*hash(MessageDigest::sha256(), b"hello, world").unwrap()
+        );
+
+        // the hasher may be reused after finishing
+        h.update(b"hello").unwrap();
+        assert_eq!(
+            hex::encode(h.finish().unwrap()),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[test]
+    fn metadata() {
+        let md = MessageDigest::sha256();
+        assert_eq!(md.name(), "SHA256");
+        assert_eq!(md.block_size(), 64);
+        assert_eq!(MessageDigest::from_name(md.name()).unwrap(), md);
+        assert_eq!(format!("{:?}", md), "MessageDigest(\"SHA256\")");
+        assert_eq!(MessageDigest::sha512().block_size(), 128);
+    }
+
+    #[test]
+    #[cfg(ossl111)]
+    fn xof_metadata() {
+        assert!(MessageDigest::shake_128().is_xof());
+        assert!(MessageDigest::shake_128()
+            .flags()
+            .contains(MessageDigestFlags::XOF));
+        assert!(!MessageDigest::sha3_256().is_xof());
+    }
+
+    #[test]
+    fn enumerate() {
+        let entries = digests();
+        assert!(entries.windows(2).all(|w| w[0].name() <= w[1].name()));
+
+        let sha256 = entries
+            .iter()
+            .find(|e| e.name().eq_ignore_ascii_case("SHA256") && e.alias_of().is_none())
+            .unwrap();
+        assert_eq!(sha256.digest(), Some(MessageDigest::sha256()));
+
+        let alias = entries.iter().find(|e| e.name() == "RSA-SHA256").unwrap();
+        assert_eq!(alias.alias_of(), Some("SHA256"));
+        assert_eq!(alias.digest(), Some(MessageDigest::sha256()));
+    }
+
+    #[test]
+    fn reader() {
+        let data = (0..100_000).map(|i| i as u8).collect::<Vec<_>>();
+        let expected = hash(MessageDigest::sha256(), &data).unwrap();
+
+        let actual = hash_reader(MessageDigest::sha256(), &data[..]).unwrap();
+        assert_eq!(&*actual, &*expected);
+
+        let dir = tempdir::TempDir::new("hash").unwrap();
+        let path = dir.path().join("data");
+        std::fs::write(&path, &data).unwrap();
+        for &buf_size in &[1, 1000, 1 << 20] {
+            let actual = hash_file(MessageDigest::sha256(), &path, buf_size).unwrap();
+            assert_eq!(&*actual, &*expected);
+        }
+
+        assert!(hash_file(MessageDigest::sha256(), dir.path().join("missing"), 1024).is_err());
+    }
+
     #[test]
     fn from_nid() {
         assert_eq!(
@@ -0,0 +1,216 @@
+//! Key derivation functions built on `EVP_PKEY_CTX`.
+//!
+//! This module provides HKDF, as described in [RFC 5869], and the TLS1-PRF used by TLS 1.0 through
+//! 1.2. Lower level control is available through the KDF methods of [`PKeyCtx`].
+//!
+//! Requires OpenSSL 1.1.0 or newer.
+//!
+//! [RFC 5869]: https://tools.ietf.org/html/rfc5869
+//! [`PKeyCtx`]: ../pkey/struct.PKeyCtx.html
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::hash::MessageDigest;
+//! use openssl::kdf::hkdf;
+//!
+//! let mut key = [0; 32];
+//! hkdf(
+//!     MessageDigest::sha256(),
+//!     b"input keying material",
+//!     b"salt",
+//!     b"context",
+//!     &mut key,
+//! )
+//! .unwrap();
+//! ```
+#[cfg(ossl111)]
+use libc::c_int;
+
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::pkey::{Id, PKeyCtx};
+
+/// The mode of operation of HKDF.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HkdfMode(c_int);
+
+#[cfg(ossl111)]
+impl HkdfMode {
+    /// Performs the extract step followed by the expand step.
+    pub const EXTRACT_AND_EXPAND: HkdfMode = HkdfMode(ffi::EVP_PKEY_HKDEF_MODE_EXTRACT_AND_EXPAND);
+
+    /// Performs only the extract step, producing a pseudorandom key.
+    pub const EXTRACT_ONLY: HkdfMode = HkdfMode(ffi::EVP_PKEY_HKDEF_MODE_EXTRACT_ONLY);
+
+    /// Performs only the expand step, using the key as the pseudorandom key.
+    pub const EXPAND_ONLY: HkdfMode = HkdfMode(ffi::EVP_PKEY_HKDEF_MODE_EXPAND_ONLY);
+
+    /// Creates a `HkdfMode` from an integer representation.
+    pub fn from_raw(value: c_int) -> HkdfMode {
+        HkdfMode(value)
+    }
+
+    /// Returns the integer representation of the `HkdfMode`.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+fn hkdf_ctx(digest: MessageDigest, key: &[u8], salt: &[u8]) -> Result<PKeyCtx, ErrorStack> {
+    let ctx = PKeyCtx::new(Id::HKDF)?;
+    ctx.derive_init()?;
+    ctx.set_hkdf_md(digest)?;
+    if !salt.is_empty() {
+        ctx.set_hkdf_salt(salt)?;
+    }
+    ctx.set_hkdf_key(key)?;
+    Ok(ctx)
+}
+
+/// Derives `out.len()` bytes from the input keying material `key` using HKDF.
+///
+/// Both the extract and expand steps are performed. An empty `salt` is treated as a string of
+/// zeros of the digest's length.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+pub fn hkdf(
+    digest: MessageDigest,
+    key: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let ctx = hkdf_ctx(digest, key, salt)?;
+    if !info.is_empty() {
+        ctx.add_hkdf_info(info)?;
+    }
+    ctx.derive(out).map(|_| ())
+}
+
+/// Performs the HKDF extract step, returning the pseudorandom key.
+///
+/// The returned key is the size of the digest's output.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+pub fn hkdf_extract(digest: MessageDigest, key: &[u8], salt: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let ctx = hkdf_ctx(digest, key, salt)?;
+    ctx.set_hkdf_mode(HkdfMode::EXTRACT_ONLY)?;
+    let mut prk = vec![0; digest.size()];
+    let len = ctx.derive(&mut prk)?;
+    prk.truncate(len);
+    Ok(prk)
+}
+
+/// Performs the HKDF expand step, deriving `out.len()` bytes from the pseudorandom key `prk`.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+pub fn hkdf_expand(
+    digest: MessageDigest,
+    prk: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let ctx = hkdf_ctx(digest, prk, &[])?;
+    ctx.set_hkdf_mode(HkdfMode::EXPAND_ONLY)?;
+    if !info.is_empty() {
+        ctx.add_hkdf_info(info)?;
+    }
+    ctx.derive(out).map(|_| ())
+}
+
+/// Derives `out.len()` bytes from `secret` and `seed` using the TLS1-PRF.
+///
+/// In TLS the seed is the concatenation of the label and the seed value.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+pub fn tls1_prf(
+    digest: MessageDigest,
+    secret: &[u8],
+    seed: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let ctx = PKeyCtx::new(Id::TLS1_PRF)?;
+    ctx.derive_init()?;
+    ctx.set_tls1_prf_md(digest)?;
+    ctx.set_tls1_prf_secret(secret)?;
+    ctx.add_tls1_prf_seed(seed)?;
+    ctx.derive(out).map(|_| ())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Test case 1 from RFC 5869
+    const IKM: [u8; 22] = [0x0b; 22];
+    const SALT: &str = "000102030405060708090a0b0c";
+    const INFO: &str = "f0f1f2f3f4f5f6f7f8f9";
+    const PRK: &str = "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5";
+    const OKM: &str =
+        "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865";
+
+    #[test]
+    fn hkdf_sha256() {
+        let mut okm = [0; 42];
+        hkdf(
+            MessageDigest::sha256(),
+            &IKM,
+            &hex::decode(SALT).unwrap(),
+            &hex::decode(INFO).unwrap(),
+            &mut okm,
+        )
+        .unwrap();
+        assert_eq!(hex::encode(&okm[..]), OKM);
+    }
+
+    #[test]
+    #[cfg(ossl111)]
+    fn hkdf_extract_then_expand() {
+        let prk = hkdf_extract(MessageDigest::sha256(), &IKM, &hex::decode(SALT).unwrap()).unwrap();
+        assert_eq!(hex::encode(&prk), PRK);
+
+        let mut okm = [0; 42];
+        hkdf_expand(
+            MessageDigest::sha256(),
+            &prk,
+            &hex::decode(INFO).unwrap(),
+            &mut okm,
+        )
+        .unwrap();
+        assert_eq!(hex::encode(&okm[..]), OKM);
+    }
+
+    #[test]
+    fn tls1_prf_sha256() {
+        let mut out = [0; 32];
+        tls1_prf(
+            MessageDigest::sha256(),
+            b"secret",
+            b"master secretseed",
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(&out[..]),
+            "5ea239aa2f6949c46c18bd3dccb71942d3809fa40fcf19c661430a9fd8abcf6a"
+        );
+
+        // the seed may be supplied in multiple pieces
+        let ctx = PKeyCtx::new(Id::TLS1_PRF).unwrap();
+        ctx.derive_init().unwrap();
+        ctx.set_tls1_prf_md(MessageDigest::sha256()).unwrap();
+        ctx.set_tls1_prf_secret(b"secret").unwrap();
+        ctx.add_tls1_prf_seed(b"master secret").unwrap();
+        ctx.add_tls1_prf_seed(b"seed").unwrap();
+        let mut out2 = [0; 32];
+        assert_eq!(ctx.derive(&mut out2).unwrap(), 32);
+        assert_eq!(out, out2);
+    }
+}
@@ -134,6 +134,9 @@ pub mod aes;
 pub mod asn1;
 pub mod base64;
 pub mod bn;
+#[cfg(ossl300)]
+pub mod cipher;
+pub mod cipher_ctx;
 #[cfg(all(not(libressl), not(osslconf = "OPENSSL_NO_CMS")))]
 pub mod cms;
 pub mod conf;
@@ -142,6 +145,7 @@ pub mod dh;
 pub mod dsa;
 pub mod ec;
 pub mod ecdsa;
+pub mod encoding;
 pub mod encrypt;
 pub mod engine;
 pub mod envelope;
@@ -150,6 +154,13 @@ pub mod ex_data;
 #[cfg(not(any(libressl, ossl300)))]
 pub mod fips;
 pub mod hash;
+#[cfg(ossl110)]
+pub mod kdf;
+#[cfg(ossl300)]
+pub mod lib_ctx;
+pub mod mac;
+#[cfg(ossl300)]
+pub mod md;
 pub mod memcmp;
 pub mod nid;
 #[cfg(not(osslconf = "OPENSSL_NO_OCSP"))]
@@ -158,6 +169,8 @@ pub mod pkcs12;
 pub mod pkcs5;
 pub mod pkcs7;
 pub mod pkey;
+#[cfg(ossl300)]
+pub mod provider;
 pub mod rand;
 pub mod rsa;
 pub mod sha;
@@ -0,0 +1,129 @@
+//! OpenSSL library contexts.
+//!
+//! A library context is an isolated scope for providers, configuration and algorithm fetching. It
+//! allows, for example, one part of a program to run with only the FIPS provider loaded while
+//! another uses legacy algorithms.
+//!
+//! Objects created within a library context, such as loaded providers, fetched algorithms, keys
+//! and SSL contexts, must be dropped before the library context itself. The constructors which
+//! take a library context are therefore `unsafe`; each has a safe counterpart using the default
+//! library context.
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::lib_ctx::LibCtx;
+//! use openssl::md::Md;
+//! use openssl::provider::Provider;
+//!
+//! let ctx = LibCtx::new().unwrap();
+//!
+//! // The provider and digest are dropped before the library context.
+//! let _provider = unsafe { Provider::load_in(&ctx, "default").unwrap() };
+//! let md = unsafe { Md::fetch_in(&ctx, "SHA256", None).unwrap() };
+//! assert_eq!(md.size(), 32);
+//! ```
+use foreign_types::{ForeignType, ForeignTypeRef};
+use std::ffi::CString;
+use std::path::Path;
+
+use crate::error::ErrorStack;
+use crate::{cvt, cvt_p};
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::OSSL_LIB_CTX;
+    fn drop = ffi::OSSL_LIB_CTX_free;
+
+    /// An OpenSSL library context.
+    pub struct LibCtx;
+    /// Reference to a [`LibCtx`].
+    ///
+    /// [`LibCtx`]: struct.LibCtx.html
+    pub struct LibCtxRef;
+}
+
+impl LibCtx {
+    /// Creates a new library context.
+    ///
+    /// This corresponds to [`OSSL_LIB_CTX_new`].
+    ///
+    /// [`OSSL_LIB_CTX_new`]: https://www.openssl.org/docs/man3.0/man3/OSSL_LIB_CTX_new.html
+    pub fn new() -> Result<LibCtx, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let ptr = cvt_p(ffi::OSSL_LIB_CTX_new())?;
+            Ok(LibCtx::from_ptr(ptr))
+        }
+    }
+}
+
+impl LibCtxRef {
+    /// Loads an OpenSSL configuration file into the library context.
+    ///
+    /// This corresponds to [`OSSL_LIB_CTX_load_config`].
+    ///
+    /// [`OSSL_LIB_CTX_load_config`]: https://www.openssl.org/docs/man3.0/man3/OSSL_LIB_CTX_load_config.html
+    pub fn load_config<P: AsRef<Path>>(&self, file: P) -> Result<(), ErrorStack> {
+        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe { cvt(ffi::OSSL_LIB_CTX_load_config(self.as_ptr(), file.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the default property query used when fetching algorithms in the library context.
+    ///
+    /// This corresponds to [`EVP_set_default_properties`].
+    ///
+    /// [`EVP_set_default_properties`]: https://www.openssl.org/docs/man3.0/man3/EVP_set_default_properties.html
+    pub fn set_default_properties(&self, properties: &str) -> Result<(), ErrorStack> {
+        let properties = CString::new(properties).unwrap();
+        unsafe {
+            cvt(ffi::EVP_set_default_properties(
+                self.as_ptr(),
+                properties.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Enables or disables the `fips=yes` default property query in the library context.
+    ///
+    /// When enabled, only algorithms implemented by a FIPS provider are fetched by default.
+    ///
+    /// This corresponds to [`EVP_default_properties_enable_fips`].
+    ///
+    /// [`EVP_default_properties_enable_fips`]: https://www.openssl.org/docs/man3.0/man3/EVP_default_properties_enable_fips.html
+    pub fn set_fips_enabled(&self, enabled: bool) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_default_properties_enable_fips(
+                self.as_ptr(),
+                enabled as _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Determines if the `fips=yes` default property query is enabled in the library context.
+    ///
+    /// This corresponds to [`EVP_default_properties_is_fips_enabled`].
+    ///
+    /// [`EVP_default_properties_is_fips_enabled`]: https://www.openssl.org/docs/man3.0/man3/EVP_default_properties_is_fips_enabled.html
+    pub fn fips_enabled(&self) -> bool {
+        unsafe { ffi::EVP_default_properties_is_fips_enabled(self.as_ptr()) != 0 }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fips_default_properties() {
+        let ctx = LibCtx::new().unwrap();
+        assert!(!ctx.fips_enabled());
+        ctx.set_fips_enabled(true).unwrap();
+        assert!(ctx.fips_enabled());
+        ctx.set_fips_enabled(false).unwrap();
+        assert!(!ctx.fips_enabled());
+    }
+}
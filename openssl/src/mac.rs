@@ -0,0 +1,279 @@
+//! Message authentication codes.
+//!
+//! The `Mac` type computes a MAC over a stream of data with a symmetric key, and can verify an
+//! expected tag in constant time. HMAC and CMAC are supported, as well as Poly1305 and SipHash on
+//! OpenSSL 1.1.1 and newer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::hash::MessageDigest;
+//! use openssl::mac::{Mac, MacAlgorithm};
+//!
+//! let algorithm = MacAlgorithm::hmac(MessageDigest::sha256());
+//!
+//! let mut mac = Mac::new(algorithm, b"my secret").unwrap();
+//! mac.update(b"hello, world!").unwrap();
+//! let tag = mac.finish().unwrap();
+//!
+//! let mut mac = Mac::new(algorithm, b"my secret").unwrap();
+//! mac.update(b"hello, world!").unwrap();
+//! assert!(mac.verify(&tag).unwrap());
+//! ```
+use cfg_if::cfg_if;
+use foreign_types::ForeignType;
+use std::io::{self, Write};
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::memcmp;
+#[cfg(ossl111)]
+use crate::pkey::Id;
+use crate::pkey::PKey;
+#[cfg(ossl110)]
+use crate::symm::Cipher;
+use crate::{cvt, cvt_p};
+
+cfg_if! {
+    if #[cfg(ossl110)] {
+        use ffi::{EVP_MD_CTX_free, EVP_MD_CTX_new};
+    } else {
+        use ffi::{EVP_MD_CTX_create as EVP_MD_CTX_new, EVP_MD_CTX_destroy as EVP_MD_CTX_free};
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Inner {
+    Hmac(MessageDigest),
+    #[cfg(ossl110)]
+    Cmac(Cipher),
+    #[cfg(ossl111)]
+    Poly1305,
+    #[cfg(ossl111)]
+    SipHash,
+}
+
+/// A MAC algorithm.
+#[derive(Copy, Clone)]
+pub struct MacAlgorithm(Inner);
+
+impl MacAlgorithm {
+    /// HMAC using the specified digest.
+    pub fn hmac(digest: MessageDigest) -> MacAlgorithm {
+        MacAlgorithm(Inner::Hmac(digest))
+    }
+
+    /// CMAC using the specified block cipher.
+    ///
+    /// The key must be the length of the cipher's key.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    pub fn cmac(cipher: Cipher) -> MacAlgorithm {
+        MacAlgorithm(Inner::Cmac(cipher))
+    }
+
+    /// Poly1305.
+    ///
+    /// The key must be 32 bytes long, and must only ever be used for a single message.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn poly1305() -> MacAlgorithm {
+        MacAlgorithm(Inner::Poly1305)
+    }
+
+    /// SipHash-2-4 with a 16 byte output.
+    ///
+    /// The key must be 16 bytes long.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn siphash() -> MacAlgorithm {
+        MacAlgorithm(Inner::SipHash)
+    }
+}
+
+/// A type which computes a message authentication code over data.
+pub struct Mac {
+    md_ctx: *mut ffi::EVP_MD_CTX,
+}
+
+unsafe impl Sync for Mac {}
+unsafe impl Send for Mac {}
+
+impl Drop for Mac {
+    fn drop(&mut self) {
+        unsafe {
+            EVP_MD_CTX_free(self.md_ctx);
+        }
+    }
+}
+
+impl Mac {
+    /// Creates a new `Mac` with the specified algorithm and key.
+    ///
+    /// OpenSSL documentation at [`EVP_DigestSignInit`].
+    ///
+    /// [`EVP_DigestSignInit`]: https://www.openssl.org/docs/manmaster/man3/EVP_DigestSignInit.html
+    pub fn new(algorithm: MacAlgorithm, key: &[u8]) -> Result<Mac, ErrorStack> {
+        let (digest, pkey) = match algorithm.0 {
+            Inner::Hmac(digest) => (Some(digest), PKey::hmac(key)?),
+            #[cfg(ossl110)]
+            Inner::Cmac(cipher) => (None, PKey::cmac(&cipher, key)?),
+            #[cfg(ossl111)]
+            Inner::Poly1305 => (None, PKey::private_key_from_raw_bytes(key, Id::POLY1305)?),
+            #[cfg(ossl111)]
+            Inner::SipHash => (None, PKey::private_key_from_raw_bytes(key, Id::SIPHASH)?),
+        };
+
+        unsafe {
+            ffi::init();
+
+            let md_ctx = cvt_p(EVP_MD_CTX_new())?;
+            let mac = Mac { md_ctx };
+            cvt(ffi::EVP_DigestSignInit(
+                mac.md_ctx,
+                ptr::null_mut(),
+                digest.map_or(ptr::null(), |d| d.as_ptr()),
+                ptr::null_mut(),
+                pkey.as_ptr(),
+            ))?;
+            Ok(mac)
+        }
+    }
+
+    /// Feeds more data into the `Mac`.
+    ///
+    /// OpenSSL documentation at [`EVP_DigestUpdate`].
+    ///
+    /// [`EVP_DigestUpdate`]: https://www.openssl.org/docs/manmaster/man3/EVP_DigestInit.html
+    pub fn update(&mut self, buf: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_DigestUpdate(
+                self.md_ctx,
+                buf.as_ptr() as *const _,
+                buf.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the MAC of the data fed into the `Mac` so far.
+    ///
+    /// More data may be fed in afterwards, and the MAC of the longer message computed.
+    ///
+    /// OpenSSL documentation at [`EVP_DigestSignFinal`].
+    ///
+    /// [`EVP_DigestSignFinal`]: https://www.openssl.org/docs/manmaster/man3/EVP_DigestSignFinal.html
+    pub fn finish(&mut self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let mut len = 0;
+            cvt(ffi::EVP_DigestSignFinal(
+                self.md_ctx,
+                ptr::null_mut(),
+                &mut len,
+            ))?;
+            let mut buf = vec![0; len];
+            cvt(ffi::EVP_DigestSignFinal(
+                self.md_ctx,
+                buf.as_mut_ptr(),
+                &mut len,
+            ))?;
+            buf.truncate(len);
+            Ok(buf)
+        }
+    }
+
+    /// Determines if the data fed into the `Mac` matches the provided tag.
+    ///
+    /// The comparison is performed in constant time.
+    pub fn verify(&mut self, tag: &[u8]) -> Result<bool, ErrorStack> {
+        let actual = self.finish()?;
+        Ok(actual.len() == tag.len() && memcmp::eq(&actual, tag))
+    }
+}
+
+impl Write for Mac {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(algorithm: MacAlgorithm, key: &[u8], data: &[u8], expected: &str) {
+        let mut mac = Mac::new(algorithm, key).unwrap();
+        let (a, b) = data.split_at(data.len() / 2);
+        mac.update(a).unwrap();
+        mac.update(b).unwrap();
+        assert_eq!(hex::encode(mac.finish().unwrap()), expected);
+
+        let expected = hex::decode(expected).unwrap();
+        let mut mac = Mac::new(algorithm, key).unwrap();
+        mac.update(data).unwrap();
+        assert!(mac.verify(&expected).unwrap());
+        assert!(!mac.verify(&expected[1..]).unwrap());
+
+        let mut mac = Mac::new(algorithm, key).unwrap();
+        mac.update(&data[1..]).unwrap();
+        assert!(!mac.verify(&expected).unwrap());
+    }
+
+    // RFC 4231 test case 2
+    #[test]
+    fn hmac_sha256() {
+        check(
+            MacAlgorithm::hmac(MessageDigest::sha256()),
+            b"Jefe",
+            b"what do ya want for nothing?",
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        );
+    }
+
+    // RFC 4493 example 2
+    #[test]
+    #[cfg(ossl110)]
+    fn cmac_aes_128() {
+        check(
+            MacAlgorithm::cmac(Cipher::aes_128_cbc()),
+            &hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap(),
+            &hex::decode("6bc1bee22e409f96e93d7e117393172a").unwrap(),
+            "070a16b46b4d4144f79bdd9dd04a287c",
+        );
+    }
+
+    // RFC 7539 section 2.5.2
+    #[test]
+    #[cfg(ossl111)]
+    fn poly1305() {
+        check(
+            MacAlgorithm::poly1305(),
+            &hex::decode("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+                .unwrap(),
+            b"Cryptographic Forum Research Group",
+            "a8061dc1305136c6c22b8baf0c0127a9",
+        );
+    }
+
+    // 128 bit output vector from the SipHash reference implementation
+    #[test]
+    #[cfg(ossl111)]
+    fn siphash() {
+        let data = (0..15).collect::<Vec<u8>>();
+        check(
+            MacAlgorithm::siphash(),
+            &hex::decode("000102030405060708090a0b0c0d0e0f").unwrap(),
+            &data,
+            "5493e99933b0a8117e08ec0f97cfc3d9",
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! Message digest algorithms fetched from providers.
+//!
+//! Unlike [`MessageDigest`], which wraps the built-in implementations, an [`Md`] is fetched by
+//! name from the providers loaded in a library context, optionally restricted by a property query.
+//! It can be used with [`Hasher::with_md`], [`Signer::with_md`] and [`Verifier::with_md`].
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::hash::Hasher;
+//! use openssl::md::Md;
+//!
+//! let md = Md::fetch("SHA256", Some("provider=default")).unwrap();
+//! let mut hasher = Hasher::with_md(&md).unwrap();
+//! hasher.update(b"hello, world").unwrap();
+//! let digest = hasher.finish().unwrap();
+//! assert_eq!(digest.len(), md.size());
+//! ```
+//!
+//! [`MessageDigest`]: ../hash/struct.MessageDigest.html
+//! [`Md`]: struct.Md.html
+//! [`Hasher::with_md`]: ../hash/struct.Hasher.html#method.with_md
+//! [`Signer::with_md`]: ../sign/struct.Signer.html#method.with_md
+//! [`Verifier::with_md`]: ../sign/struct.Verifier.html#method.with_md
+use foreign_types::{ForeignType, ForeignTypeRef};
+use std::ffi::{CStr, CString};
+use std::ptr;
+use std::str;
+
+use crate::cvt_p;
+use crate::error::ErrorStack;
+use crate::lib_ctx::LibCtxRef;
+use crate::nid::Nid;
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::EVP_MD;
+    fn drop = ffi::EVP_MD_free;
+
+    /// A message digest algorithm fetched from a provider.
+    pub struct Md;
+    /// Reference to an [`Md`].
+    ///
+    /// [`Md`]: struct.Md.html
+    pub struct MdRef;
+}
+
+impl Clone for Md {
+    fn clone(&self) -> Md {
+        MdRef::to_owned(self)
+    }
+}
+
+impl ToOwned for MdRef {
+    type Owned = Md;
+
+    fn to_owned(&self) -> Md {
+        unsafe {
+            ffi::EVP_MD_up_ref(self.as_ptr());
+            Md::from_ptr(self.as_ptr())
+        }
+    }
+}
+
+impl Md {
+    /// Fetches a digest implementation by name from the default library context.
+    ///
+    /// `properties` is a property query such as `"fips=yes"` or `"provider=default"` restricting
+    /// which implementation is selected.
+    ///
+    /// This corresponds to [`EVP_MD_fetch`].
+    ///
+    /// [`EVP_MD_fetch`]: https://www.openssl.org/docs/man3.0/man3/EVP_MD_fetch.html
+    pub fn fetch(algorithm: &str, properties: Option<&str>) -> Result<Md, ErrorStack> {
+        unsafe { Md::fetch_raw(ptr::null_mut(), algorithm, properties) }
+    }
+
+    /// Like [`fetch`], but fetches the digest from the library context `ctx`.
+    ///
+    /// This corresponds to [`EVP_MD_fetch`].
+    ///
+    /// # Safety
+    ///
+    /// The returned `Md`, and anything created from it, must be dropped before `ctx`.
+    ///
+    /// [`fetch`]: #method.fetch
+    /// [`EVP_MD_fetch`]: https://www.openssl.org/docs/man3.0/man3/EVP_MD_fetch.html
+    pub unsafe fn fetch_in(
+        ctx: &LibCtxRef,
+        algorithm: &str,
+        properties: Option<&str>,
+    ) -> Result<Md, ErrorStack> {
+        Md::fetch_raw(ctx.as_ptr(), algorithm, properties)
+    }
+
+    unsafe fn fetch_raw(
+        ctx: *mut ffi::OSSL_LIB_CTX,
+        algorithm: &str,
+        properties: Option<&str>,
+    ) -> Result<Md, ErrorStack> {
+        let algorithm = CString::new(algorithm).unwrap();
+        let properties = properties.map(|s| CString::new(s).unwrap());
+        ffi::init();
+        let ptr = cvt_p(ffi::EVP_MD_fetch(
+            ctx,
+            algorithm.as_ptr(),
+            properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+        ))?;
+        Ok(Md::from_ptr(ptr))
+    }
+}
+
+impl MdRef {
+    /// Returns the name of the digest.
+    ///
+    /// This corresponds to [`EVP_MD_get0_name`].
+    ///
+    /// [`EVP_MD_get0_name`]: https://www.openssl.org/docs/man3.0/man3/EVP_MD_get0_name.html
+    pub fn name(&self) -> &str {
+        unsafe {
+            let name = ffi::EVP_MD_get0_name(self.as_ptr());
+            str::from_utf8(CStr::from_ptr(name).to_bytes()).unwrap()
+        }
+    }
+
+    /// Returns the size of the digest in bytes.
+    pub fn size(&self) -> usize {
+        unsafe { ffi::EVP_MD_size(self.as_ptr()) as usize }
+    }
+
+    /// Returns the block size of the digest in bytes.
+    pub fn block_size(&self) -> usize {
+        unsafe { ffi::EVP_MD_block_size(self.as_ptr()) as usize }
+    }
+
+    /// Returns the `Nid` of the digest.
+    pub fn type_(&self) -> Nid {
+        unsafe { Nid::from_raw(ffi::EVP_MD_type(self.as_ptr())) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fetch() {
+        let md = Md::fetch("SHA2-256", None).unwrap();
+        assert_eq!(md.name(), "SHA2-256");
+        assert_eq!(md.size(), 32);
+        assert_eq!(md.block_size(), 64);
+        assert_eq!(md.type_(), Nid::SHA256);
+
+        let md = md.clone();
+        assert_eq!(md.size(), 32);
+
+        Md::fetch("SHA256", Some("provider=default")).unwrap();
+        assert!(Md::fetch("SHA256", Some("provider=nonexistent")).is_err());
+        assert!(Md::fetch("NOT-A-DIGEST", None).is_err());
+    }
+}
@@ -1,14 +1,18 @@
 use bitflags::bitflags;
-use foreign_types::ForeignTypeRef;
+use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::{c_int, c_long, c_ulong};
 use std::mem;
 use std::ptr;
 
-use crate::asn1::Asn1GeneralizedTimeRef;
+use crate::asn1::{
+    Asn1GeneralizedTimeRef, Asn1IntegerRef, Asn1ObjectRef, Asn1OctetString, Asn1OctetStringRef,
+    Asn1TimeRef,
+};
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
+use crate::pkey::{HasPrivate, PKeyRef};
 use crate::stack::StackRef;
-use crate::util::ForeignTypeRefExt;
+use crate::util::{ForeignTypeExt, ForeignTypeRefExt};
 use crate::x509::store::X509StoreRef;
 use crate::x509::{X509Ref, X509};
 use crate::{cvt, cvt_p};
@@ -140,6 +144,96 @@ impl<'a> OcspStatus<'a> {
     }
 }
 
+/// A builder used to construct an `OcspBasicResponse`.
+pub struct OcspBasicResponseBuilder(OcspBasicResponse);
+
+impl OcspBasicResponseBuilder {
+    /// Creates a new builder.
+    pub fn new() -> Result<OcspBasicResponseBuilder, ErrorStack> {
+        unsafe {
+            ffi::init();
+
+            cvt_p(ffi::OCSP_BASICRESP_new()).map(|p| OcspBasicResponseBuilder(OcspBasicResponse(p)))
+        }
+    }
+
+    /// Adds the status of the certificate identified by `id` to the response.
+    ///
+    /// `reason` and `revocation_time` are only used if `status` is `REVOKED`.
+    ///
+    /// This corresponds to [`OCSP_basic_add1_status`].
+    ///
+    /// [`OCSP_basic_add1_status`]: https://www.openssl.org/docs/man1.1.0/crypto/OCSP_basic_add1_status.html
+    pub fn add_status(
+        &mut self,
+        id: &OcspCertIdRef,
+        status: OcspCertStatus,
+        reason: OcspRevokedStatus,
+        revocation_time: Option<&Asn1TimeRef>,
+        this_update: &Asn1TimeRef,
+        next_update: Option<&Asn1TimeRef>,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt_p(ffi::OCSP_basic_add1_status(
+                self.0.as_ptr(),
+                id.as_ptr(),
+                status.as_raw(),
+                reason.as_raw(),
+                revocation_time.map_or(ptr::null_mut(), Asn1TimeRef::as_ptr),
+                this_update.as_ptr(),
+                next_update.map_or(ptr::null_mut(), Asn1TimeRef::as_ptr),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Copies the nonce extension from `request` into the response, if the request has one.
+    ///
+    /// This corresponds to [`OCSP_copy_nonce`].
+    ///
+    /// [`OCSP_copy_nonce`]: https://www.openssl.org/docs/man1.1.0/crypto/OCSP_copy_nonce.html
+    pub fn copy_nonce(&mut self, request: &OcspRequestRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OCSP_copy_nonce(self.0.as_ptr(), request.as_ptr())).map(|_| ()) }
+    }
+
+    /// Signs the response with the responder's certificate and private key.
+    ///
+    /// `certs` contains additional certificates to include in the response, such as the chain of
+    /// the responder certificate.
+    ///
+    /// This corresponds to [`OCSP_basic_sign`].
+    ///
+    /// [`OCSP_basic_sign`]: https://www.openssl.org/docs/man1.1.0/crypto/OCSP_basic_sign.html
+    pub fn sign<T>(
+        &mut self,
+        signer: &X509Ref,
+        key: &PKeyRef<T>,
+        hash: MessageDigest,
+        certs: Option<&StackRef<X509>>,
+        flags: OcspFlag,
+    ) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            cvt(ffi::OCSP_basic_sign(
+                self.0.as_ptr(),
+                signer.as_ptr(),
+                key.as_ptr(),
+                hash.as_ptr(),
+                certs.map_or(ptr::null_mut(), StackRef::as_ptr),
+                flags.bits(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Consumes the builder, returning the basic response.
+    pub fn build(self) -> OcspBasicResponse {
+        self.0
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::OCSP_BASICRESP;
     fn drop = ffi::OCSP_BASICRESP_free;
@@ -148,6 +242,13 @@ foreign_type_and_impl_send_sync! {
     pub struct OcspBasicResponseRef;
 }
 
+impl OcspBasicResponse {
+    /// Returns a new builder.
+    pub fn builder() -> Result<OcspBasicResponseBuilder, ErrorStack> {
+        OcspBasicResponseBuilder::new()
+    }
+}
+
 impl OcspBasicResponseRef {
     /// Verifies the validity of the response.
     ///
@@ -193,7 +294,7 @@ impl OcspBasicResponseRef {
 
                 Some(OcspStatus {
                     status: OcspCertStatus(status),
-                    reason: OcspRevokedStatus(status),
+                    reason: OcspRevokedStatus(reason),
                     revocation_time,
                     this_update: Asn1GeneralizedTimeRef::from_ptr(this_update),
                     next_update: Asn1GeneralizedTimeRef::from_ptr(next_update),
@@ -231,6 +332,72 @@ impl OcspCertId {
     }
 }
 
+impl OcspCertIdRef {
+    /// Returns the serial number of the certificate.
+    pub fn serial_number(&self) -> &Asn1IntegerRef {
+        unsafe {
+            let mut serial = ptr::null_mut();
+            ffi::OCSP_id_get0_info(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut serial,
+                self.as_ptr(),
+            );
+            Asn1IntegerRef::from_const_ptr_opt(serial).expect("serial number must not be null")
+        }
+    }
+
+    /// Returns the OID of the hash algorithm used to compute the issuer name and key hashes.
+    pub fn hash_algorithm(&self) -> &Asn1ObjectRef {
+        unsafe {
+            let mut md = ptr::null_mut();
+            ffi::OCSP_id_get0_info(
+                ptr::null_mut(),
+                &mut md,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                self.as_ptr(),
+            );
+            Asn1ObjectRef::from_const_ptr_opt(md).expect("hash algorithm must not be null")
+        }
+    }
+
+    /// Returns the hash of the issuer's distinguished name.
+    pub fn issuer_name_hash(&self) -> &[u8] {
+        unsafe {
+            let mut hash = ptr::null_mut();
+            ffi::OCSP_id_get0_info(
+                &mut hash,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                self.as_ptr(),
+            );
+            Asn1OctetStringRef::from_const_ptr_opt(hash)
+                .expect("issuer name hash must not be null")
+                .as_slice()
+        }
+    }
+
+    /// Returns the hash of the issuer's public key.
+    pub fn issuer_key_hash(&self) -> &[u8] {
+        unsafe {
+            let mut hash = ptr::null_mut();
+            ffi::OCSP_id_get0_info(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut hash,
+                ptr::null_mut(),
+                self.as_ptr(),
+            );
+            Asn1OctetStringRef::from_const_ptr_opt(hash)
+                .expect("issuer key hash must not be null")
+                .as_slice()
+        }
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::OCSP_RESPONSE;
     fn drop = ffi::OCSP_RESPONSE_free;
@@ -341,6 +508,77 @@ impl OcspRequestRef {
             Ok(OcspOneReqRef::from_ptr_mut(ptr))
         }
     }
+
+    /// Returns an iterator over the single certificate requests contained in the request.
+    ///
+    /// This corresponds to [`OCSP_request_onereq_get0`].
+    ///
+    /// [`OCSP_request_onereq_get0`]: https://www.openssl.org/docs/man1.1.0/crypto/OCSP_request_onereq_get0.html
+    pub fn one_requests(&self) -> OcspOneReqs<'_> {
+        OcspOneReqs {
+            request: self,
+            idx: 0,
+        }
+    }
+
+    /// Adds a nonce extension to the request.
+    ///
+    /// If `nonce` is `None`, a random 16 byte nonce is generated.
+    ///
+    /// This corresponds to [`OCSP_request_add1_nonce`].
+    ///
+    /// [`OCSP_request_add1_nonce`]: https://www.openssl.org/docs/man1.1.0/crypto/OCSP_request_add1_nonce.html
+    pub fn add_nonce(&mut self, nonce: Option<&[u8]>) -> Result<(), ErrorStack> {
+        unsafe {
+            let (ptr, len) = match nonce {
+                Some(nonce) => {
+                    assert!(nonce.len() <= c_int::max_value() as usize);
+                    (nonce.as_ptr() as *mut _, nonce.len() as c_int)
+                }
+                None => (ptr::null_mut(), 0),
+            };
+            cvt(ffi::OCSP_request_add1_nonce(self.as_ptr(), ptr, len)).map(|_| ())
+        }
+    }
+
+    /// Returns the contents of the request's nonce extension, if it has one.
+    ///
+    /// This corresponds to [`OCSP_REQUEST_get1_ext_d2i`] called with `NID_id_pkix_OCSP_Nonce`.
+    ///
+    /// [`OCSP_REQUEST_get1_ext_d2i`]: https://www.openssl.org/docs/man1.1.0/crypto/OCSP_REQUEST_get1_ext_d2i.html
+    pub fn nonce(&self) -> Option<Asn1OctetString> {
+        unsafe {
+            let nonce = ffi::OCSP_REQUEST_get1_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_id_pkix_OCSP_Nonce,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Asn1OctetString::from_ptr_opt(nonce as *mut _)
+        }
+    }
+}
+
+/// An iterator over the single certificate requests of an `OcspRequest`.
+pub struct OcspOneReqs<'a> {
+    request: &'a OcspRequestRef,
+    idx: c_int,
+}
+
+impl<'a> Iterator for OcspOneReqs<'a> {
+    type Item = &'a OcspOneReqRef;
+
+    fn next(&mut self) -> Option<&'a OcspOneReqRef> {
+        unsafe {
+            if self.idx >= ffi::OCSP_request_onereq_count(self.request.as_ptr()) {
+                return None;
+            }
+
+            let one = ffi::OCSP_request_onereq_get0(self.request.as_ptr(), self.idx);
+            self.idx += 1;
+            Some(OcspOneReqRef::from_const_ptr(one))
+        }
+    }
 }
 
 foreign_type_and_impl_send_sync! {
@@ -350,3 +588,88 @@ foreign_type_and_impl_send_sync! {
     pub struct OcspOneReq;
     pub struct OcspOneReqRef;
 }
+
+impl OcspOneReqRef {
+    /// Returns the ID of the certificate whose status is requested.
+    pub fn cert_id(&self) -> &OcspCertIdRef {
+        unsafe { OcspCertIdRef::from_const_ptr(ffi::OCSP_onereq_get0_id(self.as_ptr())) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::asn1::Asn1Time;
+    use crate::pkey::PKey;
+    use crate::stack::Stack;
+    use crate::x509::store::X509StoreBuilder;
+
+    #[test]
+    fn responder_round_trip() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let ca = X509::from_pem(include_bytes!("../test/root-ca.pem")).unwrap();
+        let ca_key = PKey::private_key_from_pem(include_bytes!("../test/root-ca.key")).unwrap();
+
+        let mut req = OcspRequest::new().unwrap();
+        let id = OcspCertId::from_cert(MessageDigest::sha1(), &cert, &ca).unwrap();
+        req.add_id(id).unwrap();
+        req.add_nonce(Some(b"0123456789abcdef")).unwrap();
+        let req = OcspRequest::from_der(&req.to_der().unwrap()).unwrap();
+
+        assert!(req.nonce().is_some());
+        let mut ids = req.one_requests().map(|one| one.cert_id());
+        let id = ids.next().unwrap();
+        assert!(ids.next().is_none());
+        assert_eq!(
+            id.serial_number().to_bn().unwrap(),
+            cert.serial_number().to_bn().unwrap()
+        );
+        assert_eq!(id.hash_algorithm().nid(), crate::nid::Nid::SHA1);
+        assert_eq!(id.issuer_name_hash().len(), 20);
+        assert_eq!(id.issuer_key_hash().len(), 20);
+
+        let now = Asn1Time::days_from_now(0).unwrap();
+        let next = Asn1Time::days_from_now(1).unwrap();
+        let mut basic = OcspBasicResponse::builder().unwrap();
+        basic
+            .add_status(
+                id,
+                OcspCertStatus::REVOKED,
+                OcspRevokedStatus::KEY_COMPROMISE,
+                Some(&now),
+                &now,
+                Some(&next),
+            )
+            .unwrap();
+        basic.copy_nonce(&req).unwrap();
+        basic
+            .sign(
+                &ca,
+                &ca_key,
+                MessageDigest::sha256(),
+                None,
+                OcspFlag::empty(),
+            )
+            .unwrap();
+        let basic = basic.build();
+        let response = OcspResponse::create(OcspResponseStatus::SUCCESSFUL, Some(&basic)).unwrap();
+
+        let response = OcspResponse::from_der(&response.to_der().unwrap()).unwrap();
+        assert_eq!(response.status(), OcspResponseStatus::SUCCESSFUL);
+        let basic = response.basic().unwrap();
+
+        let mut certs = Stack::new().unwrap();
+        certs.push(ca.clone()).unwrap();
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(&ca).unwrap();
+        let store = store.build();
+        basic.verify(&certs, &store, OcspFlag::NO_VERIFY).unwrap();
+
+        let id = OcspCertId::from_cert(MessageDigest::sha1(), &cert, &ca).unwrap();
+        let status = basic.find_status(&id).unwrap();
+        assert_eq!(status.status, OcspCertStatus::REVOKED);
+        assert_eq!(status.reason, OcspRevokedStatus::KEY_COMPROMISE);
+        assert!(status.revocation_time.is_some());
+        status.check_validity(300, None).unwrap();
+    }
+}
@@ -5,11 +5,25 @@ use libc::c_int;
 use std::ffi::CString;
 use std::ptr;
 
+#[cfg(ossl300)]
+use crate::asn1::Asn1Object;
+#[cfg(ossl110)]
+use crate::asn1::Asn1StringRef;
 use crate::error::ErrorStack;
+#[cfg(ossl110)]
+use crate::hash::MessageDigest;
 use crate::nid::Nid;
+#[cfg(ossl110)]
+use crate::pkcs7::Pkcs7;
 use crate::pkey::{HasPrivate, PKey, PKeyRef, Private};
 use crate::stack::Stack;
+#[cfg(ossl110)]
+use crate::stack::StackRef;
+#[cfg(ossl110)]
+use crate::string::OpensslString;
 use crate::util::ForeignTypeExt;
+#[cfg(ossl110)]
+use crate::util::ForeignTypeRefExt;
 use crate::x509::{X509Ref, X509};
 use crate::{cvt, cvt_p};
 
@@ -21,6 +35,34 @@ foreign_type_and_impl_send_sync! {
     pub struct Pkcs12Ref;
 }
 
+#[cfg(ossl110)]
+mod bag {
+    use crate::stack::Stackable;
+
+    foreign_type_and_impl_send_sync! {
+        type CType = ffi::PKCS12_SAFEBAG;
+        fn drop = ffi::PKCS12_SAFEBAG_free;
+
+        pub struct SafeBag;
+        pub struct SafeBagRef;
+    }
+
+    impl Stackable for SafeBag {
+        type StackType = ffi::stack_st_PKCS12_SAFEBAG;
+    }
+}
+
+#[cfg(ossl110)]
+use self::bag::SafeBag;
+
+/// The OID of Oracle's trusted key usage attribute, which Java requires on trusted certificates.
+#[cfg(ossl300)]
+const ORACLE_TRUSTED_KEY_USAGE: &str = "2.16.840.1.113894.746875.1.1";
+
+/// The OID of the `anyExtendedKeyUsage` key purpose.
+#[cfg(ossl300)]
+const ANY_EXTENDED_KEY_USAGE: &str = "2.5.29.37.0";
+
 impl Pkcs12Ref {
     to_der! {
         /// Serializes the `Pkcs12` to its standard DER encoding.
@@ -57,6 +99,166 @@ impl Pkcs12Ref {
             Ok(ParsedPkcs12 { pkey, cert, chain })
         }
     }
+
+    /// Extracts the contents of the `Pkcs12`, allowing the key and certificate to be absent.
+    ///
+    /// Archives without a MAC, such as those used as Java truststores, are accepted.
+    #[cfg(ossl110)]
+    pub fn parse2(&self, pass: &str) -> Result<ParsedPkcs12_2, ErrorStack> {
+        let mut pkey = None;
+        let mut key_id = None;
+        let mut certs = vec![];
+        for bag in self.bags(pass)? {
+            match bag.content {
+                Pkcs12BagContent::Key(key) if pkey.is_none() => {
+                    pkey = Some(key);
+                    key_id = bag.local_key_id;
+                }
+                Pkcs12BagContent::Cert(cert) => certs.push((cert, bag.local_key_id)),
+                _ => {}
+            }
+        }
+
+        // Like `PKCS12_parse`, the end-entity certificate is the one sharing the key's local key
+        // ID, falling back to the first certificate whose public key matches.
+        let position = match &pkey {
+            Some(pkey) => certs
+                .iter()
+                .position(|(_, id)| key_id.is_some() && *id == key_id)
+                .or_else(|| {
+                    certs.iter().position(|(cert, _)| {
+                        cert.public_key()
+                            .map(|key| key.public_eq(pkey))
+                            .unwrap_or(false)
+                    })
+                }),
+            None => None,
+        };
+        let cert = position.map(|i| certs.remove(i).0);
+
+        let ca = if certs.is_empty() {
+            None
+        } else {
+            let mut ca = Stack::new()?;
+            for (cert, _) in certs {
+                ca.push(cert)?;
+            }
+            Some(ca)
+        };
+
+        Ok(ParsedPkcs12_2 { pkey, cert, ca })
+    }
+
+    /// Returns every safe bag in the `Pkcs12`, in archive order.
+    ///
+    /// The MAC is verified if present, and encrypted contents and shrouded keys are decrypted
+    /// with `pass`. Nested safe contents bags are flattened.
+    #[cfg(ossl110)]
+    pub fn bags(&self, pass: &str) -> Result<Vec<Pkcs12Bag>, ErrorStack> {
+        unsafe {
+            let pass = CString::new(pass.as_bytes()).unwrap();
+
+            if self.mac_present() && ffi::PKCS12_verify_mac(self.as_ptr(), pass.as_ptr(), -1) != 1 {
+                return Err(ErrorStack::internal(
+                    ffi::ERR_LIB_PKCS12,
+                    ffi::PKCS12_R_MAC_VERIFY_FAILURE,
+                    "mac verify failure",
+                ));
+            }
+
+            let safes = cvt_p(ffi::PKCS12_unpack_authsafes(self.as_ptr()))?;
+            let safes = Stack::<Pkcs7>::from_ptr(safes);
+
+            let mut bags = vec![];
+            for safe in &safes {
+                let contents = match ffi::OBJ_obj2nid((*safe.as_ptr()).type_) {
+                    ffi::NID_pkcs7_data => ffi::PKCS12_unpack_p7data(safe.as_ptr()),
+                    ffi::NID_pkcs7_encrypted => {
+                        ffi::PKCS12_unpack_p7encdata(safe.as_ptr(), pass.as_ptr(), -1)
+                    }
+                    _ => {
+                        return Err(ErrorStack::internal(
+                            ffi::ERR_LIB_PKCS12,
+                            ffi::PKCS12_R_CONTENT_TYPE_NOT_DATA,
+                            "unsupported content type",
+                        ))
+                    }
+                };
+                let contents = cvt_p(contents)?;
+                let contents = Stack::<SafeBag>::from_ptr(contents);
+                collect_bags(&contents, &pass, &mut bags)?;
+            }
+
+            Ok(bags)
+        }
+    }
+
+    /// Determines if the `Pkcs12` is integrity protected by a MAC.
+    ///
+    /// This corresponds to [`PKCS12_mac_present`].
+    ///
+    /// [`PKCS12_mac_present`]: https://www.openssl.org/docs/manmaster/man3/PKCS12_mac_present.html
+    #[cfg(ossl110)]
+    pub fn mac_present(&self) -> bool {
+        unsafe { ffi::PKCS12_mac_present(self.as_ptr()) == 1 }
+    }
+}
+
+#[cfg(ossl110)]
+unsafe fn collect_bags(
+    bags: &StackRef<SafeBag>,
+    pass: &CString,
+    out: &mut Vec<Pkcs12Bag>,
+) -> Result<(), ErrorStack> {
+    for bag in bags {
+        let bag = bag.as_ptr();
+
+        let content = match ffi::PKCS12_SAFEBAG_get_nid(bag) {
+            ffi::NID_keyBag => {
+                let pkey = cvt_p(ffi::EVP_PKCS82PKEY(ffi::PKCS12_SAFEBAG_get0_p8inf(bag)))?;
+                Pkcs12BagContent::Key(PKey::from_ptr(pkey))
+            }
+            ffi::NID_pkcs8ShroudedKeyBag => {
+                let p8 = cvt_p(ffi::PKCS12_decrypt_skey(bag, pass.as_ptr(), -1))?;
+                let pkey = ffi::EVP_PKCS82PKEY(p8);
+                ffi::PKCS8_PRIV_KEY_INFO_free(p8);
+                Pkcs12BagContent::Key(PKey::from_ptr(cvt_p(pkey)?))
+            }
+            ffi::NID_certBag => {
+                let cert = cvt_p(ffi::PKCS12_SAFEBAG_get1_cert(bag))?;
+                Pkcs12BagContent::Cert(X509::from_ptr(cert))
+            }
+            ffi::NID_safeContentsBag => {
+                let safes = StackRef::from_const_ptr(ffi::PKCS12_SAFEBAG_get0_safes(bag));
+                collect_bags(safes, pass, out)?;
+                continue;
+            }
+            nid => Pkcs12BagContent::Other(Nid::from_raw(nid)),
+        };
+
+        let friendly_name =
+            OpensslString::from_ptr_opt(ffi::PKCS12_get_friendlyname(bag)).map(|s| s.to_string());
+
+        let attrs = ffi::PKCS12_SAFEBAG_get0_attrs(bag);
+        let loc = ffi::X509at_get_attr_by_NID(attrs, ffi::NID_localKeyID, -1);
+        let local_key_id = if loc < 0 {
+            None
+        } else {
+            let attr = ffi::X509at_get_attr(attrs, loc);
+            let id =
+                ffi::X509_ATTRIBUTE_get0_data(attr, 0, ffi::V_ASN1_OCTET_STRING, ptr::null_mut());
+            Asn1StringRef::from_const_ptr_opt(id as *const ffi::ASN1_STRING)
+                .map(|id| id.as_slice().to_vec())
+        };
+
+        out.push(Pkcs12Bag {
+            friendly_name,
+            local_key_id,
+            content,
+        });
+    }
+
+    Ok(())
 }
 
 impl Pkcs12 {
@@ -88,6 +290,18 @@ impl Pkcs12 {
             iter: ffi::PKCS12_DEFAULT_ITER,
             mac_iter: ffi::PKCS12_DEFAULT_ITER,
             ca: None,
+            #[cfg(ossl110)]
+            name: None,
+            #[cfg(ossl110)]
+            pkey: None,
+            #[cfg(ossl110)]
+            cert: None,
+            #[cfg(ossl110)]
+            bags: vec![],
+            #[cfg(ossl110)]
+            mac_md: None,
+            #[cfg(ossl110)]
+            mac: true,
         }
     }
 }
@@ -99,22 +313,87 @@ pub struct ParsedPkcs12 {
     pub chain: Option<Stack<X509>>,
 }
 
+/// The contents of a PKCS #12 archive, returned by [`Pkcs12Ref::parse2`].
+#[cfg(ossl110)]
+#[derive(Debug)]
+pub struct ParsedPkcs12_2 {
+    pub pkey: Option<PKey<Private>>,
+    pub cert: Option<X509>,
+    pub ca: Option<Stack<X509>>,
+}
+
+/// A safe bag of a PKCS #12 archive, returned by [`Pkcs12Ref::bags`].
+#[cfg(ossl110)]
+#[derive(Debug)]
+pub struct Pkcs12Bag {
+    /// The `friendlyName` attribute of the bag, used by Java as the keystore alias.
+    pub friendly_name: Option<String>,
+    /// The `localKeyId` attribute of the bag, linking a key to its certificate.
+    pub local_key_id: Option<Vec<u8>>,
+    pub content: Pkcs12BagContent,
+}
+
+/// The value held by a [`Pkcs12Bag`].
+#[cfg(ossl110)]
+#[derive(Debug)]
+pub enum Pkcs12BagContent {
+    /// A private key, from either a plain or a shrouded key bag.
+    Key(PKey<Private>),
+    /// An X.509 certificate.
+    Cert(X509),
+    /// A bag of an unsupported type, such as a CRL or secret bag, identified by its bag type.
+    Other(Nid),
+}
+
 pub struct Pkcs12Builder {
     nid_key: Nid,
     nid_cert: Nid,
     iter: c_int,
     mac_iter: c_int,
     ca: Option<Stack<X509>>,
+    #[cfg(ossl110)]
+    name: Option<String>,
+    #[cfg(ossl110)]
+    pkey: Option<PKey<Private>>,
+    #[cfg(ossl110)]
+    cert: Option<X509>,
+    #[cfg(ossl110)]
+    bags: Vec<BagEntry>,
+    #[cfg(ossl110)]
+    mac_md: Option<MessageDigest>,
+    #[cfg(ossl110)]
+    mac: bool,
+}
+
+#[cfg(ossl110)]
+struct BagEntry {
+    content: BagContent,
+    friendly_name: Option<String>,
+    local_key_id: Option<Vec<u8>>,
+}
+
+#[cfg(ossl110)]
+enum BagContent {
+    Key(PKey<Private>),
+    Cert(X509),
+    #[cfg(ossl300)]
+    TrustedCert(X509),
 }
 
 impl Pkcs12Builder {
     /// The encryption algorithm that should be used for the key
+    ///
+    /// Passing a cipher such as `Nid::AES_256_CBC` rather than a PKCS #12 PBE algorithm selects
+    /// PBES2 encryption.
     pub fn key_algorithm(&mut self, nid: Nid) -> &mut Self {
         self.nid_key = nid;
         self
     }
 
     /// The encryption algorithm that should be used for the cert
+    ///
+    /// Passing a cipher such as `Nid::AES_256_CBC` rather than a PKCS #12 PBE algorithm selects
+    /// PBES2 encryption.
     pub fn cert_algorithm(&mut self, nid: Nid) -> &mut Self {
         self.nid_cert = nid;
         self
@@ -142,6 +421,113 @@ impl Pkcs12Builder {
         self
     }
 
+    /// The friendly name of the key and certificate provided to `build2`.
+    #[cfg(ossl110)]
+    pub fn name(&mut self, name: &str) -> &mut Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// The private key stored by `build2`.
+    #[cfg(ossl110)]
+    pub fn pkey<T>(&mut self, pkey: &PKeyRef<T>) -> &mut Self
+    where
+        T: HasPrivate,
+    {
+        self.pkey = Some(to_private(pkey));
+        self
+    }
+
+    /// The certificate stored by `build2`.
+    ///
+    /// If a key is also set, the two are linked by a local key ID derived from the certificate.
+    #[cfg(ossl110)]
+    pub fn cert(&mut self, cert: &X509Ref) -> &mut Self {
+        self.cert = Some(cert.to_owned());
+        self
+    }
+
+    /// Adds a further private key to the archive built by `build2`.
+    ///
+    /// A key is paired with its certificate by giving both the same local key ID.
+    #[cfg(ossl110)]
+    pub fn add_key<T>(
+        &mut self,
+        pkey: &PKeyRef<T>,
+        friendly_name: Option<&str>,
+        local_key_id: Option<&[u8]>,
+    ) -> &mut Self
+    where
+        T: HasPrivate,
+    {
+        self.push_bag(
+            BagContent::Key(to_private(pkey)),
+            friendly_name,
+            local_key_id,
+        )
+    }
+
+    /// Adds a further certificate to the archive built by `build2`.
+    #[cfg(ossl110)]
+    pub fn add_cert(
+        &mut self,
+        cert: &X509Ref,
+        friendly_name: Option<&str>,
+        local_key_id: Option<&[u8]>,
+    ) -> &mut Self {
+        self.push_bag(
+            BagContent::Cert(cert.to_owned()),
+            friendly_name,
+            local_key_id,
+        )
+    }
+
+    /// Adds a trusted certificate to the archive built by `build2`.
+    ///
+    /// Java only treats a certificate without a private key as a trusted entry if it carries
+    /// Oracle's trusted key usage attribute, which this sets to `anyExtendedKeyUsage`.
+    #[cfg(ossl300)]
+    pub fn add_trusted_cert(&mut self, cert: &X509Ref, friendly_name: Option<&str>) -> &mut Self {
+        self.push_bag(
+            BagContent::TrustedCert(cert.to_owned()),
+            friendly_name,
+            None,
+        )
+    }
+
+    /// The digest used to compute the MAC.
+    ///
+    /// `build2` defaults to SHA-256, while `build` uses OpenSSL's default unless this is set.
+    #[cfg(ossl110)]
+    pub fn mac_md(&mut self, md: MessageDigest) -> &mut Self {
+        self.mac_md = Some(md);
+        self
+    }
+
+    /// Omits the MAC from the archive built by `build2`.
+    ///
+    /// Java truststores are commonly stored this way, with only the keys protected by password.
+    #[cfg(ossl110)]
+    pub fn no_mac(&mut self) -> &mut Self {
+        self.mac = false;
+        self
+    }
+
+    #[cfg(ossl110)]
+    fn push_bag(
+        &mut self,
+        content: BagContent,
+        friendly_name: Option<&str>,
+        local_key_id: Option<&[u8]>,
+    ) -> &mut Self {
+        self.bags.push(BagEntry {
+            content,
+            friendly_name: friendly_name.map(str::to_string),
+            local_key_id: local_key_id.map(<[u8]>::to_vec),
+        });
+        self
+    }
+
     /// Builds the PKCS #12 object
     ///
     /// # Arguments
@@ -178,7 +564,7 @@ impl Pkcs12Builder {
             // https://www.openssl.org/docs/man1.0.2/crypto/PKCS12_create.html
             let keytype = 0;
 
-            cvt_p(ffi::PKCS12_create(
+            let p12 = cvt_p(ffi::PKCS12_create(
                 pass.as_ptr() as *const _ as *mut _,
                 friendly_name.as_ptr() as *const _ as *mut _,
                 pkey,
@@ -190,9 +576,182 @@ impl Pkcs12Builder {
                 self.mac_iter,
                 keytype,
             ))
-            .map(Pkcs12)
+            .map(Pkcs12)?;
+
+            // PKCS12_create always uses the default digest, so replace its MAC
+            #[cfg(ossl110)]
+            {
+                if let Some(md) = self.mac_md.filter(|_| p12.mac_present()) {
+                    cvt(ffi::PKCS12_set_mac(
+                        p12.as_ptr(),
+                        pass.as_ptr(),
+                        -1,
+                        ptr::null_mut(),
+                        0,
+                        self.mac_iter,
+                        md.as_ptr(),
+                    ))?;
+                }
+            }
+
+            Ok(p12)
+        }
+    }
+
+    /// Builds the PKCS #12 object from the key, certificates and bags added to the builder.
+    ///
+    /// Unlike `build`, neither a key nor a certificate is required, so this can produce
+    /// certificate-only truststores.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - the password used to encrypt the keys and certificates, and for the MAC
+    #[cfg(ossl110)]
+    pub fn build2(&self, password: &str) -> Result<Pkcs12, ErrorStack> {
+        unsafe {
+            let pass = CString::new(password).unwrap();
+
+            let mut entries = vec![];
+            let local_key_id = match (&self.pkey, &self.cert) {
+                (Some(_), Some(cert)) => Some(cert.digest(MessageDigest::sha1())?.to_vec()),
+                _ => None,
+            };
+            if let Some(pkey) = &self.pkey {
+                entries.push(BagEntry {
+                    content: BagContent::Key(pkey.clone()),
+                    friendly_name: self.name.clone(),
+                    local_key_id: local_key_id.clone(),
+                });
+            }
+            if let Some(cert) = &self.cert {
+                entries.push(BagEntry {
+                    content: BagContent::Cert(cert.clone()),
+                    friendly_name: self.name.clone(),
+                    local_key_id,
+                });
+            }
+            if let Some(ca) = &self.ca {
+                for cert in ca {
+                    entries.push(BagEntry {
+                        content: BagContent::Cert(cert.to_owned()),
+                        friendly_name: None,
+                        local_key_id: None,
+                    });
+                }
+            }
+
+            let cert_bags = Stack::<SafeBag>::new()?;
+            let key_bags = Stack::<SafeBag>::new()?;
+            for entry in entries.iter().chain(&self.bags) {
+                let bag = match &entry.content {
+                    BagContent::Key(pkey) => cvt_p(ffi::PKCS12_add_key(
+                        &mut key_bags.as_ptr(),
+                        pkey.as_ptr(),
+                        0,
+                        self.iter,
+                        self.key_nid().as_raw(),
+                        pass.as_ptr(),
+                    ))?,
+                    BagContent::Cert(cert) => {
+                        cvt_p(ffi::PKCS12_add_cert(&mut cert_bags.as_ptr(), cert.as_ptr()))?
+                    }
+                    #[cfg(ossl300)]
+                    BagContent::TrustedCert(cert) => {
+                        let bag =
+                            cvt_p(ffi::PKCS12_add_cert(&mut cert_bags.as_ptr(), cert.as_ptr()))?;
+                        let attr = CString::new(ORACLE_TRUSTED_KEY_USAGE).unwrap();
+                        let usage = Asn1Object::from_str(ANY_EXTENDED_KEY_USAGE)?;
+                        cvt(ffi::PKCS12_add1_attr_by_txt(
+                            bag,
+                            attr.as_ptr(),
+                            ffi::V_ASN1_OBJECT,
+                            usage.as_ptr() as *const _,
+                            -1,
+                        ))?;
+                        bag
+                    }
+                };
+
+                if let Some(name) = &entry.friendly_name {
+                    let name = CString::new(name.as_str()).unwrap();
+                    cvt(ffi::PKCS12_add_friendlyname_utf8(bag, name.as_ptr(), -1))?;
+                }
+                if let Some(id) = &entry.local_key_id {
+                    cvt(ffi::PKCS12_add_localkeyid(
+                        bag,
+                        id.as_ptr() as *mut _,
+                        id.len() as c_int,
+                    ))?;
+                }
+            }
+
+            let safes = Stack::<Pkcs7>::new()?;
+            if !cert_bags.is_empty() {
+                cvt(ffi::PKCS12_add_safe(
+                    &mut safes.as_ptr(),
+                    cert_bags.as_ptr(),
+                    self.cert_nid().as_raw(),
+                    self.iter,
+                    pass.as_ptr(),
+                ))?;
+            }
+            if !key_bags.is_empty() {
+                // The keys are already shrouded, so their safe needs no further encryption.
+                cvt(ffi::PKCS12_add_safe(
+                    &mut safes.as_ptr(),
+                    key_bags.as_ptr(),
+                    -1,
+                    0,
+                    ptr::null(),
+                ))?;
+            }
+
+            let p12 = Pkcs12::from_ptr(cvt_p(ffi::PKCS12_add_safes(safes.as_ptr(), 0))?);
+
+            if self.mac {
+                let md = self.mac_md.unwrap_or_else(MessageDigest::sha256);
+                cvt(ffi::PKCS12_set_mac(
+                    p12.as_ptr(),
+                    pass.as_ptr(),
+                    -1,
+                    ptr::null_mut(),
+                    0,
+                    self.mac_iter,
+                    md.as_ptr(),
+                ))?;
+            }
+
+            Ok(p12)
+        }
+    }
+
+    // `PKCS12_create` substitutes its defaults for `NID_undef`, but the lower level functions used
+    // by `build2` do not.
+    #[cfg(ossl110)]
+    fn key_nid(&self) -> Nid {
+        match self.nid_key {
+            Nid::UNDEF if cfg!(ossl300) => Nid::AES_256_CBC,
+            Nid::UNDEF => Nid::PBE_WITHSHA1AND3_KEY_TRIPLEDES_CBC,
+            nid => nid,
         }
     }
+
+    #[cfg(ossl110)]
+    fn cert_nid(&self) -> Nid {
+        match self.nid_cert {
+            Nid::UNDEF if cfg!(ossl300) => Nid::AES_256_CBC,
+            Nid::UNDEF => Nid::PBE_WITHSHA1AND40BITRC2_CBC,
+            nid => nid,
+        }
+    }
+}
+
+#[cfg(ossl110)]
+fn to_private<T>(pkey: &PKeyRef<T>) -> PKey<Private>
+where
+    T: HasPrivate,
+{
+    unsafe { PKeyRef::<Private>::from_ptr(pkey.as_ptr()).to_owned() }
 }
 
 #[cfg(test)]
@@ -201,6 +760,8 @@ mod test {
     use crate::hash::MessageDigest;
     use crate::nid::Nid;
     use crate::pkey::PKey;
+    #[cfg(ossl110)]
+    use crate::pkey::Private;
     use crate::rsa::Rsa;
     use crate::x509::extension::KeyUsage;
     use crate::x509::{X509Name, X509};
@@ -280,4 +841,171 @@ mod test {
         );
         assert!(parsed.pkey.public_eq(&pkey));
     }
+
+    #[cfg(ossl110)]
+    fn self_signed(pkey: &PKey<Private>, cn: &str) -> X509 {
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, cn).unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+            .unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(pkey).unwrap();
+        builder.sign(pkey, MessageDigest::sha256()).unwrap();
+        builder.build()
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn create_mac_md() {
+        let pkey = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let cert = self_signed(&pkey, "leaf");
+
+        let mut builder = Pkcs12::builder();
+        builder
+            .key_algorithm(Nid::AES_256_CBC)
+            .cert_algorithm(Nid::AES_256_CBC)
+            .mac_md(MessageDigest::sha512());
+        let der = builder
+            .build("mypass", "leaf", &pkey, &cert)
+            .unwrap()
+            .to_der()
+            .unwrap();
+
+        // The DER encoding of the SHA-512 OID in the MAC's AlgorithmIdentifier
+        let sha512 = [
+            0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03,
+        ];
+        assert!(der.windows(sha512.len()).any(|w| w == sha512));
+        let parsed = Pkcs12::from_der(&der).unwrap().parse2("mypass").unwrap();
+        assert!(parsed.pkey.unwrap().public_eq(&pkey));
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn create2() {
+        let pkey = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let cert = self_signed(&pkey, "leaf");
+        let ca_key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let ca_cert = self_signed(&ca_key, "ca");
+        let mut ca = Stack::new().unwrap();
+        ca.push(ca_cert.clone()).unwrap();
+
+        let mut builder = Pkcs12::builder();
+        builder
+            .key_algorithm(Nid::AES_256_CBC)
+            .cert_algorithm(Nid::AES_256_CBC)
+            .mac_md(MessageDigest::sha256())
+            .name("alias")
+            .pkey(&pkey)
+            .cert(&cert)
+            .ca(ca);
+        let der = builder.build2("mypass").unwrap().to_der().unwrap();
+
+        let pkcs12 = Pkcs12::from_der(&der).unwrap();
+        assert!(pkcs12.mac_present());
+        let parsed = pkcs12.parse2("mypass").unwrap();
+        assert!(parsed.pkey.unwrap().public_eq(&pkey));
+        assert_eq!(
+            parsed.cert.unwrap().to_der().unwrap(),
+            cert.to_der().unwrap()
+        );
+        let parsed_ca = parsed.ca.unwrap();
+        assert_eq!(parsed_ca.len(), 1);
+        assert_eq!(parsed_ca[0].to_der().unwrap(), ca_cert.to_der().unwrap());
+
+        let bags = pkcs12.bags("mypass").unwrap();
+        assert_eq!(bags.len(), 3);
+        let key_id = cert.digest(MessageDigest::sha1()).unwrap().to_vec();
+        let named = bags
+            .iter()
+            .filter(|bag| bag.friendly_name.as_deref() == Some("alias"))
+            .collect::<Vec<_>>();
+        assert_eq!(named.len(), 2);
+        for bag in named {
+            assert_eq!(bag.local_key_id.as_ref(), Some(&key_id));
+        }
+
+        assert!(pkcs12.bags("wrongpass").is_err());
+        assert!(pkcs12.parse2("wrongpass").is_err());
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn create2_multiple_keys() {
+        let first_key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let first_cert = self_signed(&first_key, "first");
+        let second_key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let second_cert = self_signed(&second_key, "second");
+
+        let mut builder = Pkcs12::builder();
+        builder
+            .add_key(&first_key, Some("first"), Some(b"1"))
+            .add_cert(&first_cert, Some("first"), Some(b"1"))
+            .add_key(&second_key, Some("second"), Some(b"2"))
+            .add_cert(&second_cert, Some("second"), Some(b"2"));
+        let pkcs12 = builder.build2("mypass").unwrap();
+
+        let bags = pkcs12.bags("mypass").unwrap();
+        assert_eq!(bags.len(), 4);
+        for (name, id, key) in &[("first", b"1", &first_key), ("second", b"2", &second_key)] {
+            let bags = bags
+                .iter()
+                .filter(|bag| bag.friendly_name.as_deref() == Some(*name))
+                .collect::<Vec<_>>();
+            assert_eq!(bags.len(), 2);
+            for bag in bags {
+                assert_eq!(bag.local_key_id.as_deref(), Some(&id[..]));
+                match &bag.content {
+                    Pkcs12BagContent::Key(pkey) => assert!(pkey.public_eq(*key)),
+                    Pkcs12BagContent::Cert(cert) => {
+                        assert!(cert.public_key().unwrap().public_eq(*key))
+                    }
+                    Pkcs12BagContent::Other(nid) => panic!("unexpected bag {:?}", nid),
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn create2_truststore() {
+        let pkey = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let first = self_signed(&pkey, "first");
+        let second = self_signed(&pkey, "second");
+
+        let mut builder = Pkcs12::builder();
+        builder
+            .cert_algorithm(Nid::from_raw(-1))
+            .no_mac()
+            .add_cert(&first, Some("first"), None);
+        #[cfg(ossl300)]
+        builder.add_trusted_cert(&second, Some("second"));
+        #[cfg(not(ossl300))]
+        builder.add_cert(&second, Some("second"), None);
+        let der = builder.build2("").unwrap().to_der().unwrap();
+
+        let pkcs12 = Pkcs12::from_der(&der).unwrap();
+        assert!(!pkcs12.mac_present());
+        let parsed = pkcs12.parse2("").unwrap();
+        assert!(parsed.pkey.is_none());
+        assert!(parsed.cert.is_none());
+        assert_eq!(parsed.ca.unwrap().len(), 2);
+
+        let names = pkcs12
+            .bags("")
+            .unwrap()
+            .into_iter()
+            .map(|bag| bag.friendly_name.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["first", "second"]);
+    }
 }
@@ -7,7 +7,7 @@ use std::ptr;
 use crate::bio::{MemBio, MemBioSlice};
 use crate::error::ErrorStack;
 use crate::pkey::{HasPrivate, PKeyRef};
-use crate::stack::{Stack, StackRef};
+use crate::stack::{Stack, StackRef, Stackable};
 use crate::symm::Cipher;
 use crate::x509::store::X509StoreRef;
 use crate::x509::{X509Ref, X509};
@@ -26,6 +26,10 @@ foreign_type_and_impl_send_sync! {
     pub struct Pkcs7Ref;
 }
 
+impl Stackable for Pkcs7 {
+    type StackType = ffi::stack_st_PKCS7;
+}
+
 bitflags! {
     pub struct Pkcs7Flags: c_int {
         const TEXT = ffi::PKCS7_TEXT;
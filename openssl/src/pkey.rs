@@ -52,10 +52,19 @@ use std::ptr;
 use crate::bio::{MemBio, MemBioSlice};
 use crate::dh::Dh;
 use crate::dsa::Dsa;
+#[cfg(ossl110)]
+use crate::ec::Asn1Flag;
 use crate::ec::EcKey;
 use crate::error::ErrorStack;
+#[cfg(ossl110)]
+use crate::hash::MessageDigest;
+#[cfg(ossl111)]
+use crate::kdf::HkdfMode;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
 use crate::nid::Nid;
-use crate::rsa::Rsa;
+use crate::rsa::{Padding, Rsa};
+use crate::sign::RsaPssSaltlen;
 use crate::symm::Cipher;
 use crate::util::{invoke_passwd_cb, CallbackState};
 use crate::{cvt, cvt_p};
@@ -76,10 +85,20 @@ pub struct Id(c_int);
 impl Id {
     pub const RSA: Id = Id(ffi::EVP_PKEY_RSA);
     pub const HMAC: Id = Id(ffi::EVP_PKEY_HMAC);
+    pub const CMAC: Id = Id(ffi::EVP_PKEY_CMAC);
     pub const DSA: Id = Id(ffi::EVP_PKEY_DSA);
     pub const DH: Id = Id(ffi::EVP_PKEY_DH);
     pub const EC: Id = Id(ffi::EVP_PKEY_EC);
 
+    #[cfg(ossl110)]
+    pub const HKDF: Id = Id(ffi::EVP_PKEY_HKDF);
+    #[cfg(ossl110)]
+    pub const TLS1_PRF: Id = Id(ffi::EVP_PKEY_TLS1_PRF);
+
+    #[cfg(ossl111)]
+    pub const POLY1305: Id = Id(ffi::EVP_PKEY_POLY1305);
+    #[cfg(ossl111)]
+    pub const SIPHASH: Id = Id(ffi::EVP_PKEY_SIPHASH);
     #[cfg(ossl111)]
     pub const ED25519: Id = Id(ffi::EVP_PKEY_ED25519);
     #[cfg(ossl111)]
@@ -211,6 +230,36 @@ impl<T> PKeyRef<T> {
     pub fn size(&self) -> usize {
         unsafe { ffi::EVP_PKEY_size(self.as_ptr()) as usize }
     }
+
+    fn print(
+        &self,
+        f: unsafe extern "C" fn(
+            *mut ffi::BIO,
+            *const ffi::EVP_PKEY,
+            c_int,
+            *mut ffi::ASN1_PCTX,
+        ) -> c_int,
+    ) -> Result<Vec<u8>, ErrorStack> {
+        let bio = MemBio::new()?;
+        unsafe {
+            cvt(f(bio.as_ptr(), self.as_ptr(), 0, ptr::null_mut()))?;
+        }
+        Ok(bio.get_buf().to_owned())
+    }
+}
+
+impl<T> PKeyRef<T>
+where
+    T: HasParams,
+{
+    /// Returns a human readable representation of the key parameters.
+    ///
+    /// This corresponds to [`EVP_PKEY_print_params`].
+    ///
+    /// [`EVP_PKEY_print_params`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_print_params.html
+    pub fn params_to_text(&self) -> Result<Vec<u8>, ErrorStack> {
+        self.print(ffi::EVP_PKEY_print_params)
+    }
 }
 
 impl<T> PKeyRef<T>
@@ -239,6 +288,17 @@ where
         ffi::i2d_PUBKEY
     }
 
+    /// Returns a human readable representation of the public key.
+    ///
+    /// This is the format printed by `openssl pkey -pubin -text`.
+    ///
+    /// This corresponds to [`EVP_PKEY_print_public`].
+    ///
+    /// [`EVP_PKEY_print_public`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_print_public.html
+    pub fn public_key_to_text(&self) -> Result<Vec<u8>, ErrorStack> {
+        self.print(ffi::EVP_PKEY_print_public)
+    }
+
     /// Returns the size of the key.
     ///
     /// This corresponds to the bit length of the modulus of an RSA key, and the bit length of the
@@ -318,6 +378,17 @@ where
         ffi::i2d_PrivateKey
     }
 
+    /// Returns a human readable representation of the private key.
+    ///
+    /// This is the format printed by `openssl pkey -text`.
+    ///
+    /// This corresponds to [`EVP_PKEY_print_private`].
+    ///
+    /// [`EVP_PKEY_print_private`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_print_private.html
+    pub fn private_key_to_text(&self) -> Result<Vec<u8>, ErrorStack> {
+        self.print(ffi::EVP_PKEY_print_private)
+    }
+
     /// Raw byte representation of a private key
     ///
     /// This function only works for algorithms that support raw private keys.
@@ -737,7 +808,8 @@ impl PKey<Private> {
 
     /// Creates a private key from its raw byte representation
     ///
-    /// Algorithm types that support raw private keys are HMAC, X25519, ED25519, X448 or ED448
+    /// Algorithm types that support raw private keys are HMAC, Poly1305, SipHash, X25519, ED25519, X448
+    /// or ED448
     ///
     /// This corresponds to [`EVP_PKEY_new_raw_private_key`].
     ///
@@ -910,6 +982,73 @@ impl PKeyCtx {
         }
     }
 
+    /// Creates a context for operations using the provided key.
+    ///
+    /// The context holds its own reference to the key.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_new`].
+    ///
+    /// [`EVP_PKEY_CTX_new`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_new.html
+    pub fn from_pkey<T>(pkey: &PKeyRef<T>) -> Result<PKeyCtx, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let ctx_ptr = cvt_p(ffi::EVP_PKEY_CTX_new(pkey.as_ptr(), ptr::null_mut()))?;
+            Ok(PKeyCtx::from_ptr(ctx_ptr))
+        }
+    }
+
+    /// Creates a context for the named algorithm, fetched from the default library context.
+    ///
+    /// `properties` is a property query restricting which implementation is selected.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_new_from_name`].
+    ///
+    /// [`EVP_PKEY_CTX_new_from_name`]: https://www.openssl.org/docs/man3.0/man3/EVP_PKEY_CTX_new_from_name.html
+    #[cfg(ossl300)]
+    pub fn new_from_name(name: &str, properties: Option<&str>) -> Result<PKeyCtx, ErrorStack> {
+        unsafe { PKeyCtx::new_from_name_raw(ptr::null_mut(), name, properties) }
+    }
+
+    /// Like [`new_from_name`], but fetches the algorithm from the library context `ctx`.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_new_from_name`].
+    ///
+    /// # Safety
+    ///
+    /// The returned `PKeyCtx`, and any keys generated with it, must be dropped before `ctx`.
+    ///
+    /// [`new_from_name`]: #method.new_from_name
+    /// [`EVP_PKEY_CTX_new_from_name`]: https://www.openssl.org/docs/man3.0/man3/EVP_PKEY_CTX_new_from_name.html
+    #[cfg(ossl300)]
+    pub unsafe fn new_from_name_in(
+        ctx: &LibCtxRef,
+        name: &str,
+        properties: Option<&str>,
+    ) -> Result<PKeyCtx, ErrorStack> {
+        PKeyCtx::new_from_name_raw(ctx.as_ptr(), name, properties)
+    }
+
+    #[cfg(ossl300)]
+    unsafe fn new_from_name_raw(
+        ctx: *mut ffi::OSSL_LIB_CTX,
+        name: &str,
+        properties: Option<&str>,
+    ) -> Result<PKeyCtx, ErrorStack> {
+        let name = CString::new(name).unwrap();
+        let properties = properties.map(|s| CString::new(s).unwrap());
+        ffi::init();
+        let ctx_ptr = cvt_p(ffi::EVP_PKEY_CTX_new_from_name(
+            ctx,
+            name.as_ptr(),
+            properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+        ))?;
+        Ok(PKeyCtx::from_ptr(ctx_ptr))
+    }
+
     pub fn keygen_init(&self) -> Result<(), ErrorStack> {
         unsafe { cvt(ffi::EVP_PKEY_keygen_init(self.as_ptr())).map(|_code| ()) }
     }
@@ -949,6 +1088,690 @@ impl PKeyCtx {
             Ok(PKey::from_ptr(pkey_ptr))
         }
     }
+
+    /// Prepares the context for parameter generation.
+    ///
+    /// This corresponds to [`EVP_PKEY_paramgen_init`].
+    ///
+    /// [`EVP_PKEY_paramgen_init`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_keygen.html
+    pub fn paramgen_init(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::EVP_PKEY_paramgen_init(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Generates a new set of key parameters.
+    ///
+    /// This corresponds to [`EVP_PKEY_paramgen`].
+    ///
+    /// [`EVP_PKEY_paramgen`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_keygen.html
+    pub fn paramgen(&self) -> Result<PKey<Params>, ErrorStack> {
+        unsafe {
+            let mut pkey_ptr = ptr::null_mut();
+            cvt(ffi::EVP_PKEY_paramgen(self.as_ptr(), &mut pkey_ptr))?;
+            Ok(PKey::from_ptr(pkey_ptr))
+        }
+    }
+
+    /// Prepares the context for signing with its private key.
+    ///
+    /// This corresponds to [`EVP_PKEY_sign_init`].
+    ///
+    /// [`EVP_PKEY_sign_init`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_sign.html
+    pub fn sign_init(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::EVP_PKEY_sign_init(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Signs `tbs`, writing the signature into `sig`.
+    ///
+    /// `tbs` is usually a message digest rather than the message itself. Returns the number of
+    /// bytes written.
+    ///
+    /// This corresponds to [`EVP_PKEY_sign`].
+    ///
+    /// [`EVP_PKEY_sign`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_sign.html
+    pub fn sign(&self, tbs: &[u8], sig: &mut [u8]) -> Result<usize, ErrorStack> {
+        self.sign_inner(tbs, Some(sig))
+    }
+
+    /// Computes an upper bound on the length of a signature.
+    ///
+    /// This corresponds to [`EVP_PKEY_sign`] with a null output buffer.
+    ///
+    /// [`EVP_PKEY_sign`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_sign.html
+    pub fn sign_len(&self) -> Result<usize, ErrorStack> {
+        self.sign_inner(&[], None)
+    }
+
+    /// Signs `tbs`, returning the signature in a new buffer.
+    ///
+    /// This corresponds to [`EVP_PKEY_sign`].
+    ///
+    /// [`EVP_PKEY_sign`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_sign.html
+    pub fn sign_to_vec(&self, tbs: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let mut sig = vec![0; self.sign_inner(tbs, None)?];
+        let len = self.sign(tbs, &mut sig)?;
+        // The advertised length is not always equal to the real length for things like DSA
+        sig.truncate(len);
+        Ok(sig)
+    }
+
+    fn sign_inner(&self, tbs: &[u8], sig: Option<&mut [u8]>) -> Result<usize, ErrorStack> {
+        let (sig, mut len) = match sig {
+            Some(sig) => (sig.as_mut_ptr(), sig.len()),
+            None => (ptr::null_mut(), 0),
+        };
+        unsafe {
+            cvt(ffi::EVP_PKEY_sign(
+                self.as_ptr(),
+                sig,
+                &mut len,
+                tbs.as_ptr(),
+                tbs.len(),
+            ))?;
+        }
+        Ok(len)
+    }
+
+    /// Prepares the context for signature verification with its public key.
+    ///
+    /// This corresponds to [`EVP_PKEY_verify_init`].
+    ///
+    /// [`EVP_PKEY_verify_init`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_verify.html
+    pub fn verify_init(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::EVP_PKEY_verify_init(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Verifies that `sig` is a valid signature of `tbs`.
+    ///
+    /// Returns `Ok(false)` if the signature does not match, and an error if the verification
+    /// could not be performed.
+    ///
+    /// This corresponds to [`EVP_PKEY_verify`].
+    ///
+    /// [`EVP_PKEY_verify`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_verify.html
+    pub fn verify(&self, tbs: &[u8], sig: &[u8]) -> Result<bool, ErrorStack> {
+        unsafe {
+            let r = ffi::EVP_PKEY_verify(
+                self.as_ptr(),
+                sig.as_ptr(),
+                sig.len(),
+                tbs.as_ptr(),
+                tbs.len(),
+            );
+            match r {
+                1 => Ok(true),
+                0 => {
+                    ErrorStack::get(); // discard error stack
+                    Ok(false)
+                }
+                _ => Err(ErrorStack::get()),
+            }
+        }
+    }
+
+    /// Prepares the context for recovering signed data with its public key.
+    ///
+    /// This corresponds to [`EVP_PKEY_verify_recover_init`].
+    ///
+    /// [`EVP_PKEY_verify_recover_init`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_verify_recover.html
+    pub fn verify_recover_init(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::EVP_PKEY_verify_recover_init(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Recovers the data signed by `sig`, writing it into `buf`.
+    ///
+    /// Returns the number of bytes written.
+    ///
+    /// This corresponds to [`EVP_PKEY_verify_recover`].
+    ///
+    /// [`EVP_PKEY_verify_recover`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_verify_recover.html
+    pub fn verify_recover(&self, sig: &[u8], buf: &mut [u8]) -> Result<usize, ErrorStack> {
+        let mut len = buf.len();
+        unsafe {
+            cvt(ffi::EVP_PKEY_verify_recover(
+                self.as_ptr(),
+                buf.as_mut_ptr(),
+                &mut len,
+                sig.as_ptr(),
+                sig.len(),
+            ))?;
+        }
+        Ok(len)
+    }
+
+    /// Recovers the data signed by `sig`, returning it in a new buffer.
+    ///
+    /// This corresponds to [`EVP_PKEY_verify_recover`].
+    ///
+    /// [`EVP_PKEY_verify_recover`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_verify_recover.html
+    pub fn verify_recover_to_vec(&self, sig: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let mut len = 0;
+        unsafe {
+            cvt(ffi::EVP_PKEY_verify_recover(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut len,
+                sig.as_ptr(),
+                sig.len(),
+            ))?;
+        }
+        let mut buf = vec![0; len];
+        let len = self.verify_recover(sig, &mut buf)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    /// Prepares the context for encryption with its public key.
+    ///
+    /// This corresponds to [`EVP_PKEY_encrypt_init`].
+    ///
+    /// [`EVP_PKEY_encrypt_init`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_encrypt_init.html
+    pub fn encrypt_init(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::EVP_PKEY_encrypt_init(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Encrypts `from`, writing the ciphertext into `to`.
+    ///
+    /// Returns the number of bytes written.
+    ///
+    /// This corresponds to [`EVP_PKEY_encrypt`].
+    ///
+    /// [`EVP_PKEY_encrypt`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_encrypt.html
+    pub fn encrypt(&self, from: &[u8], to: &mut [u8]) -> Result<usize, ErrorStack> {
+        let mut written = to.len();
+        unsafe {
+            cvt(ffi::EVP_PKEY_encrypt(
+                self.as_ptr(),
+                to.as_mut_ptr(),
+                &mut written,
+                from.as_ptr(),
+                from.len(),
+            ))?;
+        }
+        Ok(written)
+    }
+
+    /// Returns the size of the buffer needed to encrypt `from`.
+    ///
+    /// This corresponds to [`EVP_PKEY_encrypt`] called with a null pointer as output argument.
+    ///
+    /// [`EVP_PKEY_encrypt`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_encrypt.html
+    pub fn encrypt_len(&self, from: &[u8]) -> Result<usize, ErrorStack> {
+        let mut written = 0;
+        unsafe {
+            cvt(ffi::EVP_PKEY_encrypt(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut written,
+                from.as_ptr(),
+                from.len(),
+            ))?;
+        }
+        Ok(written)
+    }
+
+    /// Encrypts `from`, returning the ciphertext in a new buffer.
+    ///
+    /// This simply wraps [`PKeyCtx::encrypt_len`] and [`PKeyCtx::encrypt`].
+    pub fn encrypt_to_vec(&self, from: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let mut buf = vec![0; self.encrypt_len(from)?];
+        let len = self.encrypt(from, &mut buf)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    /// Prepares the context for decryption with its private key.
+    ///
+    /// This corresponds to [`EVP_PKEY_decrypt_init`].
+    ///
+    /// [`EVP_PKEY_decrypt_init`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_decrypt_init.html
+    pub fn decrypt_init(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::EVP_PKEY_decrypt_init(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Decrypts `from`, writing the plaintext into `to`.
+    ///
+    /// Returns the number of bytes written.
+    ///
+    /// This corresponds to [`EVP_PKEY_decrypt`].
+    ///
+    /// [`EVP_PKEY_decrypt`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_decrypt.html
+    pub fn decrypt(&self, from: &[u8], to: &mut [u8]) -> Result<usize, ErrorStack> {
+        let mut written = to.len();
+        unsafe {
+            cvt(ffi::EVP_PKEY_decrypt(
+                self.as_ptr(),
+                to.as_mut_ptr(),
+                &mut written,
+                from.as_ptr(),
+                from.len(),
+            ))?;
+        }
+        Ok(written)
+    }
+
+    /// Returns the size of the buffer needed to decrypt `from`.
+    ///
+    /// This corresponds to [`EVP_PKEY_decrypt`] called with a null pointer as output argument.
+    ///
+    /// [`EVP_PKEY_decrypt`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_decrypt.html
+    pub fn decrypt_len(&self, from: &[u8]) -> Result<usize, ErrorStack> {
+        let mut written = 0;
+        unsafe {
+            cvt(ffi::EVP_PKEY_decrypt(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut written,
+                from.as_ptr(),
+                from.len(),
+            ))?;
+        }
+        Ok(written)
+    }
+
+    /// Decrypts `from`, returning the plaintext in a new buffer.
+    ///
+    /// This simply wraps [`PKeyCtx::decrypt_len`] and [`PKeyCtx::decrypt`].
+    pub fn decrypt_to_vec(&self, from: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let mut buf = vec![0; self.decrypt_len(from)?];
+        let len = self.decrypt(from, &mut buf)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    /// Prepares the context for key derivation.
+    ///
+    /// This corresponds to [`EVP_PKEY_derive_init`].
+    ///
+    /// [`EVP_PKEY_derive_init`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_derive_init.html
+    pub fn derive_init(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::EVP_PKEY_derive_init(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Derives a key, writing it into `buf`.
+    ///
+    /// For KDF contexts such as HKDF and TLS1-PRF the length of `buf` determines the length of the
+    /// output. Returns the number of bytes written.
+    ///
+    /// This corresponds to [`EVP_PKEY_derive`].
+    ///
+    /// [`EVP_PKEY_derive`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_derive.html
+    pub fn derive(&self, buf: &mut [u8]) -> Result<usize, ErrorStack> {
+        let mut len = buf.len();
+        unsafe {
+            cvt(ffi::EVP_PKEY_derive(
+                self.as_ptr(),
+                buf.as_mut_ptr(),
+                &mut len,
+            ))?;
+        }
+        Ok(len)
+    }
+
+    /// Sets the peer key used for key derivation.
+    ///
+    /// This corresponds to [`EVP_PKEY_derive_set_peer`].
+    ///
+    /// [`EVP_PKEY_derive_set_peer`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_derive.html
+    pub fn derive_set_peer<T>(&self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe { cvt(ffi::EVP_PKEY_derive_set_peer(self.as_ptr(), key.as_ptr())).map(|_| ()) }
+    }
+
+    /// Returns the size of the derived key.
+    ///
+    /// This is not meaningful for KDF contexts, whose output length is chosen by the caller.
+    ///
+    /// This corresponds to [`EVP_PKEY_derive`] called with a null pointer as output argument.
+    ///
+    /// [`EVP_PKEY_derive`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_derive.html
+    pub fn derive_len(&self) -> Result<usize, ErrorStack> {
+        let mut len = 0;
+        unsafe {
+            cvt(ffi::EVP_PKEY_derive(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut len,
+            ))?;
+        }
+        Ok(len)
+    }
+
+    /// Derives a key, returning it in a new buffer.
+    ///
+    /// This simply wraps [`PKeyCtx::derive_len`] and [`PKeyCtx::derive`].
+    pub fn derive_to_vec(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut buf = vec![0; self.derive_len()?];
+        let len = self.derive(&mut buf)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    /// Sets the digest used for signing and verification.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_signature_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_signature_md`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_ctrl.html
+    pub fn set_signature_md(&self, md: MessageDigest) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_PKEY_CTX_set_signature_md(
+                self.as_ptr(),
+                md.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the RSA padding mode in use.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to `EVP_PKEY_CTX_get_rsa_padding`.
+    pub fn rsa_padding(&self) -> Result<Padding, ErrorStack> {
+        unsafe {
+            let mut pad = 0;
+            cvt(ffi::EVP_PKEY_CTX_get_rsa_padding(self.as_ptr(), &mut pad))
+                .map(|_| Padding::from_raw(pad))
+        }
+    }
+
+    /// Sets the RSA padding mode.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_padding`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_padding`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_rsa_padding.html
+    pub fn set_rsa_padding(&self, padding: Padding) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_PKEY_CTX_set_rsa_padding(
+                self.as_ptr(),
+                padding.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the RSA PSS salt length.
+    ///
+    /// This is only useful for RSA keys using `Padding::PKCS1_PSS`.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_pss_saltlen`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_pss_saltlen`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_rsa_pss_saltlen.html
+    pub fn set_rsa_pss_saltlen(&self, len: RsaPssSaltlen) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_PKEY_CTX_set_rsa_pss_saltlen(
+                self.as_ptr(),
+                len.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the RSA MGF1 algorithm.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_mgf1_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_mgf1_md`]: https://www.openssl.org/docs/manmaster/man7/RSA-PSS.html
+    pub fn set_rsa_mgf1_md(&self, md: MessageDigest) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_PKEY_CTX_set_rsa_mgf1_md(
+                self.as_ptr(),
+                md.as_ptr() as *mut _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the RSA OAEP algorithm.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_oaep_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_oaep_md`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_CTX_set_rsa_oaep_md.html
+    #[cfg(any(ossl102, libressl310))]
+    pub fn set_rsa_oaep_md(&self, md: MessageDigest) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_PKEY_CTX_set_rsa_oaep_md(
+                self.as_ptr(),
+                md.as_ptr() as *mut _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the RSA OAEP label.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set0_rsa_oaep_label`].
+    ///
+    /// [`EVP_PKEY_CTX_set0_rsa_oaep_label`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_CTX_set0_rsa_oaep_label.html
+    #[cfg(any(ossl102, libressl310))]
+    pub fn set_rsa_oaep_label(&self, label: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            let p = cvt_p(ffi::CRYPTO_malloc(
+                label.len() as _,
+                concat!(file!(), "\0").as_ptr() as *const _,
+                line!() as c_int,
+            ))?;
+            ptr::copy_nonoverlapping(label.as_ptr(), p as *mut u8, label.len());
+
+            cvt(ffi::EVP_PKEY_CTX_set0_rsa_oaep_label(
+                self.as_ptr(),
+                p,
+                label.len() as c_int,
+            ))
+            .map(|_| ())
+            .map_err(|e| {
+                #[cfg(not(ossl110))]
+                ffi::CRYPTO_free(p);
+                #[cfg(ossl110)]
+                ffi::CRYPTO_free(
+                    p,
+                    concat!(file!(), "\0").as_ptr() as *const _,
+                    line!() as c_int,
+                );
+                e
+            })
+        }
+    }
+
+    /// Sets the named curve used for EC parameter and key generation.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_ec_paramgen_curve_nid`].
+    ///
+    /// [`EVP_PKEY_CTX_set_ec_paramgen_curve_nid`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_ec_paramgen_curve_nid.html
+    pub fn set_ec_paramgen_curve_nid(&self, nid: Nid) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_PKEY_CTX_set_ec_paramgen_curve_nid(
+                self.as_ptr(),
+                nid.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets whether generated EC parameters are encoded as a named curve or explicitly.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_ec_param_enc`].
+    ///
+    /// [`EVP_PKEY_CTX_set_ec_param_enc`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_ec_paramgen_curve_nid.html
+    #[cfg(ossl110)]
+    pub fn set_ec_param_enc(&self, flag: Asn1Flag) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_PKEY_CTX_set_ec_param_enc(
+                self.as_ptr(),
+                flag.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the digest used by HKDF.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_hkdf_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_hkdf_md`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_PKEY_CTX_set_hkdf_md.html
+    #[cfg(ossl110)]
+    pub fn set_hkdf_md(&self, digest: MessageDigest) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_PKEY_CTX_set_hkdf_md(
+                self.as_ptr(),
+                digest.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the HKDF mode of operation.
+    ///
+    /// Defaults to `HkdfMode::EXTRACT_AND_EXPAND`.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_hkdf_mode`].
+    ///
+    /// [`EVP_PKEY_CTX_set_hkdf_mode`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_PKEY_CTX_set_hkdf_md.html
+    #[cfg(ossl111)]
+    pub fn set_hkdf_mode(&self, mode: HkdfMode) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_PKEY_CTX_set_hkdf_mode(
+                self.as_ptr(),
+                mode.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the HKDF salt.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set1_hkdf_salt`].
+    ///
+    /// [`EVP_PKEY_CTX_set1_hkdf_salt`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_PKEY_CTX_set_hkdf_md.html
+    #[cfg(ossl110)]
+    pub fn set_hkdf_salt(&self, salt: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(salt.len() <= c_int::max_value() as usize);
+            cvt(ffi::EVP_PKEY_CTX_set1_hkdf_salt(
+                self.as_ptr(),
+                salt.as_ptr(),
+                salt.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the HKDF input keying material.
+    ///
+    /// In `HkdfMode::EXPAND_ONLY` mode this is the pseudorandom key.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set1_hkdf_key`].
+    ///
+    /// [`EVP_PKEY_CTX_set1_hkdf_key`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_PKEY_CTX_set_hkdf_md.html
+    #[cfg(ossl110)]
+    pub fn set_hkdf_key(&self, key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(key.len() <= c_int::max_value() as usize);
+            cvt(ffi::EVP_PKEY_CTX_set1_hkdf_key(
+                self.as_ptr(),
+                key.as_ptr(),
+                key.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Appends to the HKDF info value.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_add1_hkdf_info`].
+    ///
+    /// [`EVP_PKEY_CTX_add1_hkdf_info`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_PKEY_CTX_set_hkdf_md.html
+    #[cfg(ossl110)]
+    pub fn add_hkdf_info(&self, info: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(info.len() <= c_int::max_value() as usize);
+            cvt(ffi::EVP_PKEY_CTX_add1_hkdf_info(
+                self.as_ptr(),
+                info.as_ptr(),
+                info.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the digest used by the TLS1-PRF.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_tls1_prf_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_tls1_prf_md`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_PKEY_CTX_set_tls1_prf_md.html
+    #[cfg(ossl110)]
+    pub fn set_tls1_prf_md(&self, digest: MessageDigest) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_PKEY_CTX_set_tls1_prf_md(
+                self.as_ptr(),
+                digest.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the TLS1-PRF secret.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set1_tls1_prf_secret`].
+    ///
+    /// [`EVP_PKEY_CTX_set1_tls1_prf_secret`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_PKEY_CTX_set_tls1_prf_md.html
+    #[cfg(ossl110)]
+    pub fn set_tls1_prf_secret(&self, secret: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(secret.len() <= c_int::max_value() as usize);
+            cvt(ffi::EVP_PKEY_CTX_set1_tls1_prf_secret(
+                self.as_ptr(),
+                secret.as_ptr(),
+                secret.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Appends to the TLS1-PRF seed.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_add1_tls1_prf_seed`].
+    ///
+    /// [`EVP_PKEY_CTX_add1_tls1_prf_seed`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_PKEY_CTX_set_tls1_prf_md.html
+    #[cfg(ossl110)]
+    pub fn add_tls1_prf_seed(&self, seed: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(seed.len() <= c_int::max_value() as usize);
+            cvt(ffi::EVP_PKEY_CTX_add1_tls1_prf_seed(
+                self.as_ptr(),
+                seed.as_ptr(),
+                seed.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -978,6 +1801,26 @@ mod tests {
         assert!(PKey::private_key_from_pem_passphrase(&pem, b"fizzbuzz").is_err());
     }
 
+    #[test]
+    fn test_to_text() {
+        let key = include_bytes!("../test/key.pem");
+        let key = PKey::private_key_from_pem(key).unwrap();
+
+        let text = String::from_utf8(key.private_key_to_text().unwrap()).unwrap();
+        assert!(text.contains("Private-Key: (2048 bit"));
+        assert!(text.contains("privateExponent:"));
+
+        let text = String::from_utf8(key.public_key_to_text().unwrap()).unwrap();
+        assert!(text.contains("Public-Key: (2048 bit"));
+        assert!(!text.contains("privateExponent:"));
+
+        let dh = include_bytes!("../test/dhparams.pem");
+        let dh = Dh::params_from_pem(dh).unwrap();
+        let dh = PKey::from_dh(dh).unwrap();
+        let text = String::from_utf8(dh.params_to_text().unwrap()).unwrap();
+        assert!(text.contains("(2048 bit)"));
+    }
+
     #[test]
     fn test_unencrypted_pkcs8() {
         let key = include_bytes!("../test/pkcs8-nocrypt.der");
@@ -1212,10 +2055,136 @@ mod tests {
         assert!(pkey.raw_public_key().is_err());
     }
 
+    #[test]
+    #[cfg(ossl300)]
+    fn test_keygen_from_name() {
+        use crate::lib_ctx::LibCtx;
+        use crate::provider::Provider;
+
+        let lib_ctx = LibCtx::new().unwrap();
+        let _provider = unsafe { Provider::load_in(&lib_ctx, "default").unwrap() };
+
+        let ctx = unsafe { PKeyCtx::new_from_name_in(&lib_ctx, "ED25519", None).unwrap() };
+        ctx.keygen_init().unwrap();
+        let key = ctx.keygen().unwrap();
+        assert_eq!(key.id(), Id::ED25519);
+        drop(key);
+
+        assert!(unsafe { PKeyCtx::new_from_name_in(&lib_ctx, "NOT-A-KEY-TYPE", None).is_err() });
+        PKeyCtx::new_from_name("ED25519", None).unwrap();
+    }
+
     #[cfg(ossl300)]
     #[test]
     fn test_ec_gen() {
         let key = PKey::ec_gen("prime256v1").unwrap();
         assert!(key.ec_key().is_ok());
     }
+
+    #[test]
+    fn test_ctx_sign_verify() {
+        let key = include_bytes!("../test/rsa.pem");
+        let key = PKey::private_key_from_pem(key).unwrap();
+        let digest = crate::hash::hash(MessageDigest::sha256(), b"hello world").unwrap();
+
+        let ctx = PKeyCtx::from_pkey(&key).unwrap();
+        ctx.sign_init().unwrap();
+        ctx.set_rsa_padding(Padding::PKCS1).unwrap();
+        ctx.set_signature_md(MessageDigest::sha256()).unwrap();
+        assert_eq!(ctx.sign_len().unwrap(), key.size());
+        let sig = ctx.sign_to_vec(&digest).unwrap();
+        assert_eq!(sig.len(), key.size());
+
+        let ctx = PKeyCtx::from_pkey(&key).unwrap();
+        ctx.verify_init().unwrap();
+        ctx.set_rsa_padding(Padding::PKCS1).unwrap();
+        ctx.set_signature_md(MessageDigest::sha256()).unwrap();
+        assert!(ctx.verify(&digest, &sig).unwrap());
+        assert!(!ctx.verify(&[0; 32], &sig).unwrap());
+
+        let ctx = PKeyCtx::from_pkey(&key).unwrap();
+        ctx.verify_recover_init().unwrap();
+        ctx.set_rsa_padding(Padding::PKCS1).unwrap();
+        ctx.set_signature_md(MessageDigest::sha256()).unwrap();
+        assert_eq!(ctx.verify_recover_to_vec(&sig).unwrap(), &*digest);
+    }
+
+    #[test]
+    fn test_ctx_sign_pss() {
+        let key = include_bytes!("../test/rsa.pem");
+        let key = PKey::private_key_from_pem(key).unwrap();
+        let digest = crate::hash::hash(MessageDigest::sha256(), b"hello world").unwrap();
+
+        let ctx = PKeyCtx::from_pkey(&key).unwrap();
+        ctx.sign_init().unwrap();
+        ctx.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+        ctx.set_signature_md(MessageDigest::sha256()).unwrap();
+        ctx.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+            .unwrap();
+        ctx.set_rsa_mgf1_md(MessageDigest::sha256()).unwrap();
+        let sig = ctx.sign_to_vec(&digest).unwrap();
+
+        let ctx = PKeyCtx::from_pkey(&key).unwrap();
+        ctx.verify_init().unwrap();
+        ctx.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+        ctx.set_signature_md(MessageDigest::sha256()).unwrap();
+        ctx.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+            .unwrap();
+        ctx.set_rsa_mgf1_md(MessageDigest::sha256()).unwrap();
+        assert!(ctx.verify(&digest, &sig).unwrap());
+    }
+
+    #[test]
+    #[cfg(any(ossl102, libressl310))]
+    fn test_ctx_encrypt_decrypt_oaep() {
+        let key = include_bytes!("../test/rsa.pem");
+        let key = PKey::private_key_from_pem(key).unwrap();
+
+        let ctx = PKeyCtx::from_pkey(&key).unwrap();
+        ctx.encrypt_init().unwrap();
+        ctx.set_rsa_padding(Padding::PKCS1_OAEP).unwrap();
+        ctx.set_rsa_oaep_md(MessageDigest::sha256()).unwrap();
+        ctx.set_rsa_oaep_label(b"label").unwrap();
+        assert_eq!(ctx.rsa_padding().unwrap(), Padding::PKCS1_OAEP);
+        let ciphertext = ctx.encrypt_to_vec(b"hello world").unwrap();
+
+        let ctx = PKeyCtx::from_pkey(&key).unwrap();
+        ctx.decrypt_init().unwrap();
+        ctx.set_rsa_padding(Padding::PKCS1_OAEP).unwrap();
+        ctx.set_rsa_oaep_md(MessageDigest::sha256()).unwrap();
+        ctx.set_rsa_oaep_label(b"label").unwrap();
+        assert_eq!(ctx.decrypt_to_vec(&ciphertext).unwrap(), b"hello world");
+
+        ctx.set_rsa_oaep_label(b"other").unwrap();
+        assert!(ctx.decrypt_to_vec(&ciphertext).is_err());
+    }
+
+    #[test]
+    fn test_ctx_ec_paramgen_derive() {
+        let ctx = PKeyCtx::new(Id::EC).unwrap();
+        ctx.paramgen_init().unwrap();
+        ctx.set_ec_paramgen_curve_nid(Nid::X9_62_PRIME256V1)
+            .unwrap();
+        #[cfg(ossl110)]
+        ctx.set_ec_param_enc(crate::ec::Asn1Flag::NAMED_CURVE)
+            .unwrap();
+        let params = ctx.paramgen().unwrap();
+        assert_eq!(params.id(), Id::EC);
+
+        let ctx = PKeyCtx::from_pkey(&params).unwrap();
+        ctx.keygen_init().unwrap();
+        let key1 = ctx.keygen().unwrap();
+        let key2 = ctx.keygen().unwrap();
+        assert_eq!(key1.bits(), 256);
+
+        let ctx = PKeyCtx::from_pkey(&key1).unwrap();
+        ctx.derive_init().unwrap();
+        ctx.derive_set_peer(&key2).unwrap();
+        let shared1 = ctx.derive_to_vec().unwrap();
+
+        let ctx = PKeyCtx::from_pkey(&key2).unwrap();
+        ctx.derive_init().unwrap();
+        ctx.derive_set_peer(&key1).unwrap();
+        assert_eq!(ctx.derive_to_vec().unwrap(), shared1);
+    }
 }
@@ -0,0 +1,205 @@
+//! OpenSSL providers.
+//!
+//! Providers supply the implementations of cryptographic algorithms. OpenSSL ships with the
+//! `default`, `base`, `legacy`, `fips` and `null` providers. Loading any provider into a library
+//! context disables the automatic loading of the `default` provider.
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+use foreign_types::{ForeignType, ForeignTypeRef};
+use std::ffi::{CStr, CString};
+use std::path::Path;
+use std::ptr;
+use std::str;
+
+use crate::error::ErrorStack;
+use crate::lib_ctx::LibCtxRef;
+use crate::{cvt, cvt_p};
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::OSSL_PROVIDER;
+    fn drop = unload;
+
+    /// A loaded provider.
+    ///
+    /// The provider is unloaded when this value is dropped.
+    pub struct Provider;
+    /// Reference to a [`Provider`].
+    ///
+    /// [`Provider`]: struct.Provider.html
+    pub struct ProviderRef;
+}
+
+unsafe fn unload(prov: *mut ffi::OSSL_PROVIDER) {
+    ffi::OSSL_PROVIDER_unload(prov);
+}
+
+fn lib_ctx_ptr(ctx: Option<&LibCtxRef>) -> *mut ffi::OSSL_LIB_CTX {
+    ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr)
+}
+
+impl Provider {
+    /// Loads a provider into the default library context.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_load`].
+    ///
+    /// [`OSSL_PROVIDER_load`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_load.html
+    pub fn load(name: &str) -> Result<Provider, ErrorStack> {
+        unsafe { Provider::load_raw(ptr::null_mut(), name) }
+    }
+
+    /// Like [`load`], but loads the provider into the library context `ctx`.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_load`].
+    ///
+    /// # Safety
+    ///
+    /// The returned `Provider` must be dropped before `ctx`.
+    ///
+    /// [`load`]: #method.load
+    /// [`OSSL_PROVIDER_load`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_load.html
+    pub unsafe fn load_in(ctx: &LibCtxRef, name: &str) -> Result<Provider, ErrorStack> {
+        Provider::load_raw(ctx.as_ptr(), name)
+    }
+
+    unsafe fn load_raw(ctx: *mut ffi::OSSL_LIB_CTX, name: &str) -> Result<Provider, ErrorStack> {
+        let name = CString::new(name).unwrap();
+        ffi::init();
+        let ptr = cvt_p(ffi::OSSL_PROVIDER_load(ctx, name.as_ptr()))?;
+        Ok(Provider::from_ptr(ptr))
+    }
+
+    /// Like [`load`], but if `retain_fallbacks` is `true` the automatic loading of the `default`
+    /// provider is not disabled.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_try_load`].
+    ///
+    /// [`load`]: #method.load
+    /// [`OSSL_PROVIDER_try_load`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_try_load.html
+    pub fn try_load(name: &str, retain_fallbacks: bool) -> Result<Provider, ErrorStack> {
+        unsafe { Provider::try_load_raw(ptr::null_mut(), name, retain_fallbacks) }
+    }
+
+    /// Like [`try_load`], but loads the provider into the library context `ctx`.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_try_load`].
+    ///
+    /// # Safety
+    ///
+    /// The returned `Provider` must be dropped before `ctx`.
+    ///
+    /// [`try_load`]: #method.try_load
+    /// [`OSSL_PROVIDER_try_load`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_try_load.html
+    pub unsafe fn try_load_in(
+        ctx: &LibCtxRef,
+        name: &str,
+        retain_fallbacks: bool,
+    ) -> Result<Provider, ErrorStack> {
+        Provider::try_load_raw(ctx.as_ptr(), name, retain_fallbacks)
+    }
+
+    unsafe fn try_load_raw(
+        ctx: *mut ffi::OSSL_LIB_CTX,
+        name: &str,
+        retain_fallbacks: bool,
+    ) -> Result<Provider, ErrorStack> {
+        let name = CString::new(name).unwrap();
+        ffi::init();
+        let ptr = cvt_p(ffi::OSSL_PROVIDER_try_load(
+            ctx,
+            name.as_ptr(),
+            retain_fallbacks as _,
+        ))?;
+        Ok(Provider::from_ptr(ptr))
+    }
+
+    /// Determines if a provider is loaded and available in a library context.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_available`].
+    ///
+    /// [`OSSL_PROVIDER_available`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_available.html
+    pub fn available(ctx: Option<&LibCtxRef>, name: &str) -> bool {
+        let name = CString::new(name).unwrap();
+        unsafe {
+            ffi::init();
+            ffi::OSSL_PROVIDER_available(lib_ctx_ptr(ctx), name.as_ptr()) == 1
+        }
+    }
+
+    /// Sets the directory searched for provider modules in a library context.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_set_default_search_path`].
+    ///
+    /// [`OSSL_PROVIDER_set_default_search_path`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_set_default_search_path.html
+    pub fn set_default_search_path<P: AsRef<Path>>(
+        ctx: Option<&LibCtxRef>,
+        path: P,
+    ) -> Result<(), ErrorStack> {
+        let path = CString::new(path.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe {
+            ffi::init();
+            cvt(ffi::OSSL_PROVIDER_set_default_search_path(
+                lib_ctx_ptr(ctx),
+                path.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+}
+
+impl ProviderRef {
+    /// Returns the name of the provider.
+    ///
+    /// This corresponds to [`OSSL_PROVIDER_get0_name`].
+    ///
+    /// [`OSSL_PROVIDER_get0_name`]: https://www.openssl.org/docs/man3.0/man3/OSSL_PROVIDER_get0_name.html
+    pub fn name(&self) -> &str {
+        unsafe {
+            let name = ffi::OSSL_PROVIDER_get0_name(self.as_ptr());
+            str::from_utf8(CStr::from_ptr(name).to_bytes()).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lib_ctx::LibCtx;
+    use crate::md::Md;
+
+    #[test]
+    fn load_into_lib_ctx() {
+        // loading any provider explicitly prevents the default provider from being loaded
+        // implicitly
+        let ctx = LibCtx::new().unwrap();
+        let _base = unsafe { Provider::load_in(&ctx, "base").unwrap() };
+        assert!(!Provider::available(Some(&ctx), "default"));
+
+        let provider = unsafe { Provider::load_in(&ctx, "default").unwrap() };
+        assert_eq!(provider.name(), "default");
+        assert!(Provider::available(Some(&ctx), "default"));
+        unsafe { Md::fetch_in(&ctx, "SHA256", Some("provider=default")).unwrap() };
+
+        drop(provider);
+        assert!(!Provider::available(Some(&ctx), "default"));
+    }
+
+    #[test]
+    fn isolated_lib_ctx() {
+        // the base provider has no digests, and loading it disables the default provider
+        let ctx = LibCtx::new().unwrap();
+        let _base = unsafe { Provider::load_in(&ctx, "base").unwrap() };
+        assert!(unsafe { Md::fetch_in(&ctx, "SHA256", None).is_err() });
+
+        let ctx = LibCtx::new().unwrap();
+        let _base = unsafe { Provider::try_load_in(&ctx, "base", true).unwrap() };
+        unsafe { Md::fetch_in(&ctx, "SHA256", None).unwrap() };
+    }
+
+    #[test]
+    fn missing_provider() {
+        let ctx = LibCtx::new().unwrap();
+        let dir = tempdir::TempDir::new("providers").unwrap();
+        Provider::set_default_search_path(Some(&ctx), dir.path()).unwrap();
+        assert!(unsafe { Provider::load_in(&ctx, "nonexistent").is_err() });
+    }
+}
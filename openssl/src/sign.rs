@@ -5,6 +5,9 @@
 //! corresponding public key to verify the integrity and authenticity of that
 //! data given the signature.
 //!
+//! The `PrehashSigner` and `PrehashVerifier` work the same way, but operate on a
+//! message digest that has already been computed rather than the data itself.
+//!
 //! # Examples
 //!
 //! Sign and verify data given an RSA keypair:
@@ -70,7 +73,9 @@ use std::ptr;
 
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
-use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
+#[cfg(ossl300)]
+use crate::md::MdRef;
+use crate::pkey::{HasPrivate, HasPublic, PKeyCtx, PKeyRef};
 use crate::rsa::Padding;
 use crate::{cvt, cvt_p};
 
@@ -87,7 +92,7 @@ pub struct RsaPssSaltlen(c_int);
 
 impl RsaPssSaltlen {
     /// Returns the integer representation of `RsaPssSaltlen`.
-    fn as_raw(&self) -> c_int {
+    pub(crate) fn as_raw(&self) -> c_int {
         self.0
     }
 
@@ -140,6 +145,21 @@ impl<'a> Signer<'a> {
         Self::new_intern(Some(type_), pkey)
     }
 
+    /// Creates a new `Signer` using a digest fetched from a provider.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// OpenSSL documentation at [`EVP_DigestSignInit`].
+    ///
+    /// [`EVP_DigestSignInit`]: https://www.openssl.org/docs/manmaster/man3/EVP_DigestSignInit.html
+    #[cfg(ossl300)]
+    pub fn with_md<T>(md: &'a MdRef, pkey: &'a PKeyRef<T>) -> Result<Signer<'a>, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe { Self::new_intern(Some(MessageDigest::from_ptr(md.as_ptr())), pkey) }
+    }
+
     /// Creates a new `Signer` without a digest.
     ///
     /// This is the only way to create a `Verifier` for Ed25519 or Ed448 keys.
@@ -432,6 +452,21 @@ impl<'a> Verifier<'a> {
         Verifier::new_intern(Some(type_), pkey)
     }
 
+    /// Creates a new `Verifier` using a digest fetched from a provider.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// OpenSSL documentation at [`EVP_DigestVerifyInit`].
+    ///
+    /// [`EVP_DigestVerifyInit`]: https://www.openssl.org/docs/manmaster/man3/EVP_DigestVerifyInit.html
+    #[cfg(ossl300)]
+    pub fn with_md<T>(md: &'a MdRef, pkey: &'a PKeyRef<T>) -> Result<Verifier<'a>, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe { Verifier::new_intern(Some(MessageDigest::from_ptr(md.as_ptr())), pkey) }
+    }
+
     /// Creates a new `Verifier` without a digest.
     ///
     /// This is the only way to create a `Verifier` for Ed25519 or Ed448 keys.
@@ -621,6 +656,223 @@ impl<'a> Write for Verifier<'a> {
     }
 }
 
+/// A type which signs a precomputed message digest.
+///
+/// Unlike [`Signer`], the data is not hashed again. The digest algorithm is
+/// only used to encode the signature (e.g. the `DigestInfo` of an RSA PKCS#1
+/// signature) and to check the length of the digest. This supports RSA and
+/// ECDSA keys, but not PureEdDSA keys such as Ed25519 which do not sign
+/// digests.
+///
+/// # Examples
+///
+/// ```rust
+/// use openssl::hash::{hash, MessageDigest};
+/// use openssl::pkey::PKey;
+/// use openssl::rsa::Rsa;
+/// use openssl::sign::{PrehashSigner, Verifier};
+///
+/// let keypair = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+///
+/// let data = b"hello, world!";
+/// let digest = hash(MessageDigest::sha256(), data).unwrap();
+///
+/// let signer = PrehashSigner::new(MessageDigest::sha256(), &keypair).unwrap();
+/// let signature = signer.sign_to_vec(&digest).unwrap();
+///
+/// // The signature is the same as one computed over the full message.
+/// let mut verifier = Verifier::new(MessageDigest::sha256(), &keypair).unwrap();
+/// verifier.update(data).unwrap();
+/// assert!(verifier.verify(&signature).unwrap());
+/// ```
+pub struct PrehashSigner<'a> {
+    ctx: PKeyCtx,
+    _p: PhantomData<&'a ()>,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl<'a> PrehashSigner<'a> {
+    /// Creates a new `PrehashSigner` for digests computed with `type_`.
+    ///
+    /// OpenSSL documentation at [`EVP_PKEY_sign_init`].
+    ///
+    /// [`EVP_PKEY_sign_init`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_sign.html
+    pub fn new<T>(
+        type_: MessageDigest,
+        pkey: &'a PKeyRef<T>,
+    ) -> Result<PrehashSigner<'a>, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        let ctx = PKeyCtx::from_pkey(pkey)?;
+        ctx.sign_init()?;
+        ctx.set_signature_md(type_)?;
+
+        Ok(PrehashSigner {
+            ctx,
+            _p: PhantomData,
+        })
+    }
+
+    /// Returns the RSA padding mode in use.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to `EVP_PKEY_CTX_get_rsa_padding`.
+    pub fn rsa_padding(&self) -> Result<Padding, ErrorStack> {
+        self.ctx.rsa_padding()
+    }
+
+    /// Sets the RSA padding mode.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_padding`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_padding`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_rsa_padding.html
+    pub fn set_rsa_padding(&mut self, padding: Padding) -> Result<(), ErrorStack> {
+        self.ctx.set_rsa_padding(padding)
+    }
+
+    /// Sets the RSA PSS salt length.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_pss_saltlen`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_pss_saltlen`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_rsa_pss_saltlen.html
+    pub fn set_rsa_pss_saltlen(&mut self, len: RsaPssSaltlen) -> Result<(), ErrorStack> {
+        self.ctx.set_rsa_pss_saltlen(len)
+    }
+
+    /// Sets the RSA MGF1 algorithm.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_mgf1_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_mgf1_md`]: https://www.openssl.org/docs/manmaster/man7/RSA-PSS.html
+    pub fn set_rsa_mgf1_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
+        self.ctx.set_rsa_mgf1_md(md)
+    }
+
+    /// Computes an upper bound on the signature length.
+    ///
+    /// The actual signature may be shorter than this value. Check the return value of
+    /// `sign` to get the exact length.
+    ///
+    /// OpenSSL documentation at [`EVP_PKEY_sign`].
+    ///
+    /// [`EVP_PKEY_sign`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_sign.html
+    pub fn len(&self) -> Result<usize, ErrorStack> {
+        self.ctx.sign_len()
+    }
+
+    /// Signs `digest`, writing the signature into the provided buffer, returning the number of
+    /// bytes written.
+    ///
+    /// This method will fail if the buffer is not large enough for the signature. Use the `len`
+    /// method to get an upper bound on the required size.
+    ///
+    /// OpenSSL documentation at [`EVP_PKEY_sign`].
+    ///
+    /// [`EVP_PKEY_sign`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_sign.html
+    pub fn sign(&self, digest: &[u8], buf: &mut [u8]) -> Result<usize, ErrorStack> {
+        self.ctx.sign(digest, buf)
+    }
+
+    /// Signs `digest`, returning the signature.
+    ///
+    /// This is a simple convenience wrapper over `len` and `sign`.
+    pub fn sign_to_vec(&self, digest: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        self.ctx.sign_to_vec(digest)
+    }
+}
+
+/// A type which verifies signatures of a precomputed message digest.
+///
+/// This is the counterpart of [`PrehashSigner`], and accepts any signature
+/// produced by a [`Signer`] over the original message.
+pub struct PrehashVerifier<'a> {
+    ctx: PKeyCtx,
+    _p: PhantomData<&'a ()>,
+}
+
+impl<'a> PrehashVerifier<'a> {
+    /// Creates a new `PrehashVerifier` for digests computed with `type_`.
+    ///
+    /// OpenSSL documentation at [`EVP_PKEY_verify_init`].
+    ///
+    /// [`EVP_PKEY_verify_init`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_verify.html
+    pub fn new<T>(
+        type_: MessageDigest,
+        pkey: &'a PKeyRef<T>,
+    ) -> Result<PrehashVerifier<'a>, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        let ctx = PKeyCtx::from_pkey(pkey)?;
+        ctx.verify_init()?;
+        ctx.set_signature_md(type_)?;
+
+        Ok(PrehashVerifier {
+            ctx,
+            _p: PhantomData,
+        })
+    }
+
+    /// Returns the RSA padding mode in use.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to `EVP_PKEY_CTX_get_rsa_padding`.
+    pub fn rsa_padding(&self) -> Result<Padding, ErrorStack> {
+        self.ctx.rsa_padding()
+    }
+
+    /// Sets the RSA padding mode.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_padding`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_padding`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_rsa_padding.html
+    pub fn set_rsa_padding(&mut self, padding: Padding) -> Result<(), ErrorStack> {
+        self.ctx.set_rsa_padding(padding)
+    }
+
+    /// Sets the RSA PSS salt length.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_pss_saltlen`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_pss_saltlen`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_rsa_pss_saltlen.html
+    pub fn set_rsa_pss_saltlen(&mut self, len: RsaPssSaltlen) -> Result<(), ErrorStack> {
+        self.ctx.set_rsa_pss_saltlen(len)
+    }
+
+    /// Sets the RSA MGF1 algorithm.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_mgf1_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_mgf1_md`]: https://www.openssl.org/docs/manmaster/man7/RSA-PSS.html
+    pub fn set_rsa_mgf1_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
+        self.ctx.set_rsa_mgf1_md(md)
+    }
+
+    /// Determines if `signature` is a valid signature of `digest`.
+    ///
+    /// OpenSSL documentation at [`EVP_PKEY_verify`].
+    ///
+    /// [`EVP_PKEY_verify`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_verify.html
+    pub fn verify(&self, digest: &[u8], signature: &[u8]) -> Result<bool, ErrorStack> {
+        self.ctx.verify(digest, signature)
+    }
+}
+
 #[cfg(not(ossl101))]
 use ffi::EVP_DigestVerifyFinal;
 
@@ -640,13 +892,12 @@ mod test {
     use std::iter;
 
     use crate::ec::{EcGroup, EcKey};
-    use crate::hash::MessageDigest;
+    use crate::hash::{hash, MessageDigest};
     use crate::nid::Nid;
     use crate::pkey::PKey;
     use crate::rsa::{Padding, Rsa};
-    #[cfg(ossl111)]
     use crate::sign::RsaPssSaltlen;
-    use crate::sign::{Signer, Verifier};
+    use crate::sign::{PrehashSigner, PrehashVerifier, Signer, Verifier};
 
     const INPUT: &str =
         "65794a68624763694f694a53557a49314e694a392e65794a7063334d694f694a71623255694c41304b49434a6c\
@@ -700,6 +951,26 @@ mod test {
         assert!(!verifier.verify(&Vec::from_hex(SIGNATURE).unwrap()).unwrap());
     }
 
+    #[test]
+    #[cfg(ossl300)]
+    fn rsa_fetched_md() {
+        use crate::md::Md;
+
+        let key = include_bytes!("../test/rsa.pem");
+        let private_key = Rsa::private_key_from_pem(key).unwrap();
+        let pkey = PKey::from_rsa(private_key).unwrap();
+        let md = Md::fetch("SHA256", Some("provider=default")).unwrap();
+
+        let mut signer = Signer::with_md(&md, &pkey).unwrap();
+        signer.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
+        let result = signer.sign_to_vec().unwrap();
+        assert_eq!(hex::encode(&result), SIGNATURE);
+
+        let mut verifier = Verifier::with_md(&md, &pkey).unwrap();
+        verifier.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
+        assert!(verifier.verify(&result).unwrap());
+    }
+
     fn test_hmac(ty: MessageDigest, tests: &[(Vec<u8>, Vec<u8>, Vec<u8>)]) {
         for &(ref key, ref data, ref res) in tests.iter() {
             let pkey = PKey::hmac(key).unwrap();
@@ -872,4 +1143,73 @@ mod test {
         verifier.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
         assert!(verifier.verify(&signature).unwrap());
     }
+
+    #[test]
+    fn rsa_prehash() {
+        let key = include_bytes!("../test/rsa.pem");
+        let private_key = Rsa::private_key_from_pem(key).unwrap();
+        let pkey = PKey::from_rsa(private_key).unwrap();
+        let input = Vec::from_hex(INPUT).unwrap();
+        let digest = hash(MessageDigest::sha256(), &input).unwrap();
+
+        let signer = PrehashSigner::new(MessageDigest::sha256(), &pkey).unwrap();
+        assert_eq!(signer.rsa_padding().unwrap(), Padding::PKCS1);
+        let signature = signer.sign_to_vec(&digest).unwrap();
+        assert_eq!(hex::encode(&signature), SIGNATURE);
+
+        let verifier = PrehashVerifier::new(MessageDigest::sha256(), &pkey).unwrap();
+        assert!(verifier.verify(&digest, &signature).unwrap());
+        assert!(!verifier.verify(&[0; 32], &signature).unwrap());
+
+        // the digest length must match the digest algorithm
+        assert!(signer.sign_to_vec(&digest[..20]).is_err());
+    }
+
+    #[test]
+    fn rsa_pss_prehash() {
+        let key = include_bytes!("../test/rsa.pem");
+        let private_key = Rsa::private_key_from_pem(key).unwrap();
+        let pkey = PKey::from_rsa(private_key).unwrap();
+        let input = Vec::from_hex(INPUT).unwrap();
+        let digest = hash(MessageDigest::sha256(), &input).unwrap();
+
+        let mut signer = PrehashSigner::new(MessageDigest::sha256(), &pkey).unwrap();
+        signer.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+        signer
+            .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+            .unwrap();
+        signer.set_rsa_mgf1_md(MessageDigest::sha256()).unwrap();
+        let signature = signer.sign_to_vec(&digest).unwrap();
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &pkey).unwrap();
+        verifier.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+        verifier
+            .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+            .unwrap();
+        verifier.set_rsa_mgf1_md(MessageDigest::sha256()).unwrap();
+        verifier.update(&input).unwrap();
+        assert!(verifier.verify(&signature).unwrap());
+    }
+
+    #[test]
+    fn ec_prehash() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        let key = PKey::from_ec_key(key).unwrap();
+        let digest = hash(MessageDigest::sha256(), b"hello world").unwrap();
+
+        let signer = PrehashSigner::new(MessageDigest::sha256(), &key).unwrap();
+        let signature = signer.sign_to_vec(&digest).unwrap();
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &key).unwrap();
+        verifier.update(b"hello world").unwrap();
+        assert!(verifier.verify(&signature).unwrap());
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+        signer.update(b"hello world").unwrap();
+        let signature = signer.sign_to_vec().unwrap();
+
+        let verifier = PrehashVerifier::new(MessageDigest::sha256(), &key).unwrap();
+        assert!(verifier.verify(&digest, &signature).unwrap());
+    }
 }
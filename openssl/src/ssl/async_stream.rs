@@ -0,0 +1,403 @@
+use std::fmt;
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::pin::Pin;
+use std::ptr;
+use std::task::{Context, Poll};
+
+use crate::error::ErrorStack;
+use crate::ssl::{Error, ErrorCode, ShutdownResult, Ssl, SslRef, SslStream};
+
+/// A nonblocking byte stream driven by a task context.
+///
+/// This is the runtime-agnostic counterpart of `Read + Write` used by [`AsyncSslStream`]. An
+/// implementation which cannot make progress must arrange for the waker in `cx` to be notified
+/// once it can, and return `Poll::Pending`.
+pub trait AsyncIo {
+    /// Attempts to read data from the stream into `buf`.
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>>;
+
+    /// Attempts to write data from `buf` into the stream.
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>>;
+
+    /// Attempts to flush any buffered data to the underlying transport.
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+}
+
+impl<S: AsyncIo + ?Sized> AsyncIo for &mut S {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        (**self).poll_read(cx, buf)
+    }
+
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        (**self).poll_write(cx, buf)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        (**self).poll_flush(cx)
+    }
+}
+
+impl<S: AsyncIo + ?Sized> AsyncIo for Box<S> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        (**self).poll_read(cx, buf)
+    }
+
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        (**self).poll_write(cx, buf)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        (**self).poll_flush(cx)
+    }
+}
+
+/// Adapts an `AsyncIo` stream to the blocking `Read + Write` interface expected by the BIO.
+///
+/// The task context is only set for the duration of a call into OpenSSL, and a pending operation
+/// is reported to OpenSSL as `WouldBlock`.
+struct StreamWrapper<S> {
+    stream: S,
+    context: *mut (),
+}
+
+// The context pointer is only non-null while `AsyncSslStream::with_context` mutably borrows the
+// stream, and it is only dereferenced through `&mut self`. A shared reference therefore never
+// exposes the task context, so the wrapper is `Send` and `Sync` whenever the stream is.
+unsafe impl<S: Send> Send for StreamWrapper<S> {}
+unsafe impl<S: Sync> Sync for StreamWrapper<S> {}
+
+/// Clears the task context of a stream when dropped, including when OpenSSL or the stream panics.
+struct ContextGuard<'a, S>(&'a mut SslStream<StreamWrapper<S>>);
+
+impl<'a, S> Drop for ContextGuard<'a, S> {
+    fn drop(&mut self) {
+        self.0.get_mut().context = ptr::null_mut();
+    }
+}
+
+impl<S> StreamWrapper<S>
+where
+    S: AsyncIo,
+{
+    fn with_context<F, T>(&mut self, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut S, &mut Context<'_>) -> Poll<io::Result<T>>,
+    {
+        assert!(!self.context.is_null());
+        let cx = unsafe { &mut *(self.context as *mut Context<'_>) };
+        match f(&mut self.stream, cx) {
+            Poll::Ready(r) => r,
+            Poll::Pending => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+        }
+    }
+}
+
+impl<S: AsyncIo> Read for StreamWrapper<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.with_context(|s, cx| s.poll_read(cx, buf))
+    }
+}
+
+impl<S: AsyncIo> Write for StreamWrapper<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.with_context(|s, cx| s.poll_write(cx, buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.with_context(|s, cx| s.poll_flush(cx))
+    }
+}
+
+/// Converts the result of an OpenSSL operation into a `Poll`.
+///
+/// `WANT_READ` and `WANT_WRITE` errors caused by the underlying stream returning `Poll::Pending`
+/// become `Poll::Pending`. If OpenSSL asks to be retried without the stream having blocked, the
+/// task is woken immediately so that the operation is retried on the next poll.
+fn cvt_poll<T>(cx: &mut Context<'_>, r: Result<T, Error>) -> Poll<Result<T, Error>> {
+    match r {
+        Ok(v) => Poll::Ready(Ok(v)),
+        Err(e) => match e.code() {
+            ErrorCode::WANT_READ | ErrorCode::WANT_WRITE => match e.io_error() {
+                Some(io) if io.kind() == io::ErrorKind::WouldBlock => Poll::Pending,
+                Some(_) => Poll::Ready(Err(e)),
+                None => {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            },
+            _ => Poll::Ready(Err(e)),
+        },
+    }
+}
+
+/// A TLS session over an asynchronous stream.
+///
+/// This drives the same state machine as [`SslStream`], but suspends the current task rather than
+/// returning `WANT_READ` or `WANT_WRITE` errors when the underlying [`AsyncIo`] stream is not
+/// ready. It does not depend on any particular runtime.
+pub struct AsyncSslStream<S>(SslStream<StreamWrapper<S>>);
+
+impl<S> fmt::Debug for AsyncSslStream<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("AsyncSslStream")
+            .field("stream", self.get_ref())
+            .field("ssl", self.ssl())
+            .finish()
+    }
+}
+
+impl<S> AsyncSslStream<S> {
+    /// Returns a shared reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.0.get_ref().stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    ///
+    /// # Warning
+    ///
+    /// It is inadvisable to read from or write to the underlying stream as it
+    /// will most likely corrupt the SSL session.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.0.get_mut().stream
+    }
+
+    /// Returns a shared reference to the `Ssl` object associated with this stream.
+    pub fn ssl(&self) -> &SslRef {
+        self.0.ssl()
+    }
+
+    /// Returns a mutable reference to the `Ssl` object associated with this stream.
+    pub fn ssl_mut(&mut self) -> &mut SslRef {
+        self.0.ssl_mut()
+    }
+}
+
+impl<S: AsyncIo> AsyncSslStream<S> {
+    /// Creates a new `AsyncSslStream`.
+    ///
+    /// Like [`SslStream::new`], this performs no IO. The handshake can be driven explicitly with
+    /// the `connect`, `accept` and `do_handshake` methods or their `poll_*` variants, or
+    /// implicitly by the first read or write if the `Ssl` has been placed in connect or accept
+    /// state.
+    pub fn new(ssl: Ssl, stream: S) -> Result<AsyncSslStream<S>, ErrorStack> {
+        let stream = StreamWrapper {
+            stream,
+            context: ptr::null_mut(),
+        };
+        SslStream::new(ssl, stream).map(AsyncSslStream)
+    }
+
+    fn with_context<F, T>(&mut self, cx: &mut Context<'_>, f: F) -> Poll<Result<T, Error>>
+    where
+        F: FnOnce(&mut SslStream<StreamWrapper<S>>) -> Result<T, Error>,
+    {
+        self.0.get_mut().context = cx as *mut Context<'_> as *mut ();
+        let r = {
+            let guard = ContextGuard(&mut self.0);
+            f(&mut *guard.0)
+        };
+        cvt_poll(cx, r)
+    }
+
+    /// Attempts to make progress on the client side of the handshake.
+    ///
+    /// This corresponds to [`SSL_connect`].
+    ///
+    /// [`SSL_connect`]: https://www.openssl.org/docs/manmaster/man3/SSL_connect.html
+    pub fn poll_connect(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.with_context(cx, |s| s.connect())
+    }
+
+    /// Attempts to make progress on the server side of the handshake.
+    ///
+    /// This corresponds to [`SSL_accept`].
+    ///
+    /// [`SSL_accept`]: https://www.openssl.org/docs/manmaster/man3/SSL_accept.html
+    pub fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.with_context(cx, |s| s.accept())
+    }
+
+    /// Attempts to make progress on the handshake.
+    ///
+    /// This corresponds to [`SSL_do_handshake`].
+    ///
+    /// [`SSL_do_handshake`]: https://www.openssl.org/docs/manmaster/man3/SSL_do_handshake.html
+    pub fn poll_do_handshake(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.with_context(cx, |s| s.do_handshake())
+    }
+
+    /// Attempts to read decrypted data from the stream.
+    ///
+    /// This corresponds to [`SSL_read`].
+    ///
+    /// [`SSL_read`]: https://www.openssl.org/docs/manmaster/man3/SSL_read.html
+    pub fn poll_ssl_read(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Error>> {
+        self.with_context(cx, |s| s.ssl_read(buf))
+    }
+
+    /// Attempts to write data to the stream.
+    ///
+    /// This corresponds to [`SSL_write`].
+    ///
+    /// [`SSL_write`]: https://www.openssl.org/docs/manmaster/man3/SSL_write.html
+    pub fn poll_ssl_write(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        self.with_context(cx, |s| s.ssl_write(buf))
+    }
+
+    /// Attempts to shut down the session.
+    ///
+    /// See [`SslStream::shutdown`] for the meaning of the returned `ShutdownResult`.
+    ///
+    /// This corresponds to [`SSL_shutdown`].
+    ///
+    /// [`SSL_shutdown`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_shutdown.html
+    pub fn poll_shutdown(&mut self, cx: &mut Context<'_>) -> Poll<Result<ShutdownResult, Error>> {
+        self.with_context(cx, |s| s.shutdown())
+    }
+
+    /// Returns a future which performs the client side of the handshake.
+    pub fn connect(&mut self) -> HandshakeFuture<'_, S> {
+        HandshakeFuture {
+            stream: self,
+            f: AsyncSslStream::poll_connect,
+        }
+    }
+
+    /// Returns a future which performs the server side of the handshake.
+    pub fn accept(&mut self) -> HandshakeFuture<'_, S> {
+        HandshakeFuture {
+            stream: self,
+            f: AsyncSslStream::poll_accept,
+        }
+    }
+
+    /// Returns a future which performs the handshake in the `Ssl`'s configured mode.
+    pub fn do_handshake(&mut self) -> HandshakeFuture<'_, S> {
+        HandshakeFuture {
+            stream: self,
+            f: AsyncSslStream::poll_do_handshake,
+        }
+    }
+
+    /// Returns a future which reads decrypted data into `buf`.
+    pub fn ssl_read<'a>(&'a mut self, buf: &'a mut [u8]) -> ReadFuture<'a, S> {
+        ReadFuture { stream: self, buf }
+    }
+
+    /// Returns a future which writes data from `buf`.
+    pub fn ssl_write<'a>(&'a mut self, buf: &'a [u8]) -> WriteFuture<'a, S> {
+        WriteFuture { stream: self, buf }
+    }
+
+    /// Returns a future which shuts down the session.
+    pub fn shutdown(&mut self) -> ShutdownFuture<'_, S> {
+        ShutdownFuture { stream: self }
+    }
+}
+
+impl<S: AsyncIo> AsyncIo for AsyncSslStream<S> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        match self.poll_ssl_read(cx, buf) {
+            Poll::Ready(Ok(n)) => Poll::Ready(Ok(n)),
+            Poll::Ready(Err(ref e)) if e.code() == ErrorCode::ZERO_RETURN => Poll::Ready(Ok(0)),
+            Poll::Ready(Err(ref e)) if e.code() == ErrorCode::SYSCALL && e.io_error().is_none() => {
+                Poll::Ready(Ok(0))
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e
+                .into_io_error()
+                .unwrap_or_else(|e| io::Error::new(io::ErrorKind::Other, e)))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.poll_ssl_write(cx, buf) {
+            Poll::Ready(Ok(n)) => Poll::Ready(Ok(n)),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e
+                .into_io_error()
+                .unwrap_or_else(|e| io::Error::new(io::ErrorKind::Other, e)))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_flush(cx)
+    }
+}
+
+/// A future performing a TLS handshake, returned by [`AsyncSslStream::connect`],
+/// [`AsyncSslStream::accept`] and [`AsyncSslStream::do_handshake`].
+#[must_use = "futures do nothing unless polled"]
+pub struct HandshakeFuture<'a, S> {
+    stream: &'a mut AsyncSslStream<S>,
+    #[allow(clippy::type_complexity)]
+    f: fn(&mut AsyncSslStream<S>, &mut Context<'_>) -> Poll<Result<(), Error>>,
+}
+
+impl<'a, S: AsyncIo> Future for HandshakeFuture<'a, S> {
+    type Output = Result<(), Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        (this.f)(this.stream, cx)
+    }
+}
+
+/// A future reading from an [`AsyncSslStream`], returned by [`AsyncSslStream::ssl_read`].
+#[must_use = "futures do nothing unless polled"]
+pub struct ReadFuture<'a, S> {
+    stream: &'a mut AsyncSslStream<S>,
+    buf: &'a mut [u8],
+}
+
+impl<'a, S: AsyncIo> Future for ReadFuture<'a, S> {
+    type Output = Result<usize, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        this.stream.poll_ssl_read(cx, this.buf)
+    }
+}
+
+/// A future writing to an [`AsyncSslStream`], returned by [`AsyncSslStream::ssl_write`].
+#[must_use = "futures do nothing unless polled"]
+pub struct WriteFuture<'a, S> {
+    stream: &'a mut AsyncSslStream<S>,
+    buf: &'a [u8],
+}
+
+impl<'a, S: AsyncIo> Future for WriteFuture<'a, S> {
+    type Output = Result<usize, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        this.stream.poll_ssl_write(cx, this.buf)
+    }
+}
+
+/// A future shutting down an [`AsyncSslStream`], returned by [`AsyncSslStream::shutdown`].
+#[must_use = "futures do nothing unless polled"]
+pub struct ShutdownFuture<'a, S> {
+    stream: &'a mut AsyncSslStream<S>,
+}
+
+impl<'a, S: AsyncIo> Future for ShutdownFuture<'a, S> {
+    type Output = Result<ShutdownResult, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.stream.poll_shutdown(cx)
+    }
+}
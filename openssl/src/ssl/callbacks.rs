@@ -3,9 +3,7 @@ use foreign_types::ForeignType;
 use foreign_types::ForeignTypeRef;
 #[cfg(any(ossl111, not(osslconf = "OPENSSL_NO_PSK")))]
 use libc::c_char;
-#[cfg(ossl111)]
-use libc::size_t;
-use libc::{c_int, c_uchar, c_uint, c_void};
+use libc::{c_int, c_uchar, c_uint, c_void, size_t};
 #[cfg(any(ossl111, not(osslconf = "OPENSSL_NO_PSK")))]
 use std::ffi::CStr;
 use std::mem;
@@ -23,11 +21,14 @@ use crate::pkey::Params;
 #[cfg(any(ossl102, libressl261))]
 use crate::ssl::AlpnError;
 use crate::ssl::{
-    try_get_session_ctx_index, SniError, Ssl, SslAlert, SslContext, SslContextRef, SslRef,
-    SslSession, SslSessionRef,
+    try_get_session_ctx_index, SniError, Ssl, SslAlert, SslAlertLevel, SslContentType, SslContext,
+    SslContextRef, SslInfoCallbackMode, SslInfoCallbackValue, SslMessageDirection, SslRef,
+    SslSession, SslSessionRef, SslVersion, TicketKeyContext, TicketKeyStatus, TicketMacCtx,
 };
 #[cfg(ossl111)]
-use crate::ssl::{ClientHelloResponse, ExtensionContext};
+use crate::ssl::{
+    ClientHelloResponse, ExtensionContext, SessionTicketResponse, SessionTicketStatus,
+};
 #[cfg(ossl111)]
 use crate::util::ForeignTypeRefExt;
 #[cfg(ossl111)]
@@ -387,6 +388,121 @@ pub unsafe extern "C" fn raw_remove_session<F>(
     callback(ctx, session)
 }
 
+pub unsafe extern "C" fn raw_ticket_key<F>(
+    ssl: *mut ffi::SSL,
+    key_name: *mut c_uchar,
+    iv: *mut c_uchar,
+    cipher_ctx: *mut ffi::EVP_CIPHER_CTX,
+    mac_ctx: *mut TicketMacCtx,
+    enc: c_int,
+) -> c_int
+where
+    F: Fn(&mut SslRef, &mut TicketKeyContext<'_>) -> Result<TicketKeyStatus, ErrorStack>
+        + 'static
+        + Sync
+        + Send,
+{
+    let session_ctx_index =
+        try_get_session_ctx_index().expect("BUG: session context index initialization failed");
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ex_data(*session_ctx_index)
+        .expect("BUG: session context missing")
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: ticket key callback missing") as *const F;
+    let mut ctx = TicketKeyContext {
+        key_name: &mut *(key_name as *mut [u8; 16]),
+        iv: &mut *(iv as *mut [u8; ffi::EVP_MAX_IV_LENGTH as usize]),
+        cipher_ctx,
+        mac_ctx,
+        encrypt: enc == 1,
+        initialized: false,
+    };
+
+    match (*callback)(ssl, &mut ctx) {
+        Ok(TicketKeyStatus::NOT_FOUND) => 0,
+        Ok(_) if !ctx.initialized => -1,
+        // renewal only applies to decryption
+        Ok(_) if ctx.encrypt => 1,
+        Ok(status) => status.0,
+        Err(e) => {
+            e.put();
+            -1
+        }
+    }
+}
+
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_generate_session_ticket<F>(ssl: *mut ffi::SSL, _: *mut c_void) -> c_int
+where
+    F: Fn(&mut SslRef) -> Result<Vec<u8>, ErrorStack> + 'static + Sync + Send,
+{
+    let session_ctx_index =
+        try_get_session_ctx_index().expect("BUG: session context index initialization failed");
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ex_data(*session_ctx_index)
+        .expect("BUG: session context missing")
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: session ticket generate callback missing") as *const F;
+
+    match (*callback)(ssl) {
+        Ok(data) => ffi::SSL_SESSION_set1_ticket_appdata(
+            ffi::SSL_get_session(ssl.as_ptr()),
+            data.as_ptr() as *const c_void,
+            data.len(),
+        ),
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_decrypt_session_ticket<F>(
+    ssl: *mut ffi::SSL,
+    session: *mut ffi::SSL_SESSION,
+    key_name: *const c_uchar,
+    key_name_len: size_t,
+    status: ffi::SSL_TICKET_STATUS,
+    _: *mut c_void,
+) -> ffi::SSL_TICKET_RETURN
+where
+    F: Fn(
+            &mut SslRef,
+            Option<&SslSessionRef>,
+            &[u8],
+            SessionTicketStatus,
+        ) -> Result<SessionTicketResponse, ErrorStack>
+        + 'static
+        + Sync
+        + Send,
+{
+    let session_ctx_index =
+        try_get_session_ctx_index().expect("BUG: session context index initialization failed");
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ex_data(*session_ctx_index)
+        .expect("BUG: session context missing")
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: session ticket decrypt callback missing") as *const F;
+    let session = SslSessionRef::from_const_ptr_opt(session);
+    let key_name = if key_name.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(key_name, key_name_len)
+    };
+
+    match (*callback)(ssl, session, key_name, SessionTicketStatus(status)) {
+        Ok(response) => response.0,
+        Err(e) => {
+            e.put();
+            ffi::SSL_TICKET_RETURN_ABORT
+        }
+    }
+}
+
 cfg_if! {
     if #[cfg(any(ossl110, libressl280))] {
         type DataPtr = *const c_uchar;
@@ -693,3 +809,109 @@ where
         }
     }
 }
+
+fn info_callback_args(where_: c_int, ret: c_int) -> (SslInfoCallbackMode, SslInfoCallbackValue) {
+    let mode = SslInfoCallbackMode::from_bits_truncate(where_);
+    let value = if mode.contains(SslInfoCallbackMode::ALERT) {
+        SslInfoCallbackValue::Alert(SslAlertLevel(ret >> 8), SslAlert(ret & 0xff))
+    } else {
+        SslInfoCallbackValue::Return(ret)
+    };
+    (mode, value)
+}
+
+pub unsafe extern "C" fn raw_info<F>(ssl: *const ffi::SSL, where_: c_int, ret: c_int)
+where
+    F: Fn(&SslRef, SslInfoCallbackMode, SslInfoCallbackValue) + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_const_ptr(ssl);
+    let callback = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: info callback missing");
+    let (mode, value) = info_callback_args(where_, ret);
+
+    callback(ssl, mode, value);
+}
+
+pub unsafe extern "C" fn ssl_raw_info<F>(ssl: *const ffi::SSL, where_: c_int, ret: c_int)
+where
+    F: Fn(&SslRef, SslInfoCallbackMode, SslInfoCallbackValue) + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_const_ptr(ssl);
+    // the callback may replace itself, so hold onto our own reference
+    let callback = ssl
+        .ex_data(Ssl::cached_ex_index::<Arc<F>>())
+        .expect("BUG: ssl info callback missing")
+        .clone();
+    let (mode, value) = info_callback_args(where_, ret);
+
+    callback(ssl, mode, value);
+}
+
+unsafe fn msg_callback_args<'a>(
+    write_p: c_int,
+    version: c_int,
+    content_type: c_int,
+    buf: *const c_void,
+    len: size_t,
+) -> (SslMessageDirection, SslVersion, SslContentType, &'a [u8]) {
+    let direction = if write_p == 0 {
+        SslMessageDirection::Received
+    } else {
+        SslMessageDirection::Sent
+    };
+    let buf = if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(buf as *const u8, len)
+    };
+    (
+        direction,
+        SslVersion(version),
+        SslContentType(content_type),
+        buf,
+    )
+}
+
+pub unsafe extern "C" fn raw_msg<F>(
+    write_p: c_int,
+    version: c_int,
+    content_type: c_int,
+    buf: *const c_void,
+    len: size_t,
+    ssl: *mut ffi::SSL,
+    arg: *mut c_void,
+) where
+    F: Fn(&SslRef, SslMessageDirection, SslVersion, SslContentType, &[u8]) + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr(ssl);
+    let callback = &*(arg as *const F);
+    let (direction, version, content_type, buf) =
+        msg_callback_args(write_p, version, content_type, buf, len);
+
+    callback(ssl, direction, version, content_type, buf);
+}
+
+pub unsafe extern "C" fn ssl_raw_msg<F>(
+    write_p: c_int,
+    version: c_int,
+    content_type: c_int,
+    buf: *const c_void,
+    len: size_t,
+    ssl: *mut ffi::SSL,
+    _: *mut c_void,
+) where
+    F: Fn(&SslRef, SslMessageDirection, SslVersion, SslContentType, &[u8]) + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr(ssl);
+    // the callback may replace itself, so hold onto our own reference
+    let callback = ssl
+        .ex_data(Ssl::cached_ex_index::<Arc<F>>())
+        .expect("BUG: ssl message callback missing")
+        .clone();
+    let (direction, version, content_type, buf) =
+        msg_callback_args(write_p, version, content_type, buf, len);
+
+    callback(ssl, direction, version, content_type, buf);
+}
@@ -61,10 +61,8 @@ use bitflags::bitflags;
 use cfg_if::cfg_if;
 use foreign_types::{ForeignType, ForeignTypeRef, Opaque};
 use libc::{c_char, c_int, c_long, c_uchar, c_uint, c_void};
-use once_cell::sync::{Lazy, OnceCell};
-use std::any::TypeId;
+use once_cell::sync::OnceCell;
 use std::cmp;
-use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::io;
@@ -77,24 +75,27 @@ use std::path::Path;
 use std::ptr;
 use std::slice;
 use std::str;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
 use crate::dh::{Dh, DhRef};
 #[cfg(all(ossl101, not(ossl110)))]
 use crate::ec::EcKey;
 use crate::ec::EcKeyRef;
 use crate::error::ErrorStack;
-use crate::ex_data::Index;
-#[cfg(ossl111)]
+use crate::ex_data::{self, free_data_box, Index};
 use crate::hash::MessageDigest;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
 #[cfg(ossl110)]
 use crate::nid::Nid;
 use crate::pkey::{HasPrivate, PKeyRef, Params, Private};
+use crate::rand::rand_bytes;
 use crate::srtp::{SrtpProtectionProfile, SrtpProtectionProfileRef};
 use crate::ssl::bio::BioMethod;
 use crate::ssl::callbacks::*;
 use crate::ssl::error::InnerError;
 use crate::stack::{Stack, StackRef};
+use crate::symm::Cipher;
 use crate::util::{ForeignTypeExt, ForeignTypeRefExt};
 use crate::x509::store::{X509Store, X509StoreBuilderRef, X509StoreRef};
 #[cfg(any(ossl102, libressl261))]
@@ -102,11 +103,15 @@ use crate::x509::verify::X509VerifyParamRef;
 use crate::x509::{X509Name, X509Ref, X509StoreContextRef, X509VerifyResult, X509};
 use crate::{cvt, cvt_n, cvt_p, init};
 
+pub use crate::ssl::async_stream::{
+    AsyncIo, AsyncSslStream, HandshakeFuture, ReadFuture, ShutdownFuture, WriteFuture,
+};
 pub use crate::ssl::connector::{
     ConnectConfiguration, SslAcceptor, SslAcceptorBuilder, SslConnector, SslConnectorBuilder,
 };
 pub use crate::ssl::error::{Error, ErrorCode, HandshakeError};
 
+mod async_stream;
 mod bio;
 mod callbacks;
 mod connector;
@@ -386,6 +391,20 @@ bitflags! {
         ///
         /// This should be paired with `SSL_VERIFY_PEER`. It has no effect on the client side.
         const FAIL_IF_NO_PEER_CERT = ffi::SSL_VERIFY_FAIL_IF_NO_PEER_CERT;
+
+        /// On the server side, only request a client certificate during the initial handshake.
+        ///
+        /// This should be paired with `SSL_VERIFY_PEER`. It has no effect on the client side.
+        const CLIENT_ONCE = ffi::SSL_VERIFY_CLIENT_ONCE;
+
+        /// On the server side, do not request a client certificate during a TLS 1.3 handshake,
+        /// deferring it to `SslRef::verify_client_post_handshake`.
+        ///
+        /// This should be paired with `SSL_VERIFY_PEER`. It has no effect on the client side.
+        ///
+        /// Requires OpenSSL 1.1.1 or newer.
+        #[cfg(ossl111)]
+        const POST_HANDSHAKE = ffi::SSL_VERIFY_POST_HANDSHAKE;
     }
 }
 
@@ -522,27 +541,12 @@ impl NameType {
     }
 }
 
-static INDEXES: Lazy<Mutex<HashMap<TypeId, c_int>>> = Lazy::new(|| Mutex::new(HashMap::new()));
-static SSL_INDEXES: Lazy<Mutex<HashMap<TypeId, c_int>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 static SESSION_CTX_INDEX: OnceCell<Index<Ssl, SslContext>> = OnceCell::new();
 
 fn try_get_session_ctx_index() -> Result<&'static Index<Ssl, SslContext>, ErrorStack> {
     SESSION_CTX_INDEX.get_or_try_init(Ssl::new_ex_index)
 }
 
-unsafe extern "C" fn free_data_box<T>(
-    _parent: *mut c_void,
-    ptr: *mut c_void,
-    _ad: *mut ffi::CRYPTO_EX_DATA,
-    _idx: c_int,
-    _argl: c_long,
-    _argp: *mut c_void,
-) {
-    if !ptr.is_null() {
-        Box::<T>::from_raw(ptr as *mut T);
-    }
-}
-
 /// An error returned from the SNI callback.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct SniError(c_int);
@@ -566,6 +570,220 @@ impl SslAlert {
     pub const UNRECOGNIZED_NAME: SslAlert = SslAlert(ffi::SSL_AD_UNRECOGNIZED_NAME);
     pub const ILLEGAL_PARAMETER: SslAlert = SslAlert(ffi::SSL_AD_ILLEGAL_PARAMETER);
     pub const DECODE_ERROR: SslAlert = SslAlert(ffi::SSL_AD_DECODE_ERROR);
+    pub const CLOSE_NOTIFY: SslAlert = SslAlert(ffi::SSL_AD_CLOSE_NOTIFY);
+    pub const UNEXPECTED_MESSAGE: SslAlert = SslAlert(ffi::SSL_AD_UNEXPECTED_MESSAGE);
+    pub const BAD_RECORD_MAC: SslAlert = SslAlert(ffi::SSL_AD_BAD_RECORD_MAC);
+    pub const DECRYPTION_FAILED: SslAlert = SslAlert(ffi::SSL_AD_DECRYPTION_FAILED);
+    pub const RECORD_OVERFLOW: SslAlert = SslAlert(ffi::SSL_AD_RECORD_OVERFLOW);
+    pub const DECOMPRESSION_FAILURE: SslAlert = SslAlert(ffi::SSL_AD_DECOMPRESSION_FAILURE);
+    pub const HANDSHAKE_FAILURE: SslAlert = SslAlert(ffi::SSL_AD_HANDSHAKE_FAILURE);
+    pub const NO_CERTIFICATE: SslAlert = SslAlert(ffi::SSL_AD_NO_CERTIFICATE);
+    pub const BAD_CERTIFICATE: SslAlert = SslAlert(ffi::SSL_AD_BAD_CERTIFICATE);
+    pub const UNSUPPORTED_CERTIFICATE: SslAlert = SslAlert(ffi::SSL_AD_UNSUPPORTED_CERTIFICATE);
+    pub const CERTIFICATE_REVOKED: SslAlert = SslAlert(ffi::SSL_AD_CERTIFICATE_REVOKED);
+    pub const CERTIFICATE_EXPIRED: SslAlert = SslAlert(ffi::SSL_AD_CERTIFICATE_EXPIRED);
+    pub const CERTIFICATE_UNKNOWN: SslAlert = SslAlert(ffi::SSL_AD_CERTIFICATE_UNKNOWN);
+    pub const UNKNOWN_CA: SslAlert = SslAlert(ffi::SSL_AD_UNKNOWN_CA);
+    pub const ACCESS_DENIED: SslAlert = SslAlert(ffi::SSL_AD_ACCESS_DENIED);
+    pub const DECRYPT_ERROR: SslAlert = SslAlert(ffi::SSL_AD_DECRYPT_ERROR);
+    pub const EXPORT_RESTRICTION: SslAlert = SslAlert(ffi::SSL_AD_EXPORT_RESTRICTION);
+    pub const PROTOCOL_VERSION: SslAlert = SslAlert(ffi::SSL_AD_PROTOCOL_VERSION);
+    pub const INSUFFICIENT_SECURITY: SslAlert = SslAlert(ffi::SSL_AD_INSUFFICIENT_SECURITY);
+    pub const INTERNAL_ERROR: SslAlert = SslAlert(ffi::SSL_AD_INTERNAL_ERROR);
+    pub const INAPPROPRIATE_FALLBACK: SslAlert = SslAlert(ffi::SSL_AD_INAPPROPRIATE_FALLBACK);
+    pub const USER_CANCELLED: SslAlert = SslAlert(ffi::SSL_AD_USER_CANCELLED);
+    pub const NO_RENEGOTIATION: SslAlert = SslAlert(ffi::SSL_AD_NO_RENEGOTIATION);
+    #[cfg(ossl111)]
+    pub const MISSING_EXTENSION: SslAlert = SslAlert(ffi::SSL_AD_MISSING_EXTENSION);
+    pub const UNSUPPORTED_EXTENSION: SslAlert = SslAlert(ffi::SSL_AD_UNSUPPORTED_EXTENSION);
+    pub const CERTIFICATE_UNOBTAINABLE: SslAlert = SslAlert(ffi::SSL_AD_CERTIFICATE_UNOBTAINABLE);
+    pub const BAD_CERTIFICATE_STATUS_RESPONSE: SslAlert =
+        SslAlert(ffi::SSL_AD_BAD_CERTIFICATE_STATUS_RESPONSE);
+    pub const BAD_CERTIFICATE_HASH_VALUE: SslAlert =
+        SslAlert(ffi::SSL_AD_BAD_CERTIFICATE_HASH_VALUE);
+    pub const UNKNOWN_PSK_IDENTITY: SslAlert = SslAlert(ffi::SSL_AD_UNKNOWN_PSK_IDENTITY);
+    #[cfg(ossl111)]
+    pub const CERTIFICATE_REQUIRED: SslAlert = SslAlert(ffi::SSL_AD_CERTIFICATE_REQUIRED);
+    #[cfg(ossl102)]
+    pub const NO_APPLICATION_PROTOCOL: SslAlert = SslAlert(ffi::SSL_AD_NO_APPLICATION_PROTOCOL);
+
+    /// Constructs an `SslAlert` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> SslAlert {
+        SslAlert(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+
+    /// Returns a two letter string describing the alert.
+    ///
+    /// This corresponds to [`SSL_alert_desc_string`].
+    ///
+    /// [`SSL_alert_desc_string`]: https://www.openssl.org/docs/manmaster/man3/SSL_alert_desc_string.html
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn description_string(&self) -> &'static str {
+        unsafe { alert_str(ffi::SSL_alert_desc_string(self.0)) }
+    }
+
+    /// Returns a longer string describing the alert.
+    ///
+    /// This corresponds to [`SSL_alert_desc_string_long`].
+    ///
+    /// [`SSL_alert_desc_string_long`]: https://www.openssl.org/docs/manmaster/man3/SSL_alert_desc_string.html
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn description_string_long(&self) -> &'static str {
+        unsafe { alert_str(ffi::SSL_alert_desc_string_long(self.0)) }
+    }
+}
+
+/// The level of an SSL/TLS alert.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslAlertLevel(c_int);
+
+impl SslAlertLevel {
+    /// A warning alert.
+    pub const WARNING: SslAlertLevel = SslAlertLevel(ffi::SSL3_AL_WARNING);
+
+    /// A fatal alert, after which the connection is terminated.
+    pub const FATAL: SslAlertLevel = SslAlertLevel(ffi::SSL3_AL_FATAL);
+
+    /// Constructs an `SslAlertLevel` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> SslAlertLevel {
+        SslAlertLevel(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+
+    /// Returns a longer string describing the alert level.
+    ///
+    /// This corresponds to [`SSL_alert_type_string_long`].
+    ///
+    /// [`SSL_alert_type_string_long`]: https://www.openssl.org/docs/manmaster/man3/SSL_alert_type_string.html
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn level_string_long(&self) -> &'static str {
+        unsafe { alert_str(ffi::SSL_alert_type_string_long(self.0 << 8)) }
+    }
+}
+
+unsafe fn alert_str(ptr: *const c_char) -> &'static str {
+    str::from_utf8(CStr::from_ptr(ptr).to_bytes()).unwrap()
+}
+
+bitflags! {
+    /// The events reported to an info callback.
+    pub struct SslInfoCallbackMode: c_int {
+        /// A state transition within the handshake.
+        const LOOP = ffi::SSL_CB_LOOP;
+
+        /// The handshake function is returning.
+        const EXIT = ffi::SSL_CB_EXIT;
+
+        /// A read operation.
+        const READ = ffi::SSL_CB_READ;
+
+        /// A write operation.
+        const WRITE = ffi::SSL_CB_WRITE;
+
+        /// An alert.
+        const ALERT = ffi::SSL_CB_ALERT;
+
+        /// An alert was received.
+        const READ_ALERT = ffi::SSL_CB_READ_ALERT;
+
+        /// An alert was sent.
+        const WRITE_ALERT = ffi::SSL_CB_WRITE_ALERT;
+
+        /// The event occurred in a client handshake.
+        const CONNECT = ffi::SSL_ST_CONNECT;
+
+        /// The event occurred in a server handshake.
+        const ACCEPT = ffi::SSL_ST_ACCEPT;
+
+        /// A state transition within a server handshake.
+        const ACCEPT_LOOP = ffi::SSL_CB_ACCEPT_LOOP;
+
+        /// A server handshake function is returning.
+        const ACCEPT_EXIT = ffi::SSL_CB_ACCEPT_EXIT;
+
+        /// A state transition within a client handshake.
+        const CONNECT_LOOP = ffi::SSL_CB_CONNECT_LOOP;
+
+        /// A client handshake function is returning.
+        const CONNECT_EXIT = ffi::SSL_CB_CONNECT_EXIT;
+
+        /// A handshake has started.
+        const HANDSHAKE_START = ffi::SSL_CB_HANDSHAKE_START;
+
+        /// A handshake has completed.
+        const HANDSHAKE_DONE = ffi::SSL_CB_HANDSHAKE_DONE;
+    }
+}
+
+/// The value passed to an info callback.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SslInfoCallbackValue {
+    /// The return code of the handshake function for `EXIT` events, which is 0 or negative if an
+    /// error occurred. Other events report 1.
+    Return(i32),
+    /// The level and description of an alert, for `ALERT` events.
+    Alert(SslAlertLevel, SslAlert),
+}
+
+/// The direction of a message passed to a message callback.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SslMessageDirection {
+    /// The message was sent to the peer.
+    Sent,
+    /// The message was received from the peer.
+    Received,
+}
+
+/// The content type of a message passed to a message callback.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslContentType(c_int);
+
+impl SslContentType {
+    /// A change cipher spec message.
+    pub const CHANGE_CIPHER_SPEC: SslContentType = SslContentType(ffi::SSL3_RT_CHANGE_CIPHER_SPEC);
+
+    /// An alert message.
+    pub const ALERT: SslContentType = SslContentType(ffi::SSL3_RT_ALERT);
+
+    /// A handshake message.
+    pub const HANDSHAKE: SslContentType = SslContentType(ffi::SSL3_RT_HANDSHAKE);
+
+    /// Application data.
+    pub const APPLICATION_DATA: SslContentType = SslContentType(ffi::SSL3_RT_APPLICATION_DATA);
+
+    /// A record header.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    pub const HEADER: SslContentType = SslContentType(ffi::SSL3_RT_HEADER);
+
+    /// The inner content type of an encrypted TLS 1.3 record.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub const INNER_CONTENT_TYPE: SslContentType = SslContentType(ffi::SSL3_RT_INNER_CONTENT_TYPE);
+
+    /// Constructs an `SslContentType` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> SslContentType {
+        SslContentType(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
 }
 
 /// An error returned from an ALPN selection callback.
@@ -603,6 +821,289 @@ impl ClientHelloResponse {
     pub const RETRY: ClientHelloResponse = ClientHelloResponse(ffi::SSL_CLIENT_HELLO_RETRY);
 }
 
+/// The type of a TLS 1.3 key update.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyUpdateType(c_int);
+
+#[cfg(ossl111)]
+impl KeyUpdateType {
+    /// No key update is pending.
+    pub const NONE: KeyUpdateType = KeyUpdateType(ffi::SSL_KEY_UPDATE_NONE);
+
+    /// Update the local keys without requesting that the peer update its own.
+    pub const NOT_REQUESTED: KeyUpdateType = KeyUpdateType(ffi::SSL_KEY_UPDATE_NOT_REQUESTED);
+
+    /// Update the local keys and request that the peer update its own.
+    pub const REQUESTED: KeyUpdateType = KeyUpdateType(ffi::SSL_KEY_UPDATE_REQUESTED);
+
+    /// Constructs a `KeyUpdateType` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> KeyUpdateType {
+        KeyUpdateType(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// Key material used to encrypt and authenticate session tickets.
+///
+/// Each key is identified by a 16 byte name which is stored unencrypted in the tickets it
+/// protects, allowing a server to find the right key when a ticket is presented for resumption.
+#[derive(Clone)]
+pub struct TicketKey {
+    name: [u8; 16],
+    cipher: Cipher,
+    cipher_key: Vec<u8>,
+    digest: MessageDigest,
+    hmac_key: Vec<u8>,
+}
+
+impl TicketKey {
+    /// Creates a new ticket key.
+    ///
+    /// Tickets are encrypted with `cipher` using `cipher_key`, and authenticated with HMAC using
+    /// `digest` and `hmac_key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cipher_key` is not the key length of `cipher`, or if `cipher` uses an IV longer
+    /// than 16 bytes.
+    pub fn new(
+        name: [u8; 16],
+        cipher: Cipher,
+        cipher_key: &[u8],
+        digest: MessageDigest,
+        hmac_key: &[u8],
+    ) -> TicketKey {
+        assert_eq!(cipher_key.len(), cipher.key_len());
+        assert!(cipher.iv_len().unwrap_or(0) <= ffi::EVP_MAX_IV_LENGTH as usize);
+
+        TicketKey {
+            name,
+            cipher,
+            cipher_key: cipher_key.to_vec(),
+            digest,
+            hmac_key: hmac_key.to_vec(),
+        }
+    }
+
+    /// Returns the name of the key.
+    pub fn name(&self) -> &[u8; 16] {
+        &self.name
+    }
+}
+
+cfg_if! {
+    if #[cfg(ossl300)] {
+        type TicketMacCtx = ffi::EVP_MAC_CTX;
+    } else {
+        type TicketMacCtx = ffi::HMAC_CTX;
+    }
+}
+
+/// The state passed to a session ticket key callback.
+///
+/// The callback is invoked both when a ticket is issued and when a ticket is presented by a client.
+/// In either case it selects the key to use by calling [`init`].
+///
+/// [`init`]: #method.init
+pub struct TicketKeyContext<'a> {
+    key_name: &'a mut [u8; 16],
+    iv: &'a mut [u8; ffi::EVP_MAX_IV_LENGTH as usize],
+    cipher_ctx: *mut ffi::EVP_CIPHER_CTX,
+    mac_ctx: *mut TicketMacCtx,
+    encrypt: bool,
+    initialized: bool,
+}
+
+impl<'a> TicketKeyContext<'a> {
+    /// Returns `true` if a new ticket is being issued, and `false` if a ticket presented by the
+    /// client is being decrypted.
+    pub fn is_encrypt(&self) -> bool {
+        self.encrypt
+    }
+
+    /// Returns the name of the key which protects the ticket being decrypted.
+    ///
+    /// When a ticket is being issued, this is not meaningful until [`init`] has been called.
+    ///
+    /// [`init`]: #method.init
+    pub fn key_name(&self) -> &[u8; 16] {
+        self.key_name
+    }
+
+    /// Selects the key used to protect the ticket.
+    ///
+    /// When a ticket is being issued, the key's name is recorded in the ticket and a random IV is
+    /// generated.
+    pub fn init(&mut self, key: &TicketKey) -> Result<(), ErrorStack> {
+        unsafe {
+            if self.encrypt {
+                *self.key_name = key.name;
+                let iv_len = key.cipher.iv_len().unwrap_or(0);
+                rand_bytes(&mut self.iv[..iv_len])?;
+                cvt(ffi::EVP_EncryptInit_ex(
+                    self.cipher_ctx,
+                    key.cipher.as_ptr(),
+                    ptr::null_mut(),
+                    key.cipher_key.as_ptr(),
+                    self.iv.as_ptr(),
+                ))?;
+            } else {
+                cvt(ffi::EVP_DecryptInit_ex(
+                    self.cipher_ctx,
+                    key.cipher.as_ptr(),
+                    ptr::null_mut(),
+                    key.cipher_key.as_ptr(),
+                    self.iv.as_ptr(),
+                ))?;
+            }
+            self.init_mac(key)?;
+        }
+
+        self.initialized = true;
+        Ok(())
+    }
+
+    #[cfg(ossl300)]
+    unsafe fn init_mac(&mut self, key: &TicketKey) -> Result<(), ErrorStack> {
+        let digest = CString::new(key.digest.type_().short_name()?).unwrap();
+        let params = [
+            ffi::OSSL_PARAM_construct_octet_string(
+                b"key\0".as_ptr() as *const c_char,
+                key.hmac_key.as_ptr() as *mut c_void,
+                key.hmac_key.len(),
+            ),
+            ffi::OSSL_PARAM_construct_utf8_string(
+                b"digest\0".as_ptr() as *const c_char,
+                digest.as_ptr() as *mut c_char,
+                0,
+            ),
+            ffi::OSSL_PARAM_construct_end(),
+        ];
+        cvt(ffi::EVP_MAC_CTX_set_params(self.mac_ctx, params.as_ptr())).map(|_| ())
+    }
+
+    #[cfg(not(ossl300))]
+    unsafe fn init_mac(&mut self, key: &TicketKey) -> Result<(), ErrorStack> {
+        assert!(key.hmac_key.len() <= c_int::max_value() as usize);
+        cvt(ffi::HMAC_Init_ex(
+            self.mac_ctx,
+            key.hmac_key.as_ptr() as *const c_void,
+            key.hmac_key.len() as c_int,
+            key.digest.as_ptr(),
+            ptr::null_mut(),
+        ))
+        .map(|_| ())
+    }
+}
+
+/// The result of a session ticket key callback.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TicketKeyStatus(c_int);
+
+impl TicketKeyStatus {
+    /// No key is available.
+    ///
+    /// When a ticket is being issued, no ticket will be sent. When a ticket is being decrypted, it
+    /// is rejected and a full handshake is performed.
+    pub const NOT_FOUND: TicketKeyStatus = TicketKeyStatus(0);
+
+    /// The key selected by [`TicketKeyContext::init`] should be used.
+    ///
+    /// [`TicketKeyContext::init`]: struct.TicketKeyContext.html#method.init
+    pub const SUCCESS: TicketKeyStatus = TicketKeyStatus(1);
+
+    /// The ticket was decrypted, but a new ticket should be issued to the client.
+    ///
+    /// This is typically returned when the ticket was protected by a key which is being rotated
+    /// out. It is treated like `SUCCESS` when a ticket is being issued.
+    pub const RENEW: TicketKeyStatus = TicketKeyStatus(2);
+}
+
+/// The outcome of decrypting a session ticket presented by a client.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SessionTicketStatus(c_int);
+
+#[cfg(ossl111)]
+impl SessionTicketStatus {
+    /// The ticket could not be decrypted due to a memory allocation failure.
+    pub const FATAL_ERR_MALLOC: SessionTicketStatus =
+        SessionTicketStatus(ffi::SSL_TICKET_FATAL_ERR_MALLOC);
+
+    /// The ticket could not be decrypted due to an internal error.
+    pub const FATAL_ERR_OTHER: SessionTicketStatus =
+        SessionTicketStatus(ffi::SSL_TICKET_FATAL_ERR_OTHER);
+
+    /// The client did not present a ticket.
+    pub const NONE: SessionTicketStatus = SessionTicketStatus(ffi::SSL_TICKET_NONE);
+
+    /// The client presented an empty ticket.
+    pub const EMPTY: SessionTicketStatus = SessionTicketStatus(ffi::SSL_TICKET_EMPTY);
+
+    /// The ticket could not be decrypted, for example because its key is not known.
+    pub const NO_DECRYPT: SessionTicketStatus = SessionTicketStatus(ffi::SSL_TICKET_NO_DECRYPT);
+
+    /// The ticket was decrypted successfully.
+    pub const SUCCESS: SessionTicketStatus = SessionTicketStatus(ffi::SSL_TICKET_SUCCESS);
+
+    /// The ticket was decrypted successfully, and the ticket key callback asked for it to be
+    /// renewed.
+    pub const SUCCESS_RENEW: SessionTicketStatus =
+        SessionTicketStatus(ffi::SSL_TICKET_SUCCESS_RENEW);
+
+    /// Constructs a `SessionTicketStatus` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> SessionTicketStatus {
+        SessionTicketStatus(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// The result of a session ticket decryption callback.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+#[cfg(ossl111)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SessionTicketResponse(c_int);
+
+#[cfg(ossl111)]
+impl SessionTicketResponse {
+    /// Abort the handshake.
+    pub const ABORT: SessionTicketResponse = SessionTicketResponse(ffi::SSL_TICKET_RETURN_ABORT);
+
+    /// Do not use the ticket, perform a full handshake, and do not issue a new ticket.
+    pub const IGNORE: SessionTicketResponse = SessionTicketResponse(ffi::SSL_TICKET_RETURN_IGNORE);
+
+    /// Do not use the ticket, perform a full handshake, and issue a new ticket.
+    pub const IGNORE_RENEW: SessionTicketResponse =
+        SessionTicketResponse(ffi::SSL_TICKET_RETURN_IGNORE_RENEW);
+
+    /// Resume the session from the ticket.
+    ///
+    /// Only valid when the ticket was decrypted successfully.
+    pub const USE: SessionTicketResponse = SessionTicketResponse(ffi::SSL_TICKET_RETURN_USE);
+
+    /// Resume the session from the ticket, and issue a new ticket.
+    ///
+    /// Only valid when the ticket was decrypted successfully.
+    pub const USE_RENEW: SessionTicketResponse =
+        SessionTicketResponse(ffi::SSL_TICKET_RETURN_USE_RENEW);
+}
+
 /// An SSL/TLS protocol version.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct SslVersion(c_int);
@@ -678,6 +1179,37 @@ impl SslContextBuilder {
         }
     }
 
+    /// Creates a new `SslContextBuilder` whose algorithms are fetched from a library context.
+    ///
+    /// `properties` is a property query restricting which algorithm implementations are used.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_new_ex`].
+    ///
+    /// # Safety
+    ///
+    /// The resulting `SslContext`, and every `Ssl` and `SslStream` created from it, must be
+    /// dropped before `ctx`.
+    ///
+    /// [`SSL_CTX_new_ex`]: https://www.openssl.org/docs/man3.0/man3/SSL_CTX_new_ex.html
+    #[cfg(ossl300)]
+    pub unsafe fn with_lib_ctx(
+        method: SslMethod,
+        ctx: &LibCtxRef,
+        properties: Option<&str>,
+    ) -> Result<SslContextBuilder, ErrorStack> {
+        let properties = properties.map(|s| CString::new(s).unwrap());
+        init();
+        let ctx = cvt_p(ffi::SSL_CTX_new_ex(
+            ctx.as_ptr(),
+            properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            method.as_ptr(),
+        ))?;
+
+        Ok(SslContextBuilder::from_ptr(ctx))
+    }
+
     /// Creates an `SslContextBuilder` from a pointer to a raw OpenSSL value.
     ///
     /// # Safety
@@ -1536,6 +2068,91 @@ impl SslContextBuilder {
         ffi::SSL_CTX_sess_set_get_cb(self.as_ptr(), Some(callbacks::raw_get_session::<F>));
     }
 
+    /// Sets the callback used to select the keys which protect session tickets.
+    ///
+    /// The callback is invoked when a ticket is issued and when a client presents a ticket for
+    /// resumption. It should select a key with [`TicketKeyContext::init`], and return a
+    /// [`TicketKeyStatus`] describing the outcome. When decrypting, the key should be looked up by
+    /// [`TicketKeyContext::key_name`]. This allows ticket keys to be shared between servers and
+    /// rotated without discarding outstanding tickets. Returning `SUCCESS` or `RENEW` without
+    /// selecting a key causes the handshake to fail.
+    ///
+    /// This corresponds to [`SSL_CTX_set_tlsext_ticket_key_evp_cb`] on OpenSSL 3.0.0 and newer, and
+    /// [`SSL_CTX_set_tlsext_ticket_key_cb`] on older versions.
+    ///
+    /// [`TicketKeyContext::init`]: struct.TicketKeyContext.html#method.init
+    /// [`TicketKeyContext::key_name`]: struct.TicketKeyContext.html#method.key_name
+    /// [`TicketKeyStatus`]: struct.TicketKeyStatus.html
+    /// [`SSL_CTX_set_tlsext_ticket_key_evp_cb`]: https://www.openssl.org/docs/man3.0/man3/SSL_CTX_set_tlsext_ticket_key_evp_cb.html
+    /// [`SSL_CTX_set_tlsext_ticket_key_cb`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_tlsext_ticket_key_cb.html
+    pub fn set_ticket_key_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &mut TicketKeyContext<'_>) -> Result<TicketKeyStatus, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            #[cfg(ossl300)]
+            ffi::SSL_CTX_set_tlsext_ticket_key_evp_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_ticket_key::<F>),
+            );
+            #[cfg(not(ossl300))]
+            ffi::SSL_CTX_set_tlsext_ticket_key_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_ticket_key::<F>),
+            );
+        }
+    }
+
+    /// Sets the callbacks used to attach application data to session tickets.
+    ///
+    /// The `generate` callback is invoked when a ticket is issued, and returns data which is stored
+    /// in the ticket. The `decrypt` callback is invoked when a client presents a ticket, and is
+    /// passed the session decrypted from it (if any), the name of the key which protected it, and
+    /// the outcome of decryption. The application data can be retrieved with
+    /// [`SslSessionRef::ticket_appdata`]. The callback decides whether the ticket is used for
+    /// resumption.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_session_ticket_cb`].
+    ///
+    /// [`SslSessionRef::ticket_appdata`]: struct.SslSessionRef.html#method.ticket_appdata
+    /// [`SSL_CTX_set_session_ticket_cb`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_session_ticket_cb.html
+    #[cfg(ossl111)]
+    pub fn set_session_ticket_callbacks<G, D>(
+        &mut self,
+        generate: G,
+        decrypt: D,
+    ) -> Result<(), ErrorStack>
+    where
+        G: Fn(&mut SslRef) -> Result<Vec<u8>, ErrorStack> + 'static + Sync + Send,
+        D: Fn(
+                &mut SslRef,
+                Option<&SslSessionRef>,
+                &[u8],
+                SessionTicketStatus,
+            ) -> Result<SessionTicketResponse, ErrorStack>
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<G>(), generate);
+            self.set_ex_data(SslContext::cached_ex_index::<D>(), decrypt);
+            cvt(ffi::SSL_CTX_set_session_ticket_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_generate_session_ticket::<G>),
+                Some(callbacks::raw_decrypt_session_ticket::<D>),
+                ptr::null_mut(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Sets the TLS key logging callback.
     ///
     /// The callback is invoked whenever TLS key material is generated, and is passed a line of NSS
@@ -1558,6 +2175,49 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets a callback which is invoked as the state of connections created from the context
+    /// changes.
+    ///
+    /// The callback is passed the event which occurred, and either the return code of the
+    /// handshake function or, for alert events, the level and description of the alert. It is
+    /// typically used for debugging.
+    ///
+    /// This corresponds to [`SSL_CTX_set_info_callback`].
+    ///
+    /// [`SSL_CTX_set_info_callback`]: https://www.openssl.org/docs/manmaster/man3/SSL_CTX_set_info_callback.html
+    pub fn set_info_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, SslInfoCallbackMode, SslInfoCallbackValue) + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_info_callback(self.as_ptr(), Some(callbacks::raw_info::<F>));
+        }
+    }
+
+    /// Sets a callback which is invoked for each protocol message sent or received by connections
+    /// created from the context.
+    ///
+    /// The callback is passed the direction, protocol version and content type of the message,
+    /// along with its raw contents. It is typically used for tracing handshakes.
+    ///
+    /// This corresponds to [`SSL_CTX_set_msg_callback`].
+    ///
+    /// [`SSL_CTX_set_msg_callback`]: https://www.openssl.org/docs/manmaster/man3/SSL_CTX_set_msg_callback.html
+    pub fn set_msg_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, SslMessageDirection, SslVersion, SslContentType, &[u8])
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            let ptr = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_msg_callback_arg(self.as_ptr(), ptr);
+            ffi::SSL_CTX_set_msg_callback(self.as_ptr(), Some(callbacks::raw_msg::<F>));
+        }
+    }
+
     /// Sets the session caching mode use for connections made with the context.
     ///
     /// Returns the previous session caching mode.
@@ -1744,6 +2404,21 @@ impl SslContextBuilder {
         }
     }
 
+    /// Enables or disables TLS 1.3 post-handshake authentication on the client side.
+    ///
+    /// When enabled, the client advertises that it is willing to provide a certificate after the
+    /// handshake has completed. Defaults to disabled.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_CTX_set_post_handshake_auth`].
+    ///
+    /// [`SSL_CTX_set_post_handshake_auth`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_CTX_set_post_handshake_auth.html
+    #[cfg(ossl111)]
+    pub fn set_post_handshake_auth(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_CTX_set_post_handshake_auth(self.as_ptr(), enabled as c_int) }
+    }
+
     /// Sets a callback which will be invoked just after the client's hello message is received.
     ///
     /// Requires OpenSSL 1.1.1 or newer.
@@ -1888,14 +2563,7 @@ impl SslContext {
     where
         T: 'static + Sync + Send,
     {
-        unsafe {
-            let idx = *INDEXES
-                .lock()
-                .unwrap_or_else(|e| e.into_inner())
-                .entry(TypeId::of::<T>())
-                .or_insert_with(|| SslContext::new_ex_index::<T>().unwrap().as_raw());
-            Index::from_raw(idx)
-        }
+        ex_data::cached_index(SslContext::new_ex_index::<T>).unwrap()
     }
 }
 
@@ -2289,6 +2957,27 @@ impl SslSessionRef {
         unsafe { ffi::SSL_SESSION_get_max_early_data(self.as_ptr()) }
     }
 
+    /// Returns the application data stored in the session's ticket, if any.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_SESSION_get0_ticket_appdata`].
+    ///
+    /// [`SSL_SESSION_get0_ticket_appdata`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_SESSION_get0_ticket_appdata.html
+    #[cfg(ossl111)]
+    pub fn ticket_appdata(&self) -> Option<&[u8]> {
+        unsafe {
+            let mut data = ptr::null_mut();
+            let mut len = 0;
+            ffi::SSL_SESSION_get0_ticket_appdata(self.as_ptr(), &mut data, &mut len);
+            if data.is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts(data as *const u8, len))
+            }
+        }
+    }
+
     /// Returns the time at which the session was established, in seconds since the Unix epoch.
     ///
     /// This corresponds to [`SSL_SESSION_get_time`].
@@ -2386,14 +3075,7 @@ impl Ssl {
     where
         T: 'static + Sync + Send,
     {
-        unsafe {
-            let idx = *SSL_INDEXES
-                .lock()
-                .unwrap_or_else(|e| e.into_inner())
-                .entry(TypeId::of::<T>())
-                .or_insert_with(|| Ssl::new_ex_index::<T>().unwrap().as_raw());
-            Index::from_raw(idx)
-        }
+        ex_data::cached_index(Ssl::new_ex_index::<T>).unwrap()
     }
 
     /// Creates a new `Ssl`.
@@ -2538,6 +3220,43 @@ impl SslRef {
         }
     }
 
+    /// Like [`SslContextBuilder::set_info_callback`].
+    ///
+    /// This corresponds to [`SSL_set_info_callback`].
+    ///
+    /// [`SslContextBuilder::set_info_callback`]: struct.SslContextBuilder.html#method.set_info_callback
+    /// [`SSL_set_info_callback`]: https://www.openssl.org/docs/manmaster/man3/SSL_set_info_callback.html
+    pub fn set_info_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, SslInfoCallbackMode, SslInfoCallbackValue) + 'static + Sync + Send,
+    {
+        unsafe {
+            // this needs to be in an Arc since the callback can register a new callback!
+            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(callback));
+            ffi::SSL_set_info_callback(self.as_ptr(), Some(ssl_raw_info::<F>));
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_msg_callback`].
+    ///
+    /// This corresponds to [`SSL_set_msg_callback`].
+    ///
+    /// [`SslContextBuilder::set_msg_callback`]: struct.SslContextBuilder.html#method.set_msg_callback
+    /// [`SSL_set_msg_callback`]: https://www.openssl.org/docs/manmaster/man3/SSL_set_msg_callback.html
+    pub fn set_msg_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, SslMessageDirection, SslVersion, SslContentType, &[u8])
+            + 'static
+            + Sync
+            + Send,
+    {
+        unsafe {
+            // this needs to be in an Arc since the callback can register a new callback!
+            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(callback));
+            ffi::SSL_set_msg_callback(self.as_ptr(), Some(ssl_raw_msg::<F>));
+        }
+    }
+
     /// Like [`SslContextBuilder::set_tmp_dh`].
     ///
     /// This corresponds to [`SSL_set_tmp_dh`].
@@ -3246,6 +3965,64 @@ impl SslRef {
         }
     }
 
+    /// Schedules a TLS 1.3 key update.
+    ///
+    /// The `KeyUpdate` message is sent with the next write to the connection, or by calling
+    /// `SslStream::do_handshake`. If `update_type` is `KeyUpdateType::REQUESTED` the peer is also
+    /// asked to update its keys.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_key_update`].
+    ///
+    /// [`SSL_key_update`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_key_update.html
+    #[cfg(ossl111)]
+    pub fn key_update(&mut self, update_type: KeyUpdateType) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_key_update(self.as_ptr(), update_type.as_raw())).map(|_| ()) }
+    }
+
+    /// Returns the type of the pending key update, or `KeyUpdateType::NONE` if none is pending.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_get_key_update_type`].
+    ///
+    /// [`SSL_get_key_update_type`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_get_key_update_type.html
+    #[cfg(ossl111)]
+    pub fn key_update_type(&self) -> KeyUpdateType {
+        unsafe { KeyUpdateType::from_raw(ffi::SSL_get_key_update_type(self.as_ptr())) }
+    }
+
+    /// Enables or disables TLS 1.3 post-handshake authentication on the client side.
+    ///
+    /// This must be called before the handshake begins.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_set_post_handshake_auth`].
+    ///
+    /// [`SSL_set_post_handshake_auth`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_set_post_handshake_auth.html
+    #[cfg(ossl111)]
+    pub fn set_post_handshake_auth(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_set_post_handshake_auth(self.as_ptr(), enabled as c_int) }
+    }
+
+    /// Requests a certificate from the client on an established TLS 1.3 connection.
+    ///
+    /// The client must have enabled post-handshake authentication, and the server must have set a
+    /// verify mode including `SslVerifyMode::PEER`. The request is sent with the next write to the
+    /// connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// This corresponds to [`SSL_verify_client_post_handshake`].
+    ///
+    /// [`SSL_verify_client_post_handshake`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_verify_client_post_handshake.html
+    #[cfg(ossl111)]
+    pub fn verify_client_post_handshake(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_verify_client_post_handshake(self.as_ptr())).map(|_| ()) }
+    }
+
     /// Gets the maximum amount of early data that can be sent on this connection.
     ///
     /// Requires OpenSSL 1.1.1 or newer.
@@ -3862,6 +4639,11 @@ impl<S> SslStream<S> {
     pub fn ssl(&self) -> &SslRef {
         &self.ssl
     }
+
+    /// Returns a mutable reference to the `Ssl` object associated with this stream.
+    pub fn ssl_mut(&mut self) -> &mut SslRef {
+        &mut self.ssl
+    }
 }
 
 impl<S: Read + Write> Read for SslStream<S> {
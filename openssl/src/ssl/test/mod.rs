@@ -1,7 +1,9 @@
 #![allow(unused_imports)]
 
+use std::cmp;
 use std::env;
 use std::fs::File;
+use std::future::Future;
 use std::io::prelude::*;
 use std::io::{self, BufReader};
 use std::iter;
@@ -9,8 +11,12 @@ use std::mem;
 use std::net::UdpSocket;
 use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::path::Path;
+use std::pin::Pin;
 use std::process::{Child, ChildStdin, Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
 use std::thread;
 use std::time::Duration;
 use tempdir::TempDir;
@@ -25,13 +31,19 @@ use crate::ssl;
 use crate::ssl::test::server::Server;
 #[cfg(any(ossl110, ossl111, libressl261))]
 use crate::ssl::SslVersion;
+use crate::ssl::{
+    AsyncIo, AsyncSslStream, Error, HandshakeError, MidHandshakeSslStream, ShutdownResult,
+    ShutdownState, Ssl, SslAcceptor, SslAcceptorBuilder, SslAlert, SslAlertLevel, SslConnector,
+    SslContentType, SslContext, SslContextBuilder, SslFiletype, SslInfoCallbackMode,
+    SslInfoCallbackValue, SslMessageDirection, SslMethod, SslOptions, SslSessionCacheMode,
+    SslStream, SslVerifyMode, StatusType, TicketKey, TicketKeyStatus,
+};
 #[cfg(ossl111)]
-use crate::ssl::{ClientHelloResponse, ExtensionContext};
 use crate::ssl::{
-    Error, HandshakeError, MidHandshakeSslStream, ShutdownResult, ShutdownState, Ssl, SslAcceptor,
-    SslAcceptorBuilder, SslConnector, SslContext, SslContextBuilder, SslFiletype, SslMethod,
-    SslOptions, SslSessionCacheMode, SslStream, SslVerifyMode, StatusType,
+    ClientHelloResponse, ExtensionContext, KeyUpdateType, SessionTicketResponse,
+    SessionTicketStatus,
 };
+use crate::symm::Cipher;
 #[cfg(ossl102)]
 use crate::x509::store::X509StoreBuilder;
 #[cfg(ossl102)]
@@ -1054,6 +1066,223 @@ fn new_session_callback_swapped_ctx() {
     assert!(CALLED_BACK.load(Ordering::SeqCst));
 }
 
+#[cfg(any(ossl110, libressl261))]
+fn ticket_resumption<F>(configure: F) -> bool
+where
+    F: FnOnce(&mut SslContextBuilder),
+{
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file("test/cert.pem", SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    ctx.set_max_proto_version(Some(SslVersion::TLS1_2)).unwrap();
+    ctx.set_session_cache_mode(SslSessionCacheMode::OFF);
+    configure(&mut ctx);
+    let ctx = ctx.build();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let guard = thread::spawn(move || {
+        for _ in 0..2 {
+            let stream = listener.accept().unwrap().0;
+            let ssl = Ssl::new(&ctx).unwrap();
+            let mut stream = ssl.accept(stream).unwrap();
+            stream.write_all(&[0]).unwrap();
+        }
+    });
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let ssl = Ssl::new(&ctx).unwrap();
+    let mut stream = ssl.connect(stream).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    let session = stream.ssl().session().unwrap().to_owned();
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    unsafe { ssl.set_session(&session).unwrap() };
+    let mut stream = ssl.connect(stream).unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+
+    guard.join().unwrap();
+
+    stream.ssl().session_reused()
+}
+
+#[cfg(any(ossl110, libressl261))]
+fn ticket_key() -> TicketKey {
+    TicketKey::new(
+        *b"0123456789abcdef",
+        Cipher::aes_128_cbc(),
+        &[1; 16],
+        MessageDigest::sha256(),
+        &[2; 32],
+    )
+}
+
+#[test]
+#[cfg(any(ossl110, libressl261))]
+fn ticket_key_callback() {
+    static ENCRYPTED: AtomicUsize = AtomicUsize::new(0);
+    static DECRYPTED: AtomicUsize = AtomicUsize::new(0);
+
+    let resumed = ticket_resumption(|ctx| {
+        ctx.set_ticket_key_callback(|_, ticket| {
+            let key = ticket_key();
+            if ticket.is_encrypt() {
+                ENCRYPTED.fetch_add(1, Ordering::SeqCst);
+                ticket.init(&key)?;
+                Ok(TicketKeyStatus::SUCCESS)
+            } else {
+                assert_eq!(ticket.key_name(), key.name());
+                DECRYPTED.fetch_add(1, Ordering::SeqCst);
+                ticket.init(&key)?;
+                Ok(TicketKeyStatus::RENEW)
+            }
+        });
+    });
+
+    assert!(resumed);
+    assert_eq!(ENCRYPTED.load(Ordering::SeqCst), 2);
+    assert_eq!(DECRYPTED.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+#[cfg(any(ossl110, libressl261))]
+fn ticket_key_callback_not_found() {
+    let resumed = ticket_resumption(|ctx| {
+        ctx.set_ticket_key_callback(|_, ticket| {
+            if ticket.is_encrypt() {
+                ticket.init(&ticket_key())?;
+                Ok(TicketKeyStatus::SUCCESS)
+            } else {
+                Ok(TicketKeyStatus::NOT_FOUND)
+            }
+        });
+    });
+
+    assert!(!resumed);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn session_ticket_callbacks() {
+    static DECRYPTED: AtomicBool = AtomicBool::new(false);
+
+    let resumed = ticket_resumption(|ctx| {
+        ctx.set_session_ticket_callbacks(
+            |_| Ok(b"app data".to_vec()),
+            |_, session, _, status| {
+                if status != SessionTicketStatus::SUCCESS {
+                    return Ok(SessionTicketResponse::IGNORE_RENEW);
+                }
+                assert_eq!(session.unwrap().ticket_appdata(), Some(&b"app data"[..]));
+                DECRYPTED.store(true, Ordering::SeqCst);
+                Ok(SessionTicketResponse::USE)
+            },
+        )
+        .unwrap();
+    });
+
+    assert!(resumed);
+    assert!(DECRYPTED.load(Ordering::SeqCst));
+}
+
+#[test]
+fn info_callback() {
+    static CLIENT_DONE: AtomicBool = AtomicBool::new(false);
+    static SERVER_DONE: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_info_callback(|_, mode, value| {
+        if mode.contains(SslInfoCallbackMode::HANDSHAKE_DONE) {
+            assert_eq!(value, SslInfoCallbackValue::Return(1));
+            SERVER_DONE.store(true, Ordering::SeqCst);
+        }
+    });
+    let server = server.build();
+
+    let mut client = server.client().build().builder();
+    client.ssl().set_info_callback(|_, mode, _| {
+        if mode.contains(SslInfoCallbackMode::HANDSHAKE_DONE) {
+            CLIENT_DONE.store(true, Ordering::SeqCst);
+        }
+    });
+    client.connect();
+    drop(server);
+
+    assert!(CLIENT_DONE.load(Ordering::SeqCst));
+    assert!(SERVER_DONE.load(Ordering::SeqCst));
+}
+
+#[test]
+fn info_callback_alert() {
+    let alerts = Arc::new(Mutex::new(vec![]));
+
+    let mut server = Server::builder();
+    server.should_error();
+    let server_alerts = alerts.clone();
+    server.ctx().set_info_callback(move |_, mode, value| {
+        if let SslInfoCallbackValue::Alert(level, alert) = value {
+            assert!(mode.contains(SslInfoCallbackMode::READ_ALERT));
+            server_alerts.lock().unwrap().push((level, alert));
+        }
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    client.connect_err();
+    drop(server);
+
+    let alerts = alerts.lock().unwrap();
+    assert_eq!(*alerts, [(SslAlertLevel::FATAL, SslAlert::UNKNOWN_CA)]);
+    assert_eq!(alerts[0].1.description_string_long(), "unknown CA");
+    assert_eq!(alerts[0].0.level_string_long(), "fatal");
+}
+
+#[test]
+fn msg_callback() {
+    let client_msgs = Arc::new(Mutex::new(vec![]));
+    let server_msgs = Arc::new(Mutex::new(vec![]));
+
+    let mut server = Server::builder();
+    let msgs = server_msgs.clone();
+    server.ssl_cb(move |ssl| {
+        let msgs = msgs.clone();
+        ssl.set_msg_callback(move |_, direction, _, content_type, buf| {
+            if content_type == SslContentType::HANDSHAKE {
+                msgs.lock().unwrap().push((direction, buf[0]));
+            }
+        });
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    let msgs = client_msgs.clone();
+    client
+        .ctx()
+        .set_msg_callback(move |_, direction, version, content_type, buf| {
+            if content_type == SslContentType::HANDSHAKE {
+                assert!(version.0 >= SslVersion::TLS1.0);
+                msgs.lock().unwrap().push((direction, buf[0]));
+            }
+        });
+    client.connect();
+    drop(server);
+
+    // ClientHello and ServerHello
+    let client_msgs = client_msgs.lock().unwrap();
+    assert_eq!(client_msgs[0], (SslMessageDirection::Sent, 1));
+    assert_eq!(client_msgs[1], (SslMessageDirection::Received, 2));
+    let server_msgs = server_msgs.lock().unwrap();
+    assert_eq!(server_msgs[0], (SslMessageDirection::Received, 1));
+    assert_eq!(server_msgs[1], (SslMessageDirection::Sent, 2));
+}
+
 #[test]
 fn keying_export() {
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
@@ -1386,6 +1615,61 @@ fn client_hello() {
     assert!(CALLED_BACK.load(Ordering::SeqCst));
 }
 
+#[test]
+#[cfg(ossl111)]
+fn key_update() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        s.read_exact(&mut [0]).unwrap();
+        // the client requested an update, so one of our own is now pending
+        assert_eq!(s.ssl().key_update_type(), KeyUpdateType::NOT_REQUESTED);
+        s.write_all(&[0]).unwrap();
+        assert_eq!(s.ssl().key_update_type(), KeyUpdateType::NONE);
+    });
+    let server = server.build();
+
+    let mut s = server.client().connect();
+    assert_eq!(s.ssl().version2(), Some(SslVersion::TLS1_3));
+    assert_eq!(s.ssl().key_update_type(), KeyUpdateType::NONE);
+    s.ssl_mut().key_update(KeyUpdateType::REQUESTED).unwrap();
+    assert_eq!(s.ssl().key_update_type(), KeyUpdateType::REQUESTED);
+    s.write_all(&[0]).unwrap();
+    assert_eq!(s.ssl().key_update_type(), KeyUpdateType::NONE);
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+#[cfg(ossl111)]
+fn post_handshake_auth() {
+    let mut server = Server::builder();
+    server.ctx().set_verify_callback(
+        SslVerifyMode::PEER | SslVerifyMode::POST_HANDSHAKE,
+        |_, _| true,
+    );
+    server.io_cb(|mut s| {
+        assert!(s.ssl().peer_certificate().is_none());
+        s.ssl_mut().verify_client_post_handshake().unwrap();
+        s.write_all(&[0]).unwrap();
+        s.read_exact(&mut [0]).unwrap();
+        assert!(s.ssl().peer_certificate().is_some());
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_post_handshake_auth(true);
+    client
+        .ctx()
+        .set_certificate_file("test/cert.pem", SslFiletype::PEM)
+        .unwrap();
+    client
+        .ctx()
+        .set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    let mut s = client.connect();
+    s.read_exact(&mut [0]).unwrap();
+    s.write_all(&[0]).unwrap();
+}
+
 #[test]
 #[cfg(ossl111)]
 fn openssl_cipher_name() {
@@ -1404,3 +1688,146 @@ fn session_cache_size() {
     let ctx = ctx.build();
     assert_eq!(ctx.session_cache_size(), 1234);
 }
+
+struct MemoryPipe {
+    read: Arc<Mutex<Vec<u8>>>,
+    write: Arc<Mutex<Vec<u8>>>,
+}
+
+impl MemoryPipe {
+    fn pair() -> (MemoryPipe, MemoryPipe) {
+        let a = Arc::new(Mutex::new(vec![]));
+        let b = Arc::new(Mutex::new(vec![]));
+        (
+            MemoryPipe {
+                read: a.clone(),
+                write: b.clone(),
+            },
+            MemoryPipe { read: b, write: a },
+        )
+    }
+}
+
+impl AsyncIo for MemoryPipe {
+    fn poll_read(&mut self, _: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut read = self.read.lock().unwrap();
+        if read.is_empty() {
+            return Poll::Pending;
+        }
+        let len = cmp::min(buf.len(), read.len());
+        buf[..len].copy_from_slice(&read[..len]);
+        read.drain(..len);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_write(&mut self, _: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.write.lock().unwrap().extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(&mut self, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) }
+}
+
+#[test]
+fn async_stream() {
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let (client_io, server_io) = MemoryPipe::pair();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_chain_file("test/cert.pem").unwrap();
+    ctx.set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    let mut server = AsyncSslStream::new(ssl, server_io).unwrap();
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let ssl = Ssl::new(&ctx).unwrap();
+    let mut client = AsyncSslStream::new(ssl, client_io).unwrap();
+
+    let mut client_done = false;
+    let mut server_done = false;
+    while !client_done || !server_done {
+        if !client_done {
+            match Pin::new(&mut client.connect()).poll(&mut cx) {
+                Poll::Ready(r) => {
+                    r.unwrap();
+                    client_done = true;
+                }
+                Poll::Pending => {}
+            }
+        }
+        if !server_done {
+            match Pin::new(&mut server.accept()).poll(&mut cx) {
+                Poll::Ready(r) => {
+                    r.unwrap();
+                    server_done = true;
+                }
+                Poll::Pending => {}
+            }
+        }
+    }
+    assert!(client.ssl().peer_certificate().is_some());
+    assert!(server.ssl().is_server());
+
+    let mut buf = [0; 5];
+    assert!(client.poll_ssl_read(&mut cx, &mut buf).is_pending());
+
+    match Pin::new(&mut server.ssl_write(b"hello")).poll(&mut cx) {
+        Poll::Ready(r) => assert_eq!(r.unwrap(), 5),
+        Poll::Pending => panic!("write should not block"),
+    }
+    match Pin::new(&mut client.ssl_read(&mut buf)).poll(&mut cx) {
+        Poll::Ready(r) => assert_eq!(r.unwrap(), 5),
+        Poll::Pending => panic!("data should be available"),
+    }
+    assert_eq!(&buf, b"hello");
+
+    match Pin::new(&mut client.shutdown()).poll(&mut cx) {
+        Poll::Ready(r) => assert_eq!(r.unwrap(), ShutdownResult::Sent),
+        Poll::Pending => panic!("shutdown should not block"),
+    }
+    match server.poll_read(&mut cx, &mut buf) {
+        Poll::Ready(r) => assert_eq!(r.unwrap(), 0),
+        Poll::Pending => panic!("close notify should be available"),
+    }
+}
+
+#[test]
+#[cfg(ossl300)]
+fn lib_ctx() {
+    use crate::lib_ctx::LibCtx;
+    use crate::provider::Provider;
+
+    let server = Server::builder().build();
+
+    let lib_ctx = LibCtx::new().unwrap();
+    let _provider = unsafe { Provider::load_in(&lib_ctx, "default").unwrap() };
+
+    {
+        let ctx = unsafe {
+            SslContextBuilder::with_lib_ctx(SslMethod::tls(), &lib_ctx, Some("provider=default"))
+                .unwrap()
+        };
+        let ssl = Ssl::new(&ctx.build()).unwrap();
+        let mut stream = ssl.connect(server.connect_tcp()).unwrap();
+        stream.read_exact(&mut [0]).unwrap();
+    }
+
+    let ctx = unsafe {
+        SslContextBuilder::with_lib_ctx(SslMethod::tls(), &lib_ctx, Some("provider=nope"))
+    };
+    assert!(ctx.is_err());
+}
@@ -53,10 +53,14 @@
 //! ```
 
 use cfg_if::cfg_if;
+#[cfg(ossl300)]
+use foreign_types::ForeignTypeRef;
 use libc::c_int;
 use std::cmp;
 use std::ptr;
 
+#[cfg(ossl300)]
+use crate::cipher::CipherRef;
 use crate::error::ErrorStack;
 use crate::nid::Nid;
 use crate::{cvt, cvt_p};
@@ -459,63 +463,88 @@ impl Crypter {
         key: &[u8],
         iv: Option<&[u8]>,
     ) -> Result<Crypter, ErrorStack> {
-        ffi::init();
+        unsafe { Crypter::new_intern(t.as_ptr(), t.block_size(), t.iv_len(), mode, key, iv) }
+    }
 
-        unsafe {
-            let ctx = cvt_p(ffi::EVP_CIPHER_CTX_new())?;
-            let crypter = Crypter {
-                ctx,
-                block_size: t.block_size(),
-            };
+    /// Creates a new `Crypter` using a cipher fetched from a provider.
+    ///
+    /// The `Crypter` holds a reference to the cipher, so it remains usable after `t` is dropped.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an IV is required by the cipher but not provided.
+    #[cfg(ossl300)]
+    pub fn with_cipher(
+        t: &CipherRef,
+        mode: Mode,
+        key: &[u8],
+        iv: Option<&[u8]>,
+    ) -> Result<Crypter, ErrorStack> {
+        unsafe { Crypter::new_intern(t.as_ptr(), t.block_size(), t.iv_length(), mode, key, iv) }
+    }
 
-            let mode = match mode {
-                Mode::Encrypt => 1,
-                Mode::Decrypt => 0,
-            };
+    unsafe fn new_intern(
+        cipher: *const ffi::EVP_CIPHER,
+        block_size: usize,
+        iv_len: Option<usize>,
+        mode: Mode,
+        key: &[u8],
+        iv: Option<&[u8]>,
+    ) -> Result<Crypter, ErrorStack> {
+        ffi::init();
 
-            cvt(ffi::EVP_CipherInit_ex(
-                crypter.ctx,
-                t.as_ptr(),
-                ptr::null_mut(),
-                ptr::null_mut(),
-                ptr::null_mut(),
-                mode,
-            ))?;
+        let ctx = cvt_p(ffi::EVP_CIPHER_CTX_new())?;
+        let crypter = Crypter { ctx, block_size };
 
-            assert!(key.len() <= c_int::max_value() as usize);
-            cvt(ffi::EVP_CIPHER_CTX_set_key_length(
-                crypter.ctx,
-                key.len() as c_int,
-            ))?;
+        let mode = match mode {
+            Mode::Encrypt => 1,
+            Mode::Decrypt => 0,
+        };
 
-            let key = key.as_ptr() as *mut _;
-            let iv = match (iv, t.iv_len()) {
-                (Some(iv), Some(len)) => {
-                    if iv.len() != len {
-                        assert!(iv.len() <= c_int::max_value() as usize);
-                        cvt(ffi::EVP_CIPHER_CTX_ctrl(
-                            crypter.ctx,
-                            ffi::EVP_CTRL_GCM_SET_IVLEN,
-                            iv.len() as c_int,
-                            ptr::null_mut(),
-                        ))?;
-                    }
-                    iv.as_ptr() as *mut _
+        cvt(ffi::EVP_CipherInit_ex(
+            crypter.ctx,
+            cipher,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            mode,
+        ))?;
+
+        assert!(key.len() <= c_int::max_value() as usize);
+        cvt(ffi::EVP_CIPHER_CTX_set_key_length(
+            crypter.ctx,
+            key.len() as c_int,
+        ))?;
+
+        let key = key.as_ptr() as *mut _;
+        let iv = match (iv, iv_len) {
+            (Some(iv), Some(len)) => {
+                if iv.len() != len {
+                    assert!(iv.len() <= c_int::max_value() as usize);
+                    cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                        crypter.ctx,
+                        ffi::EVP_CTRL_GCM_SET_IVLEN,
+                        iv.len() as c_int,
+                        ptr::null_mut(),
+                    ))?;
                 }
-                (Some(_), None) | (None, None) => ptr::null_mut(),
-                (None, Some(_)) => panic!("an IV is required for this cipher"),
-            };
-            cvt(ffi::EVP_CipherInit_ex(
-                crypter.ctx,
-                ptr::null(),
-                ptr::null_mut(),
-                key,
-                iv,
-                mode,
-            ))?;
+                iv.as_ptr() as *mut _
+            }
+            (Some(_), None) | (None, None) => ptr::null_mut(),
+            (None, Some(_)) => panic!("an IV is required for this cipher"),
+        };
+        cvt(ffi::EVP_CipherInit_ex(
+            crypter.ctx,
+            ptr::null(),
+            ptr::null_mut(),
+            key,
+            iv,
+            mode,
+        ))?;
 
-            Ok(crypter)
-        }
+        Ok(crypter)
     }
 
     /// Enables or disables padding.
@@ -1357,6 +1386,27 @@ mod tests {
         assert_eq!(pt, hex::encode(out));
     }
 
+    #[test]
+    #[cfg(ossl300)]
+    fn test_fetched_cipher() {
+        let cipher = crate::cipher::Cipher::fetch("AES-128-CBC", Some("provider=default")).unwrap();
+        let key = Vec::from_hex("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let iv = Vec::from_hex("000102030405060708090a0b0c0d0e0f").unwrap();
+        let pt = b"some plaintext that spans multiple blocks";
+
+        let mut c = Crypter::with_cipher(&cipher, Mode::Encrypt, &key, Some(&iv)).unwrap();
+        drop(cipher);
+        let mut ct = vec![0; pt.len() + Cipher::aes_128_cbc().block_size()];
+        let mut len = c.update(pt, &mut ct).unwrap();
+        len += c.finalize(&mut ct[len..]).unwrap();
+        ct.truncate(len);
+
+        assert_eq!(
+            ct,
+            encrypt(Cipher::aes_128_cbc(), &key, Some(&iv), pt).unwrap()
+        );
+    }
+
     #[test]
     fn test_aes128_ccm() {
         let key = "3ee186594f110fb788a8bf8aa8be5d4a";
@@ -1,17 +1,200 @@
-use crate::cvt_p;
-use crate::x509::Stackable;
+//! Certificate revocation lists.
+//!
+//! A CRL is a signed list of certificates that have been revoked by their
+//! issuing certificate authority before their scheduled expiration date.
+
+use cfg_if::cfg_if;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_int, c_long};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+
+use crate::asn1::{
+    Asn1BitStringRef, Asn1GeneralizedTime, Asn1Integer, Asn1IntegerRef, Asn1TimeRef,
+};
+use crate::bio::MemBio;
+use crate::conf::ConfRef;
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
+use crate::stack::{StackRef, Stackable};
+use crate::util::{ForeignTypeExt, ForeignTypeRefExt};
+use crate::x509::{
+    GeneralName, X509AlgorithmRef, X509Extension, X509ExtensionRef, X509NameRef, X509Ref,
+    X509v3Context,
+};
+use crate::{cvt, cvt_n, cvt_p};
+
+/// A builder used to construct an `X509CRL`.
+pub struct X509CrlBuilder(X509CRL);
+
+impl X509CrlBuilder {
+    /// Creates a new builder.
+    pub fn new() -> Result<X509CrlBuilder, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::X509_CRL_new()).map(|p| X509CrlBuilder(X509CRL(p)))
+        }
+    }
+
+    /// Sets the version of the CRL.
+    ///
+    /// Note that the version is zero-indexed; that is, a CRL corresponding to version 2 of the
+    /// X.509 standard should pass `1` to this method. Version 2 is required if the CRL contains
+    /// any extensions.
+    pub fn set_version(&mut self, version: i32) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_set_version(
+                self.0.as_ptr(),
+                version as c_long,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the issuer name of the CRL.
+    pub fn set_issuer_name(&mut self, issuer_name: &X509NameRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_set_issuer_name(
+                self.0.as_ptr(),
+                issuer_name.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the `lastUpdate` field of the CRL.
+    pub fn set_last_update(&mut self, last_update: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(X509_CRL_set1_lastUpdate(
+                self.0.as_ptr(),
+                last_update.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the `nextUpdate` field of the CRL.
+    pub fn set_next_update(&mut self, next_update: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(X509_CRL_set1_nextUpdate(
+                self.0.as_ptr(),
+                next_update.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds an entry to the CRL.
+    ///
+    /// This corresponds to [`X509_CRL_add0_revoked`].
+    ///
+    /// [`X509_CRL_add0_revoked`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_add0_revoked.html
+    pub fn add_revoked(&mut self, revoked: X509Revoked) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_add0_revoked(
+                self.0.as_ptr(),
+                revoked.as_ptr(),
+            ))?;
+            mem::forget(revoked);
+            Ok(())
+        }
+    }
+
+    /// Returns a context object which is needed to create certain X509 extension values, such as
+    /// the `AuthorityKeyIdentifier`.
+    ///
+    /// `issuer` is the certificate of the authority issuing the CRL.
+    pub fn x509v3_context<'a>(
+        &'a self,
+        issuer: &'a X509Ref,
+        conf: Option<&'a ConfRef>,
+    ) -> X509v3Context<'a> {
+        unsafe {
+            let mut ctx = mem::zeroed();
+
+            ffi::X509V3_set_ctx(
+                &mut ctx,
+                issuer.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                self.0.as_ptr(),
+                0,
+            );
+
+            // nodb case taken care of since we zeroed ctx above
+            if let Some(conf) = conf {
+                ffi::X509V3_set_nconf(&mut ctx, conf.as_ptr());
+            }
+
+            X509v3Context(ctx, PhantomData)
+        }
+    }
+
+    /// Adds an X509 extension value to the CRL.
+    ///
+    /// This works just as `append_extension2` except it takes ownership of the `X509Extension`.
+    pub fn append_extension(&mut self, extension: X509Extension) -> Result<(), ErrorStack> {
+        self.append_extension2(&extension)
+    }
+
+    /// Adds an X509 extension value to the CRL.
+    ///
+    /// This corresponds to [`X509_CRL_add_ext`].
+    ///
+    /// [`X509_CRL_add_ext`]: https://www.openssl.org/docs/man1.1.0/man3/X509_CRL_add_ext.html
+    pub fn append_extension2(&mut self, extension: &X509ExtensionRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_add_ext(
+                self.0.as_ptr(),
+                extension.as_ptr(),
+                -1,
+            ))?;
+            Ok(())
+        }
+    }
+
+    /// Signs the CRL with a private key.
+    ///
+    /// The entries of the CRL are sorted by serial number before signing.
+    pub fn sign<T>(&mut self, key: &PKeyRef<T>, hash: MessageDigest) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            cvt(ffi::X509_CRL_sort(self.0.as_ptr()))?;
+            cvt(ffi::X509_CRL_sign(
+                self.0.as_ptr(),
+                key.as_ptr(),
+                hash.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Consumes the builder, returning the CRL.
+    pub fn build(self) -> X509CRL {
+        self.0
+    }
+}
 
 foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_CRL;
     fn drop = ffi::X509_CRL_free;
 
-    /// A public or private key context.
+    /// An `X509` certificate revocation list.
     pub struct X509CRL;
     /// Reference to `X509CRL`.
     pub struct X509CRLRef;
 }
 
 impl X509CRL {
+    /// Returns a new builder.
+    pub fn builder() -> Result<X509CrlBuilder, ErrorStack> {
+        X509CrlBuilder::new()
+    }
+
     from_pem! {
         /// Deserializes a PEM-encoded CRL structure.
         ///
@@ -37,6 +220,532 @@ impl X509CRL {
     }
 }
 
+impl X509CRLRef {
+    to_pem! {
+        /// Serializes the CRL into a PEM-encoded X509 CRL structure.
+        ///
+        /// The output will have a header of `-----BEGIN X509 CRL-----`.
+        ///
+        /// This corresponds to [`PEM_write_bio_X509_CRL`].
+        ///
+        /// [`PEM_write_bio_X509_CRL`]: https://www.openssl.org/docs/man1.1.0/crypto/PEM_write_bio_X509_CRL.html
+        to_pem,
+        ffi::PEM_write_bio_X509_CRL
+    }
+
+    to_der! {
+        /// Serializes the CRL into a DER-encoded X509 CRL structure.
+        ///
+        /// This corresponds to [`i2d_X509_CRL`].
+        ///
+        /// [`i2d_X509_CRL`]: https://www.openssl.org/docs/man1.1.0/crypto/i2d_X509_CRL.html
+        to_der,
+        ffi::i2d_X509_CRL
+    }
+
+    /// Returns a human readable representation of the CRL.
+    ///
+    /// This is the format printed by `openssl crl -text`.
+    ///
+    /// This corresponds to [`X509_CRL_print`].
+    ///
+    /// [`X509_CRL_print`]: https://www.openssl.org/docs/man3.0/man3/X509_print_ex.html
+    pub fn to_text(&self) -> Result<Vec<u8>, ErrorStack> {
+        let bio = MemBio::new()?;
+        unsafe {
+            cvt(ffi::X509_CRL_print(bio.as_ptr(), self.as_ptr()))?;
+        }
+        Ok(bio.get_buf().to_owned())
+    }
+
+    /// Returns the version of the CRL.
+    ///
+    /// Note that the version is zero-indexed; that is, a CRL corresponding to version 2 of the
+    /// X.509 standard returns `1`.
+    ///
+    /// This corresponds to [`X509_CRL_get_version`].
+    ///
+    /// [`X509_CRL_get_version`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get_version.html
+    pub fn version(&self) -> i32 {
+        unsafe { X509_CRL_get_version(self.as_ptr()) as i32 }
+    }
+
+    /// Returns the issuer name of the CRL.
+    ///
+    /// This corresponds to [`X509_CRL_get_issuer`].
+    ///
+    /// [`X509_CRL_get_issuer`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get_issuer.html
+    pub fn issuer_name(&self) -> &X509NameRef {
+        unsafe {
+            let name = X509_CRL_get_issuer(self.as_ptr());
+            X509NameRef::from_const_ptr_opt(name).expect("issuer name must not be null")
+        }
+    }
+
+    /// Returns the `lastUpdate` (also known as `thisUpdate`) field of the CRL.
+    ///
+    /// This corresponds to [`X509_CRL_get0_lastUpdate`].
+    ///
+    /// [`X509_CRL_get0_lastUpdate`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get0_lastUpdate.html
+    pub fn last_update(&self) -> &Asn1TimeRef {
+        unsafe {
+            let date = X509_CRL_get0_lastUpdate(self.as_ptr());
+            Asn1TimeRef::from_const_ptr_opt(date).expect("last_update must not be null")
+        }
+    }
+
+    /// Returns the `nextUpdate` field of the CRL, if present.
+    ///
+    /// This corresponds to [`X509_CRL_get0_nextUpdate`].
+    ///
+    /// [`X509_CRL_get0_nextUpdate`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get0_nextUpdate.html
+    pub fn next_update(&self) -> Option<&Asn1TimeRef> {
+        unsafe { Asn1TimeRef::from_const_ptr_opt(X509_CRL_get0_nextUpdate(self.as_ptr())) }
+    }
+
+    /// Returns the CRL's signature.
+    pub fn signature(&self) -> &Asn1BitStringRef {
+        unsafe {
+            let mut signature = ptr::null();
+            X509_CRL_get0_signature(self.as_ptr(), &mut signature, ptr::null_mut());
+            Asn1BitStringRef::from_const_ptr_opt(signature).expect("signature must not be null")
+        }
+    }
+
+    /// Returns the CRL's signature algorithm.
+    pub fn signature_algorithm(&self) -> &X509AlgorithmRef {
+        unsafe {
+            let mut algor = ptr::null();
+            X509_CRL_get0_signature(self.as_ptr(), ptr::null_mut(), &mut algor);
+            X509AlgorithmRef::from_const_ptr_opt(algor)
+                .expect("signature algorithm must not be null")
+        }
+    }
+
+    /// Returns the value of the CRL number extension, if it exists.
+    ///
+    /// This corresponds to [`X509_CRL_get_ext_d2i`] called with `NID_crl_number`.
+    ///
+    /// [`X509_CRL_get_ext_d2i`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get_ext_d2i.html
+    pub fn crl_number(&self) -> Option<Asn1Integer> {
+        unsafe {
+            let number = ffi::X509_CRL_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_crl_number,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Asn1Integer::from_ptr_opt(number as *mut _)
+        }
+    }
+
+    /// Returns the base CRL number from the delta CRL indicator extension, if it exists.
+    ///
+    /// The presence of this extension marks the CRL as a delta CRL.
+    ///
+    /// This corresponds to [`X509_CRL_get_ext_d2i`] called with `NID_delta_crl`.
+    ///
+    /// [`X509_CRL_get_ext_d2i`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get_ext_d2i.html
+    pub fn delta_crl_indicator(&self) -> Option<Asn1Integer> {
+        unsafe {
+            let number = ffi::X509_CRL_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_delta_crl,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Asn1Integer::from_ptr_opt(number as *mut _)
+        }
+    }
+
+    /// Returns the issuing distribution point extension, if it exists.
+    ///
+    /// This corresponds to [`X509_CRL_get_ext_d2i`] called with `NID_issuing_distribution_point`.
+    ///
+    /// [`X509_CRL_get_ext_d2i`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get_ext_d2i.html
+    pub fn issuing_distribution_point(&self) -> Option<IssuingDistPoint> {
+        unsafe {
+            let idp = ffi::X509_CRL_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_issuing_distribution_point,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            IssuingDistPoint::from_ptr_opt(idp as *mut _)
+        }
+    }
+
+    /// Returns the entries of the CRL, if it contains any.
+    ///
+    /// This corresponds to [`X509_CRL_get_REVOKED`].
+    ///
+    /// [`X509_CRL_get_REVOKED`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get_REVOKED.html
+    pub fn revoked(&self) -> Option<&StackRef<X509Revoked>> {
+        unsafe {
+            let revoked = X509_CRL_get_REVOKED(self.as_ptr());
+            StackRef::from_const_ptr_opt(revoked)
+        }
+    }
+
+    /// Check if the CRL is signed using the given public key.
+    ///
+    /// Only the signature is checked: no other checks (such as the validity period) are performed.
+    ///
+    /// Returns `true` if verification succeeds.
+    ///
+    /// This corresponds to [`X509_CRL_verify`].
+    ///
+    /// [`X509_CRL_verify`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_verify.html
+    pub fn verify<T>(&self, key: &PKeyRef<T>) -> Result<bool, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe { cvt_n(ffi::X509_CRL_verify(self.as_ptr(), key.as_ptr())).map(|n| n != 0) }
+    }
+}
+
 impl Stackable for X509CRL {
     type StackType = ffi::stack_st_X509_CRL;
 }
+
+/// The reason a certificate was revoked.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CrlReason(c_int);
+
+impl CrlReason {
+    pub const UNSPECIFIED: CrlReason = CrlReason(ffi::CRL_REASON_UNSPECIFIED);
+    pub const KEY_COMPROMISE: CrlReason = CrlReason(ffi::CRL_REASON_KEY_COMPROMISE);
+    pub const CA_COMPROMISE: CrlReason = CrlReason(ffi::CRL_REASON_CA_COMPROMISE);
+    pub const AFFILIATION_CHANGED: CrlReason = CrlReason(ffi::CRL_REASON_AFFILIATION_CHANGED);
+    pub const SUPERSEDED: CrlReason = CrlReason(ffi::CRL_REASON_SUPERSEDED);
+    pub const CESSATION_OF_OPERATION: CrlReason = CrlReason(ffi::CRL_REASON_CESSATION_OF_OPERATION);
+    pub const CERTIFICATE_HOLD: CrlReason = CrlReason(ffi::CRL_REASON_CERTIFICATE_HOLD);
+    pub const REMOVE_FROM_CRL: CrlReason = CrlReason(ffi::CRL_REASON_REMOVE_FROM_CRL);
+    pub const PRIVILEGE_WITHDRAWN: CrlReason = CrlReason(ffi::CRL_REASON_PRIVILEGE_WITHDRAWN);
+    pub const AA_COMPROMISE: CrlReason = CrlReason(ffi::CRL_REASON_AA_COMPROMISE);
+
+    pub fn from_raw(raw: c_int) -> CrlReason {
+        CrlReason(raw)
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// A builder used to construct an `X509Revoked`.
+pub struct X509RevokedBuilder(X509Revoked);
+
+impl X509RevokedBuilder {
+    /// Creates a new builder.
+    pub fn new() -> Result<X509RevokedBuilder, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::X509_REVOKED_new()).map(|p| X509RevokedBuilder(X509Revoked(p)))
+        }
+    }
+
+    /// Sets the serial number of the revoked certificate.
+    pub fn set_serial_number(&mut self, serial_number: &Asn1IntegerRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_REVOKED_set_serialNumber(
+                self.0.as_ptr(),
+                serial_number.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the date at which the certificate was revoked.
+    pub fn set_revocation_date(&mut self, date: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_REVOKED_set_revocationDate(
+                self.0.as_ptr(),
+                date.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the reason code entry extension.
+    ///
+    /// This corresponds to [`X509_REVOKED_add1_ext_i2d`] called with `NID_crl_reason`.
+    ///
+    /// [`X509_REVOKED_add1_ext_i2d`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REVOKED_add1_ext_i2d.html
+    pub fn set_reason_code(&mut self, reason: CrlReason) -> Result<(), ErrorStack> {
+        unsafe {
+            let value = cvt_p(ffi::ASN1_ENUMERATED_new())?;
+            let r =
+                cvt(ffi::ASN1_ENUMERATED_set(value, reason.as_raw() as c_long)).and_then(|_| {
+                    cvt(ffi::X509_REVOKED_add1_ext_i2d(
+                        self.0.as_ptr(),
+                        ffi::NID_crl_reason,
+                        value as *mut _,
+                        0,
+                        ffi::X509V3_ADD_REPLACE,
+                    ))
+                });
+            ffi::ASN1_ENUMERATED_free(value);
+            r.map(|_| ())
+        }
+    }
+
+    /// Sets the invalidity date entry extension.
+    ///
+    /// This is the date at which it is known or suspected that the private key was compromised
+    /// or that the certificate otherwise became invalid.
+    ///
+    /// This corresponds to [`X509_REVOKED_add1_ext_i2d`] called with `NID_invalidity_date`.
+    ///
+    /// [`X509_REVOKED_add1_ext_i2d`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REVOKED_add1_ext_i2d.html
+    pub fn set_invalidity_date(&mut self, date: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            let value = cvt_p(ffi::ASN1_TIME_to_generalizedtime(
+                date.as_ptr(),
+                ptr::null_mut(),
+            ))?;
+            let value = Asn1GeneralizedTime::from_ptr(value);
+            cvt(ffi::X509_REVOKED_add1_ext_i2d(
+                self.0.as_ptr(),
+                ffi::NID_invalidity_date,
+                value.as_ptr() as *mut _,
+                0,
+                ffi::X509V3_ADD_REPLACE,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Consumes the builder, returning the CRL entry.
+    pub fn build(self) -> X509Revoked {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::X509_REVOKED;
+    fn drop = ffi::X509_REVOKED_free;
+
+    /// An entry of a certificate revocation list.
+    pub struct X509Revoked;
+    /// Reference to `X509Revoked`.
+    pub struct X509RevokedRef;
+}
+
+impl X509Revoked {
+    /// Returns a new builder.
+    pub fn builder() -> Result<X509RevokedBuilder, ErrorStack> {
+        X509RevokedBuilder::new()
+    }
+}
+
+impl X509RevokedRef {
+    /// Returns the serial number of the revoked certificate.
+    ///
+    /// This corresponds to [`X509_REVOKED_get0_serialNumber`].
+    ///
+    /// [`X509_REVOKED_get0_serialNumber`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REVOKED_get0_serialNumber.html
+    pub fn serial_number(&self) -> &Asn1IntegerRef {
+        unsafe {
+            let r = X509_REVOKED_get0_serialNumber(self.as_ptr());
+            Asn1IntegerRef::from_const_ptr_opt(r).expect("serial number must not be null")
+        }
+    }
+
+    /// Returns the date at which the certificate was revoked.
+    ///
+    /// This corresponds to [`X509_REVOKED_get0_revocationDate`].
+    ///
+    /// [`X509_REVOKED_get0_revocationDate`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REVOKED_get0_revocationDate.html
+    pub fn revocation_date(&self) -> &Asn1TimeRef {
+        unsafe {
+            let date = X509_REVOKED_get0_revocationDate(self.as_ptr());
+            Asn1TimeRef::from_const_ptr_opt(date).expect("revocation date must not be null")
+        }
+    }
+
+    /// Returns the value of the reason code entry extension, if it exists.
+    ///
+    /// This corresponds to [`X509_REVOKED_get_ext_d2i`] called with `NID_crl_reason`.
+    ///
+    /// [`X509_REVOKED_get_ext_d2i`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REVOKED_get_ext_d2i.html
+    pub fn reason_code(&self) -> Option<CrlReason> {
+        unsafe {
+            let reason = ffi::X509_REVOKED_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_crl_reason,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            ) as *mut ffi::ASN1_ENUMERATED;
+            if reason.is_null() {
+                return None;
+            }
+            let raw = ffi::ASN1_ENUMERATED_get(reason);
+            ffi::ASN1_ENUMERATED_free(reason);
+            Some(CrlReason(raw as c_int))
+        }
+    }
+
+    /// Returns the value of the invalidity date entry extension, if it exists.
+    ///
+    /// This corresponds to [`X509_REVOKED_get_ext_d2i`] called with `NID_invalidity_date`.
+    ///
+    /// [`X509_REVOKED_get_ext_d2i`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REVOKED_get_ext_d2i.html
+    pub fn invalidity_date(&self) -> Option<Asn1GeneralizedTime> {
+        unsafe {
+            let date = ffi::X509_REVOKED_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_invalidity_date,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Asn1GeneralizedTime::from_ptr_opt(date as *mut _)
+        }
+    }
+}
+
+impl Stackable for X509Revoked {
+    type StackType = ffi::stack_st_X509_REVOKED;
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::ISSUING_DIST_POINT;
+    fn drop = ffi::ISSUING_DIST_POINT_free;
+
+    /// The issuing distribution point CRL extension.
+    pub struct IssuingDistPoint;
+    /// Reference to `IssuingDistPoint`.
+    pub struct IssuingDistPointRef;
+}
+
+impl IssuingDistPointRef {
+    /// Returns the full name of the distribution point, if it is specified that way.
+    pub fn full_name(&self) -> Option<&StackRef<GeneralName>> {
+        unsafe {
+            let dp = (*self.as_ptr()).distpoint;
+            if dp.is_null() || (*dp).type_ != 0 {
+                return None;
+            }
+            StackRef::from_const_ptr_opt((*dp).name as *const ffi::stack_st_GENERAL_NAME)
+        }
+    }
+
+    /// Returns `true` if the CRL only contains end entity certificates.
+    pub fn only_user_certs(&self) -> bool {
+        unsafe { (*self.as_ptr()).onlyuser > 0 }
+    }
+
+    /// Returns `true` if the CRL only contains CA certificates.
+    pub fn only_ca_certs(&self) -> bool {
+        unsafe { (*self.as_ptr()).onlyCA > 0 }
+    }
+
+    /// Returns `true` if the CRL only contains attribute certificates.
+    pub fn only_attribute_certs(&self) -> bool {
+        unsafe { (*self.as_ptr()).onlyattr > 0 }
+    }
+
+    /// Returns `true` if the CRL is an indirect CRL.
+    pub fn indirect_crl(&self) -> bool {
+        unsafe { (*self.as_ptr()).indirectCRL > 0 }
+    }
+
+    /// Returns the bit string of revocation reasons covered by the CRL, if it is restricted.
+    ///
+    /// Bit `n` is set if the CRL covers the reason with value `n`.
+    pub fn only_some_reasons(&self) -> Option<&Asn1BitStringRef> {
+        unsafe { Asn1BitStringRef::from_const_ptr_opt((*self.as_ptr()).onlysomereasons) }
+    }
+}
+
+cfg_if! {
+    if #[cfg(any(ossl110, libressl281))] {
+        use ffi::{
+            X509_CRL_get_issuer, X509_CRL_get0_lastUpdate, X509_CRL_get0_nextUpdate,
+            X509_CRL_get_REVOKED,
+        };
+    } else {
+        #[allow(bad_style)]
+        unsafe fn X509_CRL_get_issuer(x: *mut ffi::X509_CRL) -> *mut ffi::X509_NAME {
+            (*(*x).crl).issuer
+        }
+
+        #[allow(bad_style)]
+        unsafe fn X509_CRL_get0_lastUpdate(x: *mut ffi::X509_CRL) -> *mut ffi::ASN1_TIME {
+            (*(*x).crl).lastUpdate
+        }
+
+        #[allow(bad_style)]
+        unsafe fn X509_CRL_get0_nextUpdate(x: *mut ffi::X509_CRL) -> *mut ffi::ASN1_TIME {
+            (*(*x).crl).nextUpdate
+        }
+
+        #[allow(bad_style)]
+        unsafe fn X509_CRL_get_REVOKED(x: *mut ffi::X509_CRL) -> *mut ffi::stack_st_X509_REVOKED {
+            (*(*x).crl).revoked
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(any(ossl110, libressl273))] {
+        use ffi::X509_CRL_get0_signature;
+    } else {
+        #[allow(bad_style)]
+        unsafe fn X509_CRL_get0_signature(
+            x: *const ffi::X509_CRL,
+            psig: *mut *const ffi::ASN1_BIT_STRING,
+            palg: *mut *const ffi::X509_ALGOR,
+        ) {
+            if !psig.is_null() {
+                *psig = (*x).signature;
+            }
+            if !palg.is_null() {
+                *palg = (*x).sig_alg;
+            }
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(any(ossl110, libressl270))] {
+        use ffi::{X509_CRL_set1_lastUpdate, X509_CRL_set1_nextUpdate};
+    } else {
+        use ffi::{
+            X509_CRL_set_lastUpdate as X509_CRL_set1_lastUpdate,
+            X509_CRL_set_nextUpdate as X509_CRL_set1_nextUpdate,
+        };
+    }
+}
+
+cfg_if! {
+    if #[cfg(ossl110)] {
+        use ffi::X509_CRL_get_version;
+    } else {
+        #[allow(bad_style)]
+        unsafe fn X509_CRL_get_version(x: *mut ffi::X509_CRL) -> ::libc::c_long {
+            ffi::ASN1_INTEGER_get((*(*x).crl).version)
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(any(ossl110, libressl270))] {
+        use ffi::{X509_REVOKED_get0_revocationDate, X509_REVOKED_get0_serialNumber};
+    } else {
+        #[allow(bad_style)]
+        unsafe fn X509_REVOKED_get0_serialNumber(
+            x: *mut ffi::X509_REVOKED,
+        ) -> *mut ffi::ASN1_INTEGER {
+            (*x).serialNumber
+        }
+
+        #[allow(bad_style)]
+        unsafe fn X509_REVOKED_get0_revocationDate(
+            x: *mut ffi::X509_REVOKED,
+        ) -> *mut ffi::ASN1_TIME {
+            (*x).revocationDate
+        }
+    }
+}
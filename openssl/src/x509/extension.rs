@@ -1,4 +1,4 @@
-//! Add extensions to an `X509` certificate or certificate request.
+//! Add extensions to an `X509` certificate, certificate request or CRL.
 //!
 //! The extensions defined for X.509 v3 certificates provide methods for
 //! associating additional attributes with users or public keys and for
@@ -16,8 +16,13 @@
 //!
 //! let extension: X509Extension = bc.build().unwrap();
 //! ```
+use foreign_types::ForeignType;
+use libc::c_int;
 use std::fmt::Write;
 
+use crate::asn1::Asn1Integer;
+use crate::bn::BigNumRef;
+use crate::cvt_p;
 use crate::error::ErrorStack;
 use crate::nid::Nid;
 use crate::x509::{X509Extension, X509v3Context};
@@ -556,3 +561,38 @@ fn append(value: &mut String, first: &mut bool, should: bool, element: &str) {
     *first = false;
     value.push_str(element);
 }
+
+/// An extension which conveys a monotonically increasing sequence number for a CRL.
+pub struct CrlNumber {
+    critical: bool,
+    number: Asn1Integer,
+}
+
+impl CrlNumber {
+    /// Construct a new `CrlNumber` extension.
+    pub fn new(number: &BigNumRef) -> Result<CrlNumber, ErrorStack> {
+        Ok(CrlNumber {
+            critical: false,
+            number: number.to_asn1_integer()?,
+        })
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    pub fn critical(&mut self) -> &mut CrlNumber {
+        self.critical = true;
+        self
+    }
+
+    /// Return the `CrlNumber` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::X509V3_EXT_i2d(
+                ffi::NID_crl_number,
+                self.critical as c_int,
+                self.number.as_ptr() as *mut _,
+            ))
+            .map(|p| X509Extension::from_ptr(p))
+        }
+    }
+}
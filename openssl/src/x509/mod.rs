@@ -9,24 +9,31 @@
 
 use cfg_if::cfg_if;
 use foreign_types::{ForeignType, ForeignTypeRef};
+#[cfg(ossl110)]
+use libc::c_void;
 use libc::{c_int, c_long};
 use std::error::Error;
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
+use std::net::IpAddr;
 use std::path::Path;
 use std::ptr;
 use std::slice;
 use std::str;
+#[cfg(ossl110)]
+use std::sync::Arc;
 
 use crate::asn1::{
     Asn1BitStringRef, Asn1IntegerRef, Asn1ObjectRef, Asn1StringRef, Asn1TimeRef, Asn1Type,
 };
-use crate::bio::MemBioSlice;
+use crate::bio::{MemBio, MemBioSlice};
 use crate::conf::ConfRef;
 use crate::error::ErrorStack;
 use crate::ex_data::Index;
+#[cfg(ossl110)]
+use crate::ex_data::{self, free_data_box};
 use crate::hash::{DigestBytes, MessageDigest};
 use crate::nid::Nid;
 use crate::pkey::{HasPrivate, HasPublic, PKey, PKeyRef, Public};
@@ -34,7 +41,9 @@ use crate::ssl::SslRef;
 use crate::stack::{Stack, StackRef, Stackable};
 use crate::string::OpensslString;
 use crate::util::{ForeignTypeExt, ForeignTypeRefExt};
-use crate::x509::verify::{X509VerifyParam, X509VerifyParamRef};
+#[cfg(ossl110)]
+use crate::x509::crl::X509CRLRef;
+use crate::x509::verify::{X509CheckFlags, X509PurposeId, X509VerifyParam, X509VerifyParamRef};
 use crate::{cvt, cvt_n, cvt_p};
 
 #[cfg(any(ossl102, libressl261))]
@@ -76,6 +85,24 @@ impl X509StoreContext {
             cvt_p(ffi::X509_STORE_CTX_new()).map(X509StoreContext)
         }
     }
+
+    #[cfg(ossl110)]
+    fn cached_ex_index<T>() -> Result<Index<X509StoreContext, T>, ErrorStack>
+    where
+        T: 'static + Sync + Send,
+    {
+        ex_data::cached_index(|| unsafe {
+            ffi::init();
+            cvt_n(ffi::X509_STORE_CTX_get_ex_new_index(
+                0,
+                ptr::null_mut(),
+                None,
+                None,
+                Some(free_data_box::<T>),
+            ))
+            .map(|idx| Index::from_raw(idx))
+        })
+    }
 }
 
 impl X509StoreContextRef {
@@ -211,6 +238,107 @@ impl X509StoreContextRef {
         let ptr = unsafe { ffi::X509_STORE_CTX_get0_param(self.as_ptr()) };
         unsafe { X509VerifyParamRef::from_ptr(ptr) }
     }
+
+    /// Sets the callback used by `verify_cert`, taking precedence over that of the store.
+    ///
+    /// The callback is invoked for every certificate in the chain and for each error found, with
+    /// the result of OpenSSL's own checks. Returning `true` accepts the certificate, overriding
+    /// any error, and returning `false` aborts verification.
+    ///
+    /// This must be called after `init`, which resets the callback.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`X509_STORE_CTX_set_verify_cb`].
+    ///
+    /// [`X509_STORE_CTX_set_verify_cb`]: https://www.openssl.org/docs/manmaster/man3/X509_STORE_CTX_set_verify_cb.html
+    #[cfg(ossl110)]
+    pub fn set_verify_callback<F>(&mut self, callback: F) -> Result<(), ErrorStack>
+    where
+        F: Fn(bool, &mut X509StoreContextRef) -> bool + 'static + Sync + Send,
+    {
+        unsafe {
+            // this needs to be in an Arc since the callback can register a new callback!
+            let idx = X509StoreContext::cached_ex_index::<Arc<F>>()?;
+            let old = ffi::X509_STORE_CTX_get_ex_data(self.as_ptr(), idx.as_raw());
+            if !old.is_null() {
+                drop(Box::<Arc<F>>::from_raw(old as *mut Arc<F>));
+            }
+            let callback = Box::into_raw(Box::new(Arc::new(callback))) as *mut c_void;
+            ffi::X509_STORE_CTX_set_ex_data(self.as_ptr(), idx.as_raw(), callback);
+            ffi::X509_STORE_CTX_set_verify_cb(self.as_ptr(), Some(raw_verify::<F>));
+        }
+        Ok(())
+    }
+
+    /// Returns the issuer of the current certificate, if it has been found.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`X509_STORE_CTX_get0_current_issuer`].
+    ///
+    /// [`X509_STORE_CTX_get0_current_issuer`]: https://www.openssl.org/docs/manmaster/man3/X509_STORE_CTX_get0_current_issuer.html
+    #[cfg(ossl110)]
+    pub fn current_issuer(&self) -> Option<&X509Ref> {
+        unsafe {
+            let ptr = ffi::X509_STORE_CTX_get0_current_issuer(self.as_ptr());
+            X509Ref::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns the CRL being checked, if revocation checking is in progress.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`X509_STORE_CTX_get0_current_crl`].
+    ///
+    /// [`X509_STORE_CTX_get0_current_crl`]: https://www.openssl.org/docs/manmaster/man3/X509_STORE_CTX_get0_current_crl.html
+    #[cfg(ossl110)]
+    pub fn current_crl(&self) -> Option<&X509CRLRef> {
+        unsafe {
+            let ptr = ffi::X509_STORE_CTX_get0_current_crl(self.as_ptr());
+            X509CRLRef::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns the number of untrusted certificates at the start of the chain.
+    ///
+    /// Certificates at this depth and above were found in the trust store.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`X509_STORE_CTX_get_num_untrusted`].
+    ///
+    /// [`X509_STORE_CTX_get_num_untrusted`]: https://www.openssl.org/docs/manmaster/man3/X509_STORE_CTX_get_num_untrusted.html
+    #[cfg(ossl110)]
+    pub fn num_untrusted(&self) -> usize {
+        unsafe { ffi::X509_STORE_CTX_get_num_untrusted(self.as_ptr()) as usize }
+    }
+
+    /// Determines if the certificate at `depth` in the chain was found in the trust store.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    pub fn is_trusted(&self, depth: u32) -> bool {
+        depth as usize >= self.num_untrusted()
+    }
+}
+
+#[cfg(ossl110)]
+extern "C" fn raw_verify<F>(preverify_ok: c_int, ctx: *mut ffi::X509_STORE_CTX) -> c_int
+where
+    F: Fn(bool, &mut X509StoreContextRef) -> bool + 'static + Sync + Send,
+{
+    unsafe {
+        let ctx = X509StoreContextRef::from_ptr_mut(ctx);
+        let idx = X509StoreContext::cached_ex_index::<Arc<F>>().expect("BUG: verify index missing");
+        let verify = ctx
+            .ex_data(idx)
+            .expect("BUG: verify callback missing")
+            .clone();
+
+        verify(preverify_ok != 0, ctx) as c_int
+    }
 }
 
 /// A builder used to construct an `X509`.
@@ -547,6 +675,91 @@ impl X509Ref {
         }
     }
 
+    /// Checks whether this certificate may be used for `purpose`.
+    ///
+    /// If `ca` is `true`, the certificate is checked as an issuer of certificates with that
+    /// purpose rather than as a leaf.
+    ///
+    /// This corresponds to [`X509_check_purpose`].
+    ///
+    /// [`X509_check_purpose`]: https://www.openssl.org/docs/manmaster/man3/X509_check_purpose.html
+    #[cfg(any(ossl102, libressl261))]
+    pub fn check_purpose(&self, purpose: X509PurposeId, ca: bool) -> Result<bool, ErrorStack> {
+        unsafe {
+            cvt_n(ffi::X509_check_purpose(
+                self.as_ptr(),
+                purpose.as_raw(),
+                ca as c_int,
+            ))
+            .map(|n| n > 0)
+        }
+    }
+
+    /// Returns `true` if this certificate is a CA certificate.
+    ///
+    /// This corresponds to [`X509_check_ca`].
+    ///
+    /// [`X509_check_ca`]: https://www.openssl.org/docs/manmaster/man3/X509_check_ca.html
+    pub fn check_ca(&self) -> bool {
+        unsafe { ffi::X509_check_ca(self.as_ptr()) > 0 }
+    }
+
+    /// Checks whether this certificate matches the DNS hostname `host`.
+    ///
+    /// This corresponds to [`X509_check_host`].
+    ///
+    /// [`X509_check_host`]: https://www.openssl.org/docs/manmaster/man3/X509_check_host.html
+    #[cfg(any(ossl102, libressl261))]
+    pub fn check_host(&self, host: &str, flags: X509CheckFlags) -> Result<bool, ErrorStack> {
+        unsafe {
+            cvt_n(ffi::X509_check_host(
+                self.as_ptr(),
+                host.as_ptr() as *const _,
+                host.len(),
+                flags.bits(),
+                ptr::null_mut(),
+            ))
+            .map(|n| n == 1)
+        }
+    }
+
+    /// Checks whether this certificate matches the RFC 822 email address `email`.
+    ///
+    /// This corresponds to [`X509_check_email`].
+    ///
+    /// [`X509_check_email`]: https://www.openssl.org/docs/manmaster/man3/X509_check_email.html
+    #[cfg(any(ossl102, libressl261))]
+    pub fn check_email(&self, email: &str, flags: X509CheckFlags) -> Result<bool, ErrorStack> {
+        unsafe {
+            cvt_n(ffi::X509_check_email(
+                self.as_ptr(),
+                email.as_ptr() as *const _,
+                email.len(),
+                flags.bits(),
+            ))
+            .map(|n| n == 1)
+        }
+    }
+
+    /// Checks whether this certificate matches the IPv4 or IPv6 address `ip`.
+    ///
+    /// This corresponds to [`X509_check_ip`].
+    ///
+    /// [`X509_check_ip`]: https://www.openssl.org/docs/manmaster/man3/X509_check_ip.html
+    #[cfg(any(ossl102, libressl261))]
+    pub fn check_ip(&self, ip: IpAddr, flags: X509CheckFlags) -> Result<bool, ErrorStack> {
+        unsafe {
+            let (buf, len) = verify::ip_octets(ip);
+            cvt_n(ffi::X509_check_ip(
+                self.as_ptr(),
+                buf.as_ptr(),
+                len,
+                flags.bits(),
+            ))
+            .map(|n| n == 1)
+        }
+    }
+
     /// Returns certificate version. If this certificate has no explicit version set, it defaults to
     /// version 1.
     ///
@@ -611,6 +824,21 @@ impl X509Ref {
         to_der,
         ffi::i2d_X509
     }
+
+    /// Returns a human readable representation of the certificate.
+    ///
+    /// This is the format printed by `openssl x509 -text`.
+    ///
+    /// This corresponds to [`X509_print`].
+    ///
+    /// [`X509_print`]: https://www.openssl.org/docs/man3.0/man3/X509_print_ex.html
+    pub fn to_text(&self) -> Result<Vec<u8>, ErrorStack> {
+        let bio = MemBio::new()?;
+        unsafe {
+            cvt(ffi::X509_print(bio.as_ptr(), self.as_ptr()))?;
+        }
+        Ok(bio.get_buf().to_owned())
+    }
 }
 
 impl ToOwned for X509Ref {
@@ -1283,6 +1511,21 @@ impl X509ReqRef {
         ffi::i2d_X509_REQ
     }
 
+    /// Returns a human readable representation of the certificate request.
+    ///
+    /// This is the format printed by `openssl req -text`.
+    ///
+    /// This corresponds to [`X509_REQ_print`].
+    ///
+    /// [`X509_REQ_print`]: https://www.openssl.org/docs/man3.0/man3/X509_print_ex.html
+    pub fn to_text(&self) -> Result<Vec<u8>, ErrorStack> {
+        let bio = MemBio::new()?;
+        unsafe {
+            cvt(ffi::X509_REQ_print(bio.as_ptr(), self.as_ptr()))?;
+        }
+        Ok(bio.get_buf().to_owned())
+    }
+
     /// Returns the numerical value of the version field of the certificate request.
     ///
     /// This corresponds to [`X509_REQ_get_version`]
@@ -36,17 +36,50 @@
 //! ```
 
 use cfg_if::cfg_if;
-use foreign_types::ForeignTypeRef;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::c_int;
+#[cfg(ossl110)]
+use libc::{c_char, c_long, c_void};
+#[cfg(ossl110)]
+use once_cell::sync::OnceCell;
+use std::ffi::CString;
 use std::mem;
+use std::path::Path;
+use std::ptr;
 
+use crate::bio::MemBioSlice;
+#[cfg(ossl110)]
+use crate::cvt_n;
 use crate::error::ErrorStack;
-use crate::stack::StackRef;
+#[cfg(ossl110)]
+use crate::ex_data::{self, free_data_box, Index};
+use crate::stack::{Stack, StackRef};
 use crate::x509::crl::X509CRLRef;
 #[cfg(any(ossl102, libressl261))]
 use crate::x509::verify::{X509VerifyFlags, X509VerifyParamRef};
+#[cfg(ossl110)]
+use crate::x509::X509StoreContextRef;
 use crate::x509::{X509Object, X509Ref};
 use crate::{cvt, cvt_p};
 
+mod info {
+    use crate::stack::Stackable;
+
+    foreign_type_and_impl_send_sync! {
+        type CType = ffi::X509_INFO;
+        fn drop = ffi::X509_INFO_free;
+
+        pub struct X509Info;
+        pub struct X509InfoRef;
+    }
+
+    impl Stackable for X509Info {
+        type StackType = ffi::stack_st_X509_INFO;
+    }
+}
+
+use self::info::X509Info;
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_STORE;
     fn drop = ffi::X509_STORE_free;
@@ -97,6 +130,46 @@ impl X509StoreBuilderRef {
         unsafe { cvt(ffi::X509_STORE_set_default_paths(self.as_ptr())).map(|_| ()) }
     }
 
+    /// Adds every certificate and CRL in a PEM bundle to the store.
+    ///
+    /// Other PEM blocks, such as private keys, are skipped. Certificates and CRLs which are
+    /// already in the store are not an error, and are included in the counts.
+    ///
+    /// The whole bundle is parsed before anything is added, so a malformed bundle leaves the
+    /// store unchanged. If adding an entry fails, the entries before it remain in the store.
+    ///
+    /// This corresponds to [`PEM_X509_INFO_read_bio`].
+    ///
+    /// [`PEM_X509_INFO_read_bio`]: https://www.openssl.org/docs/manmaster/man3/PEM_X509_INFO_read_bio.html
+    pub fn add_pem_bundle(&mut self, pem: &[u8]) -> Result<X509BundleCounts, ErrorStack> {
+        let bio = MemBioSlice::new(pem)?;
+        unsafe { add_x509_infos(self.as_ptr(), bio.as_ptr()) }
+    }
+
+    /// Adds every certificate and CRL in a PEM bundle file to the store.
+    ///
+    /// Unlike a `File` lookup, this reports how many certificates and CRLs were read. Entries
+    /// are added as described for `add_pem_bundle`.
+    ///
+    /// This corresponds to [`PEM_X509_INFO_read_bio`].
+    ///
+    /// [`PEM_X509_INFO_read_bio`]: https://www.openssl.org/docs/manmaster/man3/PEM_X509_INFO_read_bio.html
+    pub fn load_pem_bundle<P: AsRef<Path>>(
+        &mut self,
+        file: P,
+    ) -> Result<X509BundleCounts, ErrorStack> {
+        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe {
+            let bio = cvt_p(ffi::BIO_new_file(
+                file.as_ptr(),
+                b"r\0".as_ptr() as *const _,
+            ))?;
+            let r = add_x509_infos(self.as_ptr(), bio);
+            ffi::BIO_free_all(bio);
+            r
+        }
+    }
+
     /// Adds a lookup method to the store.
     ///
     /// This corresponds to [`X509_STORE_add_lookup`].
@@ -119,6 +192,122 @@ impl X509StoreBuilderRef {
     pub fn set_flags(&mut self, flags: X509VerifyFlags) -> Result<(), ErrorStack> {
         unsafe { cvt(ffi::X509_STORE_set_flags(self.as_ptr(), flags.bits())).map(|_| ()) }
     }
+
+    /// Sets the callback used to verify certificate chains with this store.
+    ///
+    /// The callback is invoked for every certificate in the chain and for each error found, with
+    /// the result of OpenSSL's own checks. Returning `true` accepts the certificate, overriding
+    /// any error, and returning `false` aborts verification.
+    ///
+    /// A callback set on an `X509StoreContextRef`, or by an `SslContextBuilder`, takes precedence.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// This corresponds to [`X509_STORE_set_verify_cb`].
+    ///
+    /// [`X509_STORE_set_verify_cb`]: https://www.openssl.org/docs/manmaster/man3/X509_STORE_set_verify_cb.html
+    #[cfg(ossl110)]
+    pub fn set_verify_callback<F>(&mut self, callback: F) -> Result<(), ErrorStack>
+    where
+        F: Fn(bool, &mut X509StoreContextRef) -> bool + 'static + Sync + Send,
+    {
+        unsafe {
+            let idx = cached_ex_index::<F>()?.as_raw();
+            let old = ffi::X509_STORE_get_ex_data(self.as_ptr(), idx);
+            if !old.is_null() {
+                drop(Box::<F>::from_raw(old as *mut F));
+            }
+            let callback = Box::into_raw(Box::new(callback)) as *mut c_void;
+            ffi::X509_STORE_set_ex_data(self.as_ptr(), idx, callback);
+            ffi::X509_STORE_set_verify_cb(self.as_ptr(), Some(raw_verify::<F>));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(ossl110)]
+fn cached_ex_index<T>() -> Result<Index<X509Store, T>, ErrorStack>
+where
+    T: 'static + Sync + Send,
+{
+    ex_data::cached_index(|| unsafe {
+        ffi::init();
+        cvt_n(ffi::CRYPTO_get_ex_new_index(
+            ffi::CRYPTO_EX_INDEX_X509_STORE,
+            0,
+            ptr::null_mut(),
+            None,
+            None,
+            Some(free_data_box::<T>),
+        ))
+        .map(|idx| Index::from_raw(idx))
+    })
+}
+
+#[cfg(ossl110)]
+extern "C" fn raw_verify<F>(preverify_ok: c_int, ctx: *mut ffi::X509_STORE_CTX) -> c_int
+where
+    F: Fn(bool, &mut X509StoreContextRef) -> bool + 'static + Sync + Send,
+{
+    unsafe {
+        let store = ffi::X509_STORE_CTX_get0_store(ctx);
+        let idx = cached_ex_index::<F>().expect("BUG: verify index missing");
+        let verify = ffi::X509_STORE_get_ex_data(store, idx.as_raw()) as *const F;
+        assert!(!verify.is_null(), "BUG: verify callback missing");
+
+        (*verify)(preverify_ok != 0, X509StoreContextRef::from_ptr_mut(ctx)) as c_int
+    }
+}
+
+/// The number of certificates and CRLs read from a bundle, including those already in the store.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct X509BundleCounts {
+    pub certs: usize,
+    pub crls: usize,
+}
+
+unsafe fn add_x509_infos(
+    store: *mut ffi::X509_STORE,
+    bio: *mut ffi::BIO,
+) -> Result<X509BundleCounts, ErrorStack> {
+    let infos = cvt_p(ffi::PEM_X509_INFO_read_bio(
+        bio,
+        ptr::null_mut(),
+        None,
+        ptr::null_mut(),
+    ))?;
+    let infos = Stack::<X509Info>::from_ptr(infos);
+
+    let mut counts = X509BundleCounts { certs: 0, crls: 0 };
+    for info in &infos {
+        let info = info.as_ptr();
+        if !(*info).x509.is_null() {
+            cvt_added(ffi::X509_STORE_add_cert(store, (*info).x509))?;
+            counts.certs += 1;
+        }
+        if !(*info).crl.is_null() {
+            cvt_added(ffi::X509_STORE_add_crl(store, (*info).crl))?;
+            counts.crls += 1;
+        }
+    }
+    Ok(counts)
+}
+
+// OpenSSL before 1.1.1 reports an error when an object is already in the store
+unsafe fn cvt_added(r: c_int) -> Result<(), ErrorStack> {
+    if r > 0 {
+        return Ok(());
+    }
+
+    let err = ffi::ERR_peek_error();
+    if ffi::ERR_GET_LIB(err) == ffi::ERR_LIB_X509
+        && ffi::ERR_GET_REASON(err) == ffi::X509_R_CERT_ALREADY_IN_HASH_TABLE
+    {
+        ffi::ERR_get_error();
+        Ok(())
+    } else {
+        Err(ErrorStack::get())
+    }
 }
 
 generic_foreign_type_and_impl_send_sync! {
@@ -174,6 +363,186 @@ impl X509LookupRef<HashDir> {
     }
 }
 
+/// Marker type corresponding to the [`X509_LOOKUP_file`] lookup method.
+///
+/// [`X509_LOOKUP_file`]: https://www.openssl.org/docs/manmaster/man3/X509_LOOKUP_file.html
+pub struct File;
+
+impl X509Lookup<File> {
+    /// Lookup method that loads certificates and CRLs from files into memory
+    /// as soon as they are added.
+    ///
+    /// This corresponds to [`X509_LOOKUP_file`].
+    ///
+    /// [`X509_LOOKUP_file`]: https://www.openssl.org/docs/manmaster/man3/X509_LOOKUP_file.html
+    pub fn file() -> &'static X509LookupMethodRef<File> {
+        unsafe { X509LookupMethodRef::from_ptr(ffi::X509_LOOKUP_file()) }
+    }
+}
+
+impl X509LookupRef<File> {
+    /// Loads certificates from a file, returning the number loaded. Must be
+    /// used with `X509Lookup::file`.
+    ///
+    /// This corresponds to [`X509_load_cert_file`].
+    ///
+    /// [`X509_load_cert_file`]: https://www.openssl.org/docs/manmaster/man3/X509_load_cert_file.html
+    pub fn load_cert_file<P: AsRef<Path>>(
+        &mut self,
+        file: P,
+        file_type: crate::ssl::SslFiletype,
+    ) -> Result<usize, ErrorStack> {
+        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe {
+            cvt(ffi::X509_load_cert_file(
+                self.as_ptr(),
+                file.as_ptr(),
+                file_type.as_raw(),
+            ))
+            .map(|n| n as usize)
+        }
+    }
+
+    /// Loads CRLs from a file, returning the number loaded. Must be used
+    /// with `X509Lookup::file`.
+    ///
+    /// This corresponds to [`X509_load_crl_file`].
+    ///
+    /// [`X509_load_crl_file`]: https://www.openssl.org/docs/manmaster/man3/X509_load_crl_file.html
+    pub fn load_crl_file<P: AsRef<Path>>(
+        &mut self,
+        file: P,
+        file_type: crate::ssl::SslFiletype,
+    ) -> Result<usize, ErrorStack> {
+        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe {
+            cvt(ffi::X509_load_crl_file(
+                self.as_ptr(),
+                file.as_ptr(),
+                file_type.as_raw(),
+            ))
+            .map(|n| n as usize)
+        }
+    }
+
+    /// Loads certificates and CRLs from a PEM bundle, returning the total
+    /// number loaded. Must be used with `X509Lookup::file`.
+    ///
+    /// This corresponds to [`X509_load_cert_crl_file`].
+    ///
+    /// [`X509_load_cert_crl_file`]: https://www.openssl.org/docs/manmaster/man3/X509_load_cert_crl_file.html
+    pub fn load_cert_crl_file<P: AsRef<Path>>(&mut self, file: P) -> Result<usize, ErrorStack> {
+        let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        unsafe {
+            cvt(ffi::X509_load_cert_crl_file(
+                self.as_ptr(),
+                file.as_ptr(),
+                ffi::X509_FILETYPE_PEM,
+            ))
+            .map(|n| n as usize)
+        }
+    }
+}
+
+/// Marker type corresponding to the lookup method returned by `X509Lookup::memory`.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+#[cfg(ossl110)]
+pub struct Memory;
+
+#[cfg(ossl110)]
+static MEMORY_METHOD: OnceCell<X509LookupMethod<Memory>> = OnceCell::new();
+
+#[cfg(ossl110)]
+const MEMORY_ADD_PEM_BUNDLE: c_int = 1;
+
+#[cfg(ossl110)]
+impl X509Lookup<Memory> {
+    /// Lookup method that loads certificates and CRLs from PEM bundles in memory as soon as
+    /// they are added.
+    ///
+    /// This is the counterpart of `X509Lookup::file` for bundles which are not on disk, such as
+    /// those embedded in the binary.
+    ///
+    /// This corresponds to [`X509_LOOKUP_meth_new`].
+    ///
+    /// [`X509_LOOKUP_meth_new`]: https://www.openssl.org/docs/manmaster/man3/X509_LOOKUP_meth_new.html
+    pub fn memory() -> Result<&'static X509LookupMethodRef<Memory>, ErrorStack> {
+        MEMORY_METHOD
+            .get_or_try_init(|| unsafe {
+                ffi::init();
+                let method = cvt_p(ffi::X509_LOOKUP_meth_new(
+                    b"Load certs from memory\0".as_ptr() as *const _,
+                ))
+                .map(|p| X509LookupMethod::from_ptr(p))?;
+                cvt(ffi::X509_LOOKUP_meth_set_ctrl(
+                    method.as_ptr(),
+                    Some(memory_ctrl),
+                ))?;
+                Ok(method)
+            })
+            .map(|method| &**method)
+    }
+}
+
+#[cfg(ossl110)]
+impl X509LookupRef<Memory> {
+    /// Loads every certificate and CRL in a PEM bundle, returning how many were read. Must be
+    /// used with `X509Lookup::memory`.
+    ///
+    /// Entries are added to the store as described for `X509StoreBuilderRef::add_pem_bundle`.
+    pub fn add_pem_bundle(&mut self, pem: &[u8]) -> Result<X509BundleCounts, ErrorStack> {
+        let mut bundle = PemBundle {
+            pem,
+            counts: X509BundleCounts { certs: 0, crls: 0 },
+        };
+        unsafe {
+            cvt(ffi::X509_LOOKUP_ctrl(
+                self.as_ptr(),
+                MEMORY_ADD_PEM_BUNDLE,
+                &mut bundle as *mut PemBundle<'_> as *const c_char,
+                0,
+                ptr::null_mut(),
+            ))?;
+        }
+        Ok(bundle.counts)
+    }
+}
+
+/// The argument of the `MEMORY_ADD_PEM_BUNDLE` control command.
+#[cfg(ossl110)]
+struct PemBundle<'a> {
+    pem: &'a [u8],
+    counts: X509BundleCounts,
+}
+
+#[cfg(ossl110)]
+unsafe extern "C" fn memory_ctrl(
+    ctx: *mut ffi::X509_LOOKUP,
+    cmd: c_int,
+    argc: *const c_char,
+    _argl: c_long,
+    _ret: *mut *mut c_char,
+) -> c_int {
+    if cmd != MEMORY_ADD_PEM_BUNDLE {
+        return 0;
+    }
+
+    let bundle = &mut *(argc as *mut PemBundle<'_>);
+    let r = MemBioSlice::new(bundle.pem)
+        .and_then(|bio| add_x509_infos(ffi::X509_LOOKUP_get_store(ctx), bio.as_ptr()));
+    match r {
+        Ok(counts) => {
+            bundle.counts = counts;
+            1
+        }
+        Err(e) => {
+            e.put();
+            0
+        }
+    }
+}
+
 generic_foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_LOOKUP_METHOD;
     fn drop = X509_LOOKUP_meth_free;
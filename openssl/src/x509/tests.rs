@@ -4,18 +4,22 @@ use crate::hash::MessageDigest;
 use crate::nid::Nid;
 use crate::pkey::{PKey, Private};
 use crate::rsa::Rsa;
+use crate::ssl::SslFiletype;
 use crate::stack::Stack;
+use crate::x509::crl::{CrlReason, X509Revoked, X509CRL};
 use crate::x509::extension::{
-    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
-    SubjectKeyIdentifier,
+    AuthorityKeyIdentifier, BasicConstraints, CrlNumber, ExtendedKeyUsage, KeyUsage,
+    SubjectAlternativeName, SubjectKeyIdentifier,
 };
-use crate::x509::store::X509StoreBuilder;
+use crate::x509::store::{X509BundleCounts, X509Lookup, X509StoreBuilder};
 #[cfg(any(ossl102, libressl261))]
-use crate::x509::verify::X509VerifyFlags;
+use crate::x509::verify::{X509CheckFlags, X509PurposeId, X509VerifyFlags, X509VerifyParam};
 #[cfg(ossl110)]
 use crate::x509::X509Builder;
 use crate::x509::{X509Name, X509Req, X509StoreContext, X509VerifyResult, X509};
 use hex::{self, FromHex};
+#[cfg(any(ossl102, libressl261))]
+use std::time::{Duration, UNIX_EPOCH};
 
 fn pkey() -> PKey<Private> {
     let rsa = Rsa::generate(2048).unwrap();
@@ -149,6 +153,22 @@ fn test_subject_alt_name() {
     assert_eq!(Some("http://www.example.com"), subject_alt_names[4].uri());
 }
 
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn test_check_identity() {
+    let cert = include_bytes!("../../test/alt_name_cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let flags = X509CheckFlags::empty();
+
+    assert!(cert.check_host("example.com", flags).unwrap());
+    assert!(!cert.check_host("www.example.com", flags).unwrap());
+    assert!(cert.check_email("test@example.com", flags).unwrap());
+    assert!(!cert.check_email("other@example.com", flags).unwrap());
+    assert!(cert.check_ip("127.0.0.1".parse().unwrap(), flags).unwrap());
+    assert!(cert.check_ip("::1".parse().unwrap(), flags).unwrap());
+    assert!(!cert.check_ip("10.0.0.1".parse().unwrap(), flags).unwrap());
+}
+
 #[test]
 fn test_subject_alt_name_iter() {
     let cert = include_bytes!("../../test/alt_name_cert.pem");
@@ -306,6 +326,33 @@ fn x509_req_builder() {
     assert!(req.verify(&pkey).unwrap());
 }
 
+#[test]
+fn test_to_text() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let text = String::from_utf8(cert.to_text().unwrap()).unwrap();
+    assert!(text.starts_with("Certificate:"));
+    assert!(text.contains("87:71:f7:bd:ee:98:2f:a5"));
+    assert!(text.contains("CN=foobar.com"));
+
+    let pkey = pkey();
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "foobar.com")
+        .unwrap();
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_subject_name(&name.build()).unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let req = builder.build();
+    let text = String::from_utf8(req.to_text().unwrap()).unwrap();
+    assert!(text.starts_with("Certificate Request:"));
+    assert!(text.contains("CN=foobar.com"));
+
+    let crl = X509CRL::from_pem(include_bytes!("../../test/crl.pem")).unwrap();
+    let text = String::from_utf8(crl.to_text().unwrap()).unwrap();
+    assert!(text.starts_with("Certificate Revocation List (CRL):"));
+    assert!(text.contains("8771F7BDEE982FA5"));
+}
+
 #[test]
 fn test_stack_from_pem() {
     let certs = include_bytes!("../../test/certs.pem");
@@ -377,6 +424,102 @@ fn test_verify_cert() {
     assert!(context.verify_cert().unwrap());
 }
 
+#[test]
+fn test_store_add_pem_bundle() {
+    let mut bundle = include_bytes!("../../test/root-ca.pem").to_vec();
+    bundle.extend_from_slice(include_bytes!("../../test/key.pem"));
+    bundle.extend_from_slice(include_bytes!("../../test/crl.pem"));
+    bundle.extend_from_slice(include_bytes!("../../test/cert.pem"));
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    let counts = store_bldr.add_pem_bundle(&bundle).unwrap();
+    assert_eq!(counts, X509BundleCounts { certs: 2, crls: 1 });
+
+    // adding the same certificates again is not an error
+    let counts = store_bldr
+        .add_pem_bundle(include_bytes!("../../test/certs.pem"))
+        .unwrap();
+    assert_eq!(counts, X509BundleCounts { certs: 2, crls: 0 });
+    assert_eq!(store_bldr.build().objects().len(), 3);
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    assert!(store_bldr
+        .add_pem_bundle(b"-----BEGIN CERTIFICATE-----\nAAAA\n")
+        .is_err());
+    assert_eq!(store_bldr.build().objects().len(), 0);
+}
+
+#[test]
+fn test_store_load_pem_bundle() {
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    let counts = store_bldr.load_pem_bundle("test/certs.pem").unwrap();
+    assert_eq!(counts, X509BundleCounts { certs: 2, crls: 0 });
+
+    assert!(store_bldr.load_pem_bundle("test/missing.pem").is_err());
+}
+
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn test_lookup_file() {
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    let lookup = store_bldr.add_lookup(X509Lookup::file()).unwrap();
+    assert_eq!(
+        lookup
+            .load_cert_file("test/root-ca.pem", SslFiletype::PEM)
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        lookup
+            .load_crl_file("test/crl.pem", SslFiletype::PEM)
+            .unwrap(),
+        1
+    );
+    assert_eq!(lookup.load_cert_crl_file("test/certs.pem").unwrap(), 2);
+    let mut store = store_bldr.build();
+
+    // the fixtures have expired, so verify at a time they were valid
+    let mut param = X509VerifyParam::new().unwrap();
+    param.set_time(UNIX_EPOCH + Duration::from_secs(1_577_836_800));
+    store.set_param(&param).unwrap();
+
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let mut context = X509StoreContext::new().unwrap();
+    context.init(&store, &cert, None).unwrap();
+    assert!(context.verify_cert().unwrap());
+}
+
+#[test]
+#[cfg(ossl110)]
+fn test_lookup_memory() {
+    let mut bundle = include_bytes!("../../test/root-ca.pem").to_vec();
+    bundle.extend_from_slice(include_bytes!("../../test/crl.pem"));
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    let lookup = store_bldr
+        .add_lookup(X509Lookup::memory().unwrap())
+        .unwrap();
+    assert_eq!(
+        lookup.add_pem_bundle(&bundle).unwrap(),
+        X509BundleCounts { certs: 1, crls: 1 }
+    );
+    assert!(lookup
+        .add_pem_bundle(b"-----BEGIN X509 CRL-----\n")
+        .is_err());
+    let mut store = store_bldr.build();
+    assert_eq!(store.objects().len(), 2);
+
+    // the fixtures have expired, so verify at a time they were valid
+    let mut param = X509VerifyParam::new().unwrap();
+    param.set_time(UNIX_EPOCH + Duration::from_secs(1_577_836_800));
+    store.set_param(&param).unwrap();
+
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let mut context = X509StoreContext::new().unwrap();
+    context.init(&store, &cert, None).unwrap();
+    assert!(context.verify_cert().unwrap());
+}
+
 #[test]
 fn test_verify_fails() {
     let cert = include_bytes!("../../test/cert.pem");
@@ -464,3 +607,258 @@ fn test_load_subject_der() {
     ];
     X509Name::from_der(SUBJECT_DER).unwrap();
 }
+
+#[test]
+fn test_crl_inspection() {
+    let crl = include_bytes!("../../test/crl.pem");
+    let crl = X509CRL::from_pem(crl).unwrap();
+
+    assert_eq!(crl.version(), 1);
+    let issuer = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    assert_eq!(
+        crl.issuer_name().to_der().unwrap(),
+        issuer.subject_name().to_der().unwrap()
+    );
+    assert!(crl.next_update().unwrap() > crl.last_update());
+    assert_eq!(
+        crl.signature_algorithm().object().nid(),
+        Nid::SHA256WITHRSAENCRYPTION
+    );
+    assert!(!crl.signature().is_empty());
+
+    let crl_number = crl.crl_number().unwrap().to_bn().unwrap();
+    assert_eq!(crl_number, BigNum::from_u32(4096).unwrap());
+    assert!(crl.delta_crl_indicator().is_none());
+
+    let idp = crl.issuing_distribution_point().unwrap();
+    let full_name = idp.full_name().unwrap();
+    assert_eq!(full_name[0].uri(), Some("http://example.com/crl.pem"));
+    assert!(idp.only_user_certs());
+    assert!(!idp.only_ca_certs());
+    assert!(!idp.indirect_crl());
+
+    let revoked = crl.revoked().unwrap();
+    assert_eq!(revoked.len(), 1);
+    let entry = &revoked[0];
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    assert_eq!(
+        entry.serial_number().to_bn().unwrap(),
+        cert.serial_number().to_bn().unwrap()
+    );
+    assert_eq!(entry.reason_code(), Some(CrlReason::KEY_COMPROMISE));
+    assert_eq!(
+        entry.invalidity_date().unwrap().to_string(),
+        "Jan  1 00:00:00 2021 GMT"
+    );
+
+    assert!(crl.verify(&issuer.public_key().unwrap()).unwrap());
+    assert!(!crl.verify(&cert.public_key().unwrap()).unwrap());
+
+    let der = crl.to_der().unwrap();
+    assert_eq!(X509CRL::from_der(&der).unwrap().to_der().unwrap(), der);
+    let pem = crl.to_pem().unwrap();
+    assert_eq!(X509CRL::from_pem(&pem).unwrap().to_der().unwrap(), der);
+}
+
+#[test]
+fn test_crl_builder() {
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let ca_key = PKey::private_key_from_pem(include_bytes!("../../test/root-ca.key")).unwrap();
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+
+    let mut revoked = X509Revoked::builder().unwrap();
+    revoked.set_serial_number(cert.serial_number()).unwrap();
+    revoked
+        .set_revocation_date(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    revoked.set_reason_code(CrlReason::SUPERSEDED).unwrap();
+    revoked
+        .set_invalidity_date(&Asn1Time::from_str("20210101000000Z").unwrap())
+        .unwrap();
+    let revoked = revoked.build();
+
+    let mut builder = X509CRL::builder().unwrap();
+    builder.set_version(1).unwrap();
+    builder.set_issuer_name(ca.subject_name()).unwrap();
+    builder
+        .set_last_update(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_next_update(&Asn1Time::days_from_now(7).unwrap())
+        .unwrap();
+    builder.add_revoked(revoked).unwrap();
+    let crl_number = CrlNumber::new(&BigNum::from_u32(42).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    builder.append_extension(crl_number).unwrap();
+    let akid = AuthorityKeyIdentifier::new()
+        .keyid(true)
+        .build(&builder.x509v3_context(&ca, None))
+        .unwrap();
+    builder.append_extension(akid).unwrap();
+    builder.sign(&ca_key, MessageDigest::sha256()).unwrap();
+    let crl = builder.build();
+
+    let crl = X509CRL::from_der(&crl.to_der().unwrap()).unwrap();
+    assert_eq!(crl.version(), 1);
+    assert!(crl.verify(&ca.public_key().unwrap()).unwrap());
+    assert_eq!(
+        crl.crl_number().unwrap().to_bn().unwrap(),
+        BigNum::from_u32(42).unwrap()
+    );
+    let entries = crl.revoked().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].serial_number().to_bn().unwrap(),
+        cert.serial_number().to_bn().unwrap()
+    );
+    assert_eq!(entries[0].reason_code(), Some(CrlReason::SUPERSEDED));
+    assert_eq!(
+        entries[0].invalidity_date().unwrap().to_string(),
+        "Jan  1 00:00:00 2021 GMT"
+    );
+}
+
+#[cfg(ossl110)]
+fn ca_and_expired_leaf() -> (X509, X509) {
+    let ca_key = pkey();
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_text("CN", "ca").unwrap();
+    let ca_name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&ca_name).unwrap();
+    builder.set_issuer_name(&ca_name).unwrap();
+    builder.set_pubkey(&ca_key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    let ca_ext = BasicConstraints::new().critical().ca().build().unwrap();
+    builder.append_extension(ca_ext).unwrap();
+    builder.sign(&ca_key, MessageDigest::sha256()).unwrap();
+    let ca = builder.build();
+
+    let key = pkey();
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_text("CN", "leaf").unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&ca_name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::from_unix(1_500_000_000).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::from_unix(1_600_000_000).unwrap())
+        .unwrap();
+    builder.sign(&ca_key, MessageDigest::sha256()).unwrap();
+    let leaf = builder.build();
+
+    (ca, leaf)
+}
+
+#[test]
+#[cfg(ossl110)]
+fn test_check_purpose_and_ca() {
+    let (ca, leaf) = ca_and_expired_leaf();
+    assert!(ca.check_ca());
+    assert!(!leaf.check_ca());
+    assert!(ca.check_purpose(X509PurposeId::SSL_SERVER, true).unwrap());
+
+    let key = pkey();
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    let eku = ExtendedKeyUsage::new().server_auth().build().unwrap();
+    builder.append_extension(eku).unwrap();
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+    let server = builder.build();
+
+    assert!(server
+        .check_purpose(X509PurposeId::SSL_SERVER, false)
+        .unwrap());
+    assert!(!server
+        .check_purpose(X509PurposeId::SMIME_SIGN, false)
+        .unwrap());
+}
+
+#[test]
+#[cfg(ossl110)]
+fn test_store_verify_callback() {
+    let (ca, leaf) = ca_and_expired_leaf();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(&ca).unwrap();
+    store_bldr
+        .set_verify_callback(|ok, ctx| {
+            ok || ctx.error().as_raw() == ffi::X509_V_ERR_CERT_HAS_EXPIRED
+        })
+        .unwrap();
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    context.init(&store, &leaf, None).unwrap();
+    assert!(context.verify_cert().unwrap());
+}
+
+#[test]
+#[cfg(ossl110)]
+fn test_context_verify_callback() {
+    use std::sync::{Arc, Mutex};
+
+    let (ca, leaf) = ca_and_expired_leaf();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(&ca).unwrap();
+    store_bldr.set_verify_callback(|ok, _| ok).unwrap();
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    context.init(&store, &leaf, None).unwrap();
+    assert!(!context.verify_cert().unwrap());
+
+    let calls = Arc::new(Mutex::new(vec![]));
+    context.cleanup();
+    context.init(&store, &leaf, None).unwrap();
+    context
+        .set_verify_callback({
+            let calls = calls.clone();
+            move |ok, ctx| {
+                let depth = ctx.error_depth();
+                let issuer = ctx
+                    .current_issuer()
+                    .and_then(|issuer| issuer.subject_name().to_der().ok());
+                calls.lock().unwrap().push((
+                    ok,
+                    depth,
+                    ctx.error().as_raw(),
+                    issuer,
+                    ctx.is_trusted(depth),
+                    ctx.current_crl().is_some(),
+                ));
+                true
+            }
+        })
+        .unwrap();
+    assert!(context.verify_cert().unwrap());
+
+    let calls = calls.lock().unwrap();
+    let ca_name = ca.subject_name().to_der().unwrap();
+    assert!(calls.contains(&(
+        false,
+        0,
+        ffi::X509_V_ERR_CERT_HAS_EXPIRED,
+        Some(ca_name.clone()),
+        false,
+        false
+    )));
+    assert!(calls.contains(&(true, 1, ffi::X509_V_OK, Some(ca_name), true, false)));
+}
@@ -1,12 +1,24 @@
+use std::error;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::mem;
 use std::net::IpAddr;
+use std::ptr;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use bitflags::bitflags;
-use foreign_types::ForeignTypeRef;
-use libc::{c_int, c_uint, c_ulong, time_t};
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_int, c_uint, c_ulong, c_void, time_t};
+use once_cell::sync::OnceCell;
 
+use crate::asn1::Asn1Object;
 use crate::error::ErrorStack;
-use crate::{cvt, cvt_p};
+use crate::stack::{Stack, StackRef};
+use crate::x509::store::X509StoreRef;
+use crate::x509::{
+    X509NameRef, X509Ref, X509StoreContext, X509StoreContextRef, X509VerifyResult, X509,
+};
+use crate::{cvt, cvt_n, cvt_p};
 
 bitflags! {
     /// Flags used to check an `X509` certificate.
@@ -60,6 +72,89 @@ bitflags! {
     }
 }
 
+/// The purpose a certificate chain is verified for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct X509PurposeId(c_int);
+
+impl X509PurposeId {
+    pub const SSL_CLIENT: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SSL_CLIENT);
+    pub const SSL_SERVER: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SSL_SERVER);
+    pub const NS_SSL_SERVER: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_NS_SSL_SERVER);
+    pub const SMIME_SIGN: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SMIME_SIGN);
+    pub const SMIME_ENCRYPT: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SMIME_ENCRYPT);
+    pub const CRL_SIGN: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_CRL_SIGN);
+    pub const ANY: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_ANY);
+    pub const OCSP_HELPER: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_OCSP_HELPER);
+    pub const TIMESTAMP_SIGN: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_TIMESTAMP_SIGN);
+    /// Requires OpenSSL 3.4.0 or newer.
+    #[cfg(ossl340)]
+    pub const CODE_SIGN: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_CODE_SIGN);
+
+    /// Constructs an `X509PurposeId` from a raw OpenSSL value.
+    pub fn from_raw(id: c_int) -> X509PurposeId {
+        X509PurposeId(id)
+    }
+
+    /// Looks up a purpose by its short name, such as `"sslserver"` or `"timestampsign"`.
+    ///
+    /// This can be used to reach purposes which are newer than the OpenSSL headers this crate
+    /// was built against. For example, `"codesign"` enforces the codeSigning extended key usage
+    /// on OpenSSL 3.4.0 and newer, and returns an error with older libraries.
+    ///
+    /// This corresponds to [`X509_PURPOSE_get_by_sname`].
+    ///
+    /// [`X509_PURPOSE_get_by_sname`]: https://www.openssl.org/docs/manmaster/man3/X509_PURPOSE_get_by_sname.html
+    pub fn from_short_name(name: &str) -> Result<X509PurposeId, ErrorStack> {
+        let unknown = || {
+            ErrorStack::internal(
+                ffi::ERR_LIB_X509V3,
+                ffi::X509V3_R_INVALID_PURPOSE,
+                "unknown certificate purpose",
+            )
+        };
+
+        let name = CString::new(name).map_err(|_| unknown())?;
+        unsafe {
+            let idx = ffi::X509_PURPOSE_get_by_sname(name.as_ptr() as *mut _);
+            if idx < 0 {
+                return Err(unknown());
+            }
+            let purpose = ffi::X509_PURPOSE_get0(idx);
+            Ok(X509PurposeId(ffi::X509_PURPOSE_get_id(purpose)))
+        }
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// The trust setting a chain's root is checked against.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct X509TrustId(c_int);
+
+impl X509TrustId {
+    pub const COMPAT: X509TrustId = X509TrustId(ffi::X509_TRUST_COMPAT);
+    pub const SSL_CLIENT: X509TrustId = X509TrustId(ffi::X509_TRUST_SSL_CLIENT);
+    pub const SSL_SERVER: X509TrustId = X509TrustId(ffi::X509_TRUST_SSL_SERVER);
+    pub const EMAIL: X509TrustId = X509TrustId(ffi::X509_TRUST_EMAIL);
+    pub const OBJECT_SIGN: X509TrustId = X509TrustId(ffi::X509_TRUST_OBJECT_SIGN);
+    pub const OCSP_SIGN: X509TrustId = X509TrustId(ffi::X509_TRUST_OCSP_SIGN);
+    pub const OCSP_REQUEST: X509TrustId = X509TrustId(ffi::X509_TRUST_OCSP_REQUEST);
+    pub const TSA: X509TrustId = X509TrustId(ffi::X509_TRUST_TSA);
+
+    /// Constructs an `X509TrustId` from a raw OpenSSL value.
+    pub fn from_raw(id: c_int) -> X509TrustId {
+        X509TrustId(id)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_VERIFY_PARAM;
     fn drop = ffi::X509_VERIFY_PARAM_free;
@@ -138,6 +233,132 @@ impl X509VerifyParamRef {
         }
     }
 
+    /// Add a DNS hostname to the set of acceptable hosts.
+    ///
+    /// Unlike `set_host`, previously configured hosts are kept, and the certificate is accepted
+    /// if it matches any of them.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_add1_host`].
+    ///
+    /// [`X509_VERIFY_PARAM_add1_host`]: https://www.openssl.org/docs/manmaster/man3/X509_VERIFY_PARAM_add1_host.html
+    pub fn add_host(&mut self, host: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_add1_host(
+                self.as_ptr(),
+                host.as_ptr() as *const _,
+                host.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the name from the certificate which matched one of the expected hosts.
+    ///
+    /// This is only set once a chain has been verified with these parameters.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_get0_peername`].
+    ///
+    /// [`X509_VERIFY_PARAM_get0_peername`]: https://www.openssl.org/docs/manmaster/man3/X509_VERIFY_PARAM_get0_peername.html
+    pub fn peername(&self) -> Option<&str> {
+        unsafe {
+            let ptr = ffi::X509_VERIFY_PARAM_get0_peername(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                CStr::from_ptr(ptr).to_str().ok()
+            }
+        }
+    }
+
+    /// Set the expected email address.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set1_email`].
+    ///
+    /// [`X509_VERIFY_PARAM_set1_email`]: https://www.openssl.org/docs/manmaster/man3/X509_VERIFY_PARAM_set1_email.html
+    pub fn set_email(&mut self, email: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set1_email(
+                self.as_ptr(),
+                email.as_ptr() as *const _,
+                email.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Set the purpose the chain is verified for.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set_purpose`].
+    ///
+    /// [`X509_VERIFY_PARAM_set_purpose`]: https://www.openssl.org/docs/manmaster/man3/X509_VERIFY_PARAM_set_purpose.html
+    pub fn set_purpose(&mut self, purpose: X509PurposeId) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set_purpose(
+                self.as_ptr(),
+                purpose.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Set the trust setting the chain's root is checked against.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set_trust`].
+    ///
+    /// [`X509_VERIFY_PARAM_set_trust`]: https://www.openssl.org/docs/manmaster/man3/X509_VERIFY_PARAM_set_trust.html
+    pub fn set_trust(&mut self, trust: X509TrustId) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set_trust(
+                self.as_ptr(),
+                trust.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds an acceptable certificate policy and enables policy checking.
+    ///
+    /// Explicit policy, inhibit any policy and inhibit policy mapping are enabled through the
+    /// corresponding `X509VerifyFlags`.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_add0_policy`].
+    ///
+    /// [`X509_VERIFY_PARAM_add0_policy`]: https://www.openssl.org/docs/manmaster/man3/X509_VERIFY_PARAM_add0_policy.html
+    pub fn add_policy(&mut self, policy: Asn1Object) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_add0_policy(
+                self.as_ptr(),
+                policy.as_ptr(),
+            ))?;
+            mem::forget(policy);
+        }
+        self.set_flags(X509VerifyFlags::POLICY_CHECK)
+    }
+
+    /// Set the authentication security level.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set_auth_level`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`X509_VERIFY_PARAM_set_auth_level`]: https://www.openssl.org/docs/manmaster/man3/X509_VERIFY_PARAM_set_auth_level.html
+    #[cfg(ossl110)]
+    pub fn set_auth_level(&mut self, level: i32) {
+        unsafe { ffi::X509_VERIFY_PARAM_set_auth_level(self.as_ptr(), level) }
+    }
+
+    /// Returns the authentication security level.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_get_auth_level`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`X509_VERIFY_PARAM_get_auth_level`]: https://www.openssl.org/docs/manmaster/man3/X509_VERIFY_PARAM_get_auth_level.html
+    #[cfg(ossl110)]
+    pub fn auth_level(&self) -> i32 {
+        unsafe { ffi::X509_VERIFY_PARAM_get_auth_level(self.as_ptr()) }
+    }
+
     /// Set the expected IPv4 or IPv6 address.
     ///
     /// This corresponds to [`X509_VERIFY_PARAM_set1_ip`].
@@ -145,18 +366,7 @@ impl X509VerifyParamRef {
     /// [`X509_VERIFY_PARAM_set1_ip`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set1_ip.html
     pub fn set_ip(&mut self, ip: IpAddr) -> Result<(), ErrorStack> {
         unsafe {
-            let mut buf = [0; 16];
-
-            let len = match ip {
-                IpAddr::V4(addr) => {
-                    buf[..4].copy_from_slice(&addr.octets());
-                    4
-                }
-                IpAddr::V6(addr) => {
-                    buf.copy_from_slice(&addr.octets());
-                    16
-                }
-            };
+            let (buf, len) = ip_octets(ip);
 
             cvt(ffi::X509_VERIFY_PARAM_set1_ip(
                 self.as_ptr(),
@@ -189,9 +399,316 @@ impl X509VerifyParamRef {
     }
 }
 
+/// Encodes `ip` in the network byte order form used for iPAddress names.
+pub(crate) fn ip_octets(ip: IpAddr) -> ([u8; 16], usize) {
+    let mut buf = [0; 16];
+    let len = match ip {
+        IpAddr::V4(addr) => {
+            buf[..4].copy_from_slice(&addr.octets());
+            4
+        }
+        IpAddr::V6(addr) => {
+            buf.copy_from_slice(&addr.octets());
+            16
+        }
+    };
+    (buf, len)
+}
+
+/// A reusable verifier for certificate chains outside of a TLS handshake.
+///
+/// Verification continues past the first error so that every problem with a chain is reported,
+/// and the verifier's parameters take precedence over those of the store.
+pub struct X509Verifier<'a> {
+    store: &'a X509StoreRef,
+    param: X509VerifyParam,
+}
+
+impl<'a> X509Verifier<'a> {
+    /// Creates a verifier that trusts the certificates of `store`.
+    pub fn new(store: &'a X509StoreRef) -> Result<X509Verifier<'a>, ErrorStack> {
+        Ok(X509Verifier {
+            store,
+            param: X509VerifyParam::new()?,
+        })
+    }
+
+    /// Returns a mutable reference to the verification parameters, such as flags and depth.
+    pub fn param_mut(&mut self) -> &mut X509VerifyParamRef {
+        &mut self.param
+    }
+
+    /// Sets the purpose the chain must be valid for.
+    pub fn set_purpose(&mut self, purpose: X509PurposeId) -> Result<(), ErrorStack> {
+        self.param.set_purpose(purpose)
+    }
+
+    /// Sets the DNS hostname the leaf certificate must match.
+    pub fn set_host(&mut self, host: &str) -> Result<(), ErrorStack> {
+        self.param.set_host(host)
+    }
+
+    /// Adds a DNS hostname the leaf certificate is accepted for, in addition to those already set.
+    ///
+    /// The leaf certificate only needs to match one of the hostnames.
+    pub fn add_host(&mut self, host: &str) -> Result<(), ErrorStack> {
+        self.param.add_host(host)
+    }
+
+    /// Sets the trust setting the chain's root is checked against.
+    pub fn set_trust(&mut self, trust: X509TrustId) -> Result<(), ErrorStack> {
+        self.param.set_trust(trust)
+    }
+
+    /// Sets the IP address the leaf certificate must match.
+    pub fn set_ip(&mut self, ip: IpAddr) -> Result<(), ErrorStack> {
+        self.param.set_ip(ip)
+    }
+
+    /// Sets the email address the leaf certificate must match.
+    pub fn set_email(&mut self, email: &str) -> Result<(), ErrorStack> {
+        self.param.set_email(email)
+    }
+
+    /// Sets the time at which the chain is verified, rather than the current time.
+    pub fn set_time(&mut self, time: SystemTime) {
+        self.param.set_time(time)
+    }
+
+    /// Verifies `leaf`, using `untrusted` as intermediates when building the chain.
+    ///
+    /// On success, the verified chain is returned, starting with the leaf and ending with the
+    /// trust anchor.
+    ///
+    /// Verification continues past errors so that all of them are reported. If the store has a
+    /// verification callback, it is still consulted for every certificate: errors it accepts are
+    /// not reported, and certificates it rejects are reported as failures. The store's callback
+    /// is only consulted on OpenSSL 1.1.0 or newer.
+    pub fn verify(
+        &self,
+        leaf: &X509Ref,
+        untrusted: &StackRef<X509>,
+    ) -> Result<Stack<X509>, X509VerifyError> {
+        let idx = verify_state_index()?;
+
+        let mut context = X509StoreContext::new()?;
+        context.init(self.store, leaf, Some(untrusted))?;
+        let mut state = VerifyState {
+            failures: vec![],
+            #[cfg(ossl110)]
+            verify_cb: unsafe { ffi::X509_STORE_CTX_get_verify_cb(context.as_ptr()) },
+        };
+        let verified = unsafe {
+            let param = ffi::X509_STORE_CTX_get0_param(context.as_ptr());
+            cvt(ffi::X509_VERIFY_PARAM_set1(param, self.param.as_ptr()))?;
+            cvt(ffi::X509_STORE_CTX_set_ex_data(
+                context.as_ptr(),
+                idx,
+                &mut state as *mut VerifyState as *mut c_void,
+            ))?;
+            ffi::X509_STORE_CTX_set_verify_cb(context.as_ptr(), Some(collect_failure));
+
+            let verified = context.verify_cert();
+            ffi::X509_STORE_CTX_set_ex_data(context.as_ptr(), idx, ptr::null_mut());
+            verified?
+        };
+
+        let mut failures = state.failures;
+        if !verified && failures.is_empty() {
+            failures.push(X509VerifyFailure::new(&context));
+        }
+        if !failures.is_empty() {
+            return Err(X509VerifyError {
+                failures,
+                error_stack: None,
+            });
+        }
+
+        let mut chain = Stack::new()?;
+        if let Some(verified) = context.chain() {
+            for cert in verified {
+                chain.push(cert.to_owned())?;
+            }
+        }
+        Ok(chain)
+    }
+}
+
+static VERIFY_STATE_INDEX: OnceCell<c_int> = OnceCell::new();
+
+fn verify_state_index() -> Result<c_int, ErrorStack> {
+    VERIFY_STATE_INDEX
+        .get_or_try_init(|| unsafe {
+            ffi::init();
+            cvt_n(ffi::X509_STORE_CTX_get_ex_new_index(
+                0,
+                ptr::null_mut(),
+                None,
+                None,
+                None,
+            ))
+        })
+        .copied()
+}
+
+/// The state shared with `collect_failure` while `X509Verifier::verify` runs.
+struct VerifyState {
+    failures: Vec<X509VerifyFailure>,
+    #[cfg(ossl110)]
+    verify_cb: ffi::X509_STORE_CTX_verify_cb,
+}
+
+extern "C" fn collect_failure(ok: c_int, ctx: *mut ffi::X509_STORE_CTX) -> c_int {
+    unsafe {
+        let state = match VERIFY_STATE_INDEX.get() {
+            Some(idx) => ffi::X509_STORE_CTX_get_ex_data(ctx, *idx) as *mut VerifyState,
+            None => return ok,
+        };
+        let state = match state.as_mut() {
+            Some(state) => state,
+            None => return ok,
+        };
+
+        // chain to the store's callback, which may override errors or reject certificates
+        #[cfg(ossl110)]
+        let ok = match state.verify_cb {
+            Some(verify_cb) => verify_cb(ok, ctx),
+            None => ok,
+        };
+
+        if ok == 0 {
+            let failure = X509VerifyFailure::new(X509StoreContextRef::from_ptr(ctx));
+            state.failures.push(failure);
+        }
+    }
+    1
+}
+
+/// A single problem found while verifying a certificate chain.
+#[derive(Clone)]
+pub struct X509VerifyFailure {
+    depth: u32,
+    cert: Option<X509>,
+    error: X509VerifyResult,
+}
+
+impl X509VerifyFailure {
+    fn new(context: &X509StoreContextRef) -> X509VerifyFailure {
+        X509VerifyFailure {
+            depth: context.error_depth(),
+            cert: context.current_cert().map(|cert| cert.to_owned()),
+            error: context.error(),
+        }
+    }
+
+    /// Returns the depth in the chain of the certificate at fault, the leaf being at depth 0.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Returns the certificate at fault, if any.
+    pub fn cert(&self) -> Option<&X509Ref> {
+        self.cert.as_deref()
+    }
+
+    /// Returns the subject name of the certificate at fault, if any.
+    pub fn subject(&self) -> Option<&X509NameRef> {
+        self.cert().map(|cert| cert.subject_name())
+    }
+
+    /// Returns the reason for the failure.
+    pub fn error(&self) -> X509VerifyResult {
+        self.error
+    }
+}
+
+impl fmt::Debug for X509VerifyFailure {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("X509VerifyFailure")
+            .field("depth", &self.depth)
+            .field("subject", &self.subject())
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl fmt::Display for X509VerifyFailure {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "depth {}", self.depth)?;
+        if let Some(subject) = self.subject() {
+            fmt.write_str(" (")?;
+            for (i, entry) in subject.entries().enumerate() {
+                if i > 0 {
+                    fmt.write_str(", ")?;
+                }
+                let key = entry.object().nid().short_name().unwrap_or("?");
+                match entry.data().as_utf8() {
+                    Ok(value) => write!(fmt, "{}={}", key, value)?,
+                    Err(_) => write!(fmt, "{}=?", key)?,
+                }
+            }
+            fmt.write_str(")")?;
+        }
+        write!(fmt, ": {}", self.error.error_string())
+    }
+}
+
+/// An error returned by `X509Verifier::verify`.
+#[derive(Debug)]
+pub struct X509VerifyError {
+    failures: Vec<X509VerifyFailure>,
+    error_stack: Option<ErrorStack>,
+}
+
+impl X509VerifyError {
+    /// Returns every problem found with the chain, in the order OpenSSL reported them.
+    ///
+    /// This is empty if verification could not be carried out at all.
+    pub fn failures(&self) -> &[X509VerifyFailure] {
+        &self.failures
+    }
+
+    /// Returns the OpenSSL error stack if verification could not be carried out at all.
+    pub fn error_stack(&self) -> Option<&ErrorStack> {
+        self.error_stack.as_ref()
+    }
+}
+
+impl From<ErrorStack> for X509VerifyError {
+    fn from(e: ErrorStack) -> X509VerifyError {
+        X509VerifyError {
+            failures: vec![],
+            error_stack: Some(e),
+        }
+    }
+}
+
+impl fmt::Display for X509VerifyError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(e) = &self.error_stack {
+            return write!(fmt, "certificate verification error: {}", e);
+        }
+        fmt.write_str("certificate verification failed")?;
+        for (i, failure) in self.failures.iter().enumerate() {
+            fmt.write_str(if i == 0 { ": " } else { "; " })?;
+            write!(fmt, "{}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for X509VerifyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.error_stack
+            .as_ref()
+            .map(|e| e as &(dyn error::Error + 'static))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::x509::store::X509StoreBuilder;
 
     #[test]
     fn get_set_verify_time() {
@@ -213,4 +730,164 @@ mod tests {
         param.set_depth(depth);
         assert_eq!(depth, param.get_depth());
     }
+
+    fn verifier_store() -> crate::x509::store::X509Store {
+        let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(&ca).unwrap();
+        store.build()
+    }
+
+    #[test]
+    fn verifier_builds_chain() {
+        let leaf = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+        let store = verifier_store();
+
+        let mut verifier = X509Verifier::new(&store).unwrap();
+        verifier.set_purpose(X509PurposeId::SSL_SERVER).unwrap();
+        verifier.set_trust(X509TrustId::SSL_SERVER).unwrap();
+        verifier.set_host("foobar.com").unwrap();
+        verifier.set_time(UNIX_EPOCH + Duration::from_secs(1_577_836_800));
+
+        let chain = verifier.verify(&leaf, &Stack::new().unwrap()).unwrap();
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0].to_der().unwrap(), leaf.to_der().unwrap());
+    }
+
+    #[test]
+    fn verifier_accepts_any_added_host() {
+        let leaf = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+        let store = verifier_store();
+
+        let mut verifier = X509Verifier::new(&store).unwrap();
+        verifier.set_host("example.com").unwrap();
+        verifier.add_host("foobar.com").unwrap();
+        verifier.set_time(UNIX_EPOCH + Duration::from_secs(1_577_836_800));
+        verifier.verify(&leaf, &Stack::new().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn peername_after_verify() {
+        let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+        let leaf = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+
+        let mut param = X509VerifyParam::new().unwrap();
+        param.add_host("example.com").unwrap();
+        param.add_host("foobar.com").unwrap();
+        param.set_time(UNIX_EPOCH + Duration::from_secs(1_577_836_800));
+        assert_eq!(param.peername(), None);
+
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(&ca).unwrap();
+        let mut store = store.build();
+        store.set_param(&param).unwrap();
+
+        let mut context = X509StoreContext::new().unwrap();
+        context.init(&store, &leaf, None).unwrap();
+        assert!(context.verify_cert().unwrap());
+        assert_eq!(context.get_param().peername(), Some("foobar.com"));
+    }
+
+    #[test]
+    fn purpose_from_short_name() {
+        assert_eq!(
+            X509PurposeId::from_short_name("sslserver").unwrap(),
+            X509PurposeId::SSL_SERVER
+        );
+        assert_eq!(
+            X509PurposeId::from_short_name("timestampsign").unwrap(),
+            X509PurposeId::TIMESTAMP_SIGN
+        );
+        #[cfg(ossl340)]
+        assert_eq!(
+            X509PurposeId::from_short_name("codesign").unwrap(),
+            X509PurposeId::CODE_SIGN
+        );
+        assert!(X509PurposeId::from_short_name("bogus").is_err());
+        assert!(X509PurposeId::from_short_name("sslserver\0").is_err());
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn get_set_auth_level() {
+        let mut param = X509VerifyParam::new().unwrap();
+        param.set_auth_level(2);
+        assert_eq!(param.auth_level(), 2);
+    }
+
+    #[test]
+    fn verifier_requires_explicit_policy() {
+        let leaf = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+        let store = verifier_store();
+
+        let mut verifier = X509Verifier::new(&store).unwrap();
+        verifier.set_time(UNIX_EPOCH + Duration::from_secs(1_577_836_800));
+        let param = verifier.param_mut();
+        param
+            .add_policy(Asn1Object::from_str("1.3.6.1.4.1.55555.1").unwrap())
+            .unwrap();
+        param.set_flags(X509VerifyFlags::EXPLICIT_POLICY).unwrap();
+
+        let err = verifier.verify(&leaf, &Stack::new().unwrap()).unwrap_err();
+        assert!(err
+            .failures()
+            .iter()
+            .any(|f| f.error().as_raw() == ffi::X509_V_ERR_NO_EXPLICIT_POLICY));
+    }
+
+    #[test]
+    fn verifier_reports_every_failure() {
+        let leaf = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+        let store = verifier_store();
+
+        let mut verifier = X509Verifier::new(&store).unwrap();
+        verifier.set_host("example.com").unwrap();
+        verifier.set_time(UNIX_EPOCH + Duration::from_secs(1_893_456_000));
+
+        let err = verifier.verify(&leaf, &Stack::new().unwrap()).unwrap_err();
+        assert!(err.error_stack().is_none());
+        let failures = err
+            .failures()
+            .iter()
+            .map(|f| (f.depth(), f.error().as_raw()))
+            .collect::<Vec<_>>();
+        assert!(failures.contains(&(0, ffi::X509_V_ERR_CERT_HAS_EXPIRED)));
+        assert!(failures.contains(&(1, ffi::X509_V_ERR_CERT_HAS_EXPIRED)));
+        assert!(failures.contains(&(0, ffi::X509_V_ERR_HOSTNAME_MISMATCH)));
+
+        let leaf_failure = err.failures().iter().find(|f| f.depth() == 0).unwrap();
+        assert_eq!(
+            leaf_failure.subject().unwrap().to_der().unwrap(),
+            leaf.subject_name().to_der().unwrap()
+        );
+        assert!(err.to_string().contains("CN=foobar.com"));
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn verifier_consults_store_callback() {
+        let leaf = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+        let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(&ca).unwrap();
+        store
+            .set_verify_callback(|ok, ctx| {
+                ok || ctx.error().as_raw() == ffi::X509_V_ERR_CERT_HAS_EXPIRED
+            })
+            .unwrap();
+        let store = store.build();
+
+        let mut verifier = X509Verifier::new(&store).unwrap();
+        verifier.set_time(UNIX_EPOCH + Duration::from_secs(1_893_456_000));
+        verifier.verify(&leaf, &Stack::new().unwrap()).unwrap();
+
+        verifier.set_host("example.com").unwrap();
+        let err = verifier.verify(&leaf, &Stack::new().unwrap()).unwrap_err();
+        let failures = err
+            .failures()
+            .iter()
+            .map(|f| f.error().as_raw())
+            .collect::<Vec<_>>();
+        assert_eq!(failures, [ffi::X509_V_ERR_HOSTNAME_MISMATCH]);
+    }
 }
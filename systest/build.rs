@@ -34,7 +34,8 @@ fn main() {
         .ok()
         .map(|v| u64::from_str_radix(&v, 16).unwrap());
 
-    for c in cfgs::get(openssl_version, libressl_version) {
+    let cfgs = cfgs::get(openssl_version, libressl_version);
+    for c in &cfgs {
         cfg.cfg(c, None);
     }
 
@@ -68,6 +69,14 @@ fn main() {
         cfg.header("openssl/cms.h");
     }
 
+    if cfgs.contains(&"ossl110") {
+        cfg.header("openssl/kdf.h");
+    }
+
+    if cfgs.contains(&"ossl300") {
+        cfg.header("openssl/params.h").header("openssl/provider.h");
+    }
+
     #[allow(clippy::if_same_then_else)]
     cfg.type_name(|s, is_struct, _is_union| {
         // Add some `*` on some callback parameters to get function pointer to
@@ -119,6 +128,8 @@ fn main() {
     cfg.skip_field_type(|s, field| {
         (s == "EVP_PKEY" && field == "pkey") ||      // union
             (s == "GENERAL_NAME" && field == "d") || // union
+            (s == "DIST_POINT_NAME" && field == "name") || // union
+            (s == "PKCS7" && field == "d") || // union
             (s == "X509_OBJECT" && field == "data") // union
     });
     cfg.skip_signededness(|s| {